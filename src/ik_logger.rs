@@ -0,0 +1,124 @@
+// ik_logger.rs
+
+use anyhow::Result;
+use rerun::RecordingStream;
+
+use crate::ik_solver::{IkSolution, IkTarget};
+use crate::krec_logger::log_joint_transform;
+use crate::robot_model::RobotModel;
+
+/// Timeline stepping through the IK solver's iterations.
+pub const IK_ITERATION_TIMELINE: &str = "ik_iteration";
+/// Color of the IK target.
+const IK_TARGET_RGBA: [u8; 4] = [0, 200, 255, 255];
+/// Color of the target link's position and path while solving.
+const IK_LINK_RGBA: [u8; 4] = [255, 200, 0, 255];
+/// Length of the target's orientation axes.
+const IK_AXES_LENGTH: f32 = 0.1;
+
+/// Log the IK target under `ik/target`, as a point and, with an orientation, x/y/z axes.
+fn log_ik_target(rec: &RecordingStream, target: &IkTarget) -> Result<()> {
+    let position = target.position.map(|v| v as f32);
+    rec.log_static(
+        "ik/target",
+        &rerun::archetypes::Points3D::new([position])
+            .with_radii([0.02])
+            .with_colors([IK_TARGET_RGBA])
+            .with_labels([format!("{} target", target.link)]),
+    )?;
+
+    if let Some(orientation) = &target.orientation {
+        let axes: Vec<[f32; 3]> = (0..3)
+            .map(|i| {
+                let axis = orientation * nalgebra::Vector3::ith(i, IK_AXES_LENGTH as f64);
+                [axis.x as f32, axis.y as f32, axis.z as f32]
+            })
+            .collect();
+        rec.log_static(
+            "ik/target/axes",
+            &rerun::archetypes::Arrows3D::from_vectors(axes)
+                .with_origins([position; 3])
+                .with_colors([[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]]),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Log an IK solve on the `ik_iteration` timeline: the robot posed at each iteration, the
+/// target link's position and path toward the target, and the remaining errors under
+/// `ik/`. A text event under `ik/result` tells whether the target was reached.
+pub fn log_ik_solution(
+    model: &RobotModel,
+    target: &IkTarget,
+    solution: &IkSolution,
+    rec: &RecordingStream,
+) -> Result<()> {
+    log_ik_target(rec, target)?;
+
+    let joint_info_map = model.joint_info_map();
+    let mut link_path: Vec<[f32; 3]> = Vec::with_capacity(solution.trace.len());
+
+    for (iteration_idx, iteration) in solution.trace.iter().enumerate() {
+        rec.set_time_sequence(IK_ITERATION_TIMELINE, iteration_idx as i64);
+
+        for (joint_name, joint_position) in &iteration.joint_positions {
+            if let Some(joint_info) = joint_info_map.get(joint_name) {
+                log_joint_transform(rec, "", joint_info, *joint_position)?;
+            }
+        }
+
+        let link_position = iteration.link_pose.translation.vector.map(|v| v as f32);
+        link_path.push([link_position.x, link_position.y, link_position.z]);
+        rec.log(
+            "ik/link_position",
+            &rerun::archetypes::Points3D::new([link_path[link_path.len() - 1]])
+                .with_radii([0.015])
+                .with_colors([IK_LINK_RGBA]),
+        )?;
+        rec.log(
+            "ik/link_path",
+            &rerun::archetypes::LineStrips3D::new([link_path.clone()]).with_colors([IK_LINK_RGBA]),
+        )?;
+
+        rec.log(
+            "ik/position_error",
+            &rerun::components::Scalar::from(iteration.position_error),
+        )?;
+        if target.orientation.is_some() {
+            rec.log(
+                "ik/orientation_error",
+                &rerun::components::Scalar::from(iteration.orientation_error),
+            )?;
+        }
+    }
+
+    let final_iteration = solution.final_iteration();
+    let iterations = solution.trace.len() - 1;
+    let (msg, level) = if solution.converged {
+        (
+            format!(
+                "Reached the {} target in {} iterations",
+                target.link, iterations
+            ),
+            rerun::TextLogLevel::INFO,
+        )
+    } else {
+        (
+            format!(
+                "Could not reach the {} target in {} iterations: {:.4} m / {:.4} rad off",
+                target.link,
+                iterations,
+                final_iteration.position_error,
+                final_iteration.orientation_error
+            ),
+            rerun::TextLogLevel::WARN,
+        )
+    };
+    rec.log(
+        "ik/result",
+        &rerun::archetypes::TextLog::new(msg).with_level(level),
+    )?;
+
+    Ok(())
+}
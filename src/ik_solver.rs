@@ -0,0 +1,227 @@
+// ik_solver.rs
+
+use anyhow::{anyhow, bail, Result};
+use nalgebra as na;
+use std::collections::HashMap;
+use urdf_rs::JointType;
+
+use crate::robot_model::RobotModel;
+use crate::utils::urdf_bfs_utils::{resolve_mimic_positions, JointInfo};
+
+/// Pose to reach with a link, relative to the root link.
+#[derive(Debug, Clone)]
+pub struct IkTarget {
+    /// Link to move onto the target, e.g. a hand or foot
+    pub link: String,
+    pub position: [f64; 3],
+    /// Orientation to reach as well; position-only when `None`
+    pub orientation: Option<na::UnitQuaternion<f64>>,
+}
+
+/// Parameters of the damped-least-squares solver.
+#[derive(Debug, Clone)]
+pub struct IkOptions {
+    pub max_iterations: usize,
+    /// Damping factor λ: larger values are more stable near singularities but slower
+    pub damping: f64,
+    /// Position error (m) under which the target counts as reached
+    pub position_tolerance: f64,
+    /// Orientation error (rad) under which the target counts as reached
+    pub orientation_tolerance: f64,
+    /// Largest joint-space step per iteration (norm, rad/m)
+    pub max_step: f64,
+    /// Starting joint positions; other joints start at zero (clamped to their limits)
+    pub initial_positions: HashMap<String, f64>,
+}
+
+impl Default for IkOptions {
+    fn default() -> Self {
+        IkOptions {
+            max_iterations: 100,
+            damping: 0.05,
+            position_tolerance: 1e-4,
+            orientation_tolerance: 1e-3,
+            max_step: 0.2,
+            initial_positions: HashMap::new(),
+        }
+    }
+}
+
+/// State of the solver at one iteration.
+#[derive(Debug, Clone)]
+pub struct IkIteration {
+    /// Positions of all joints set so far, mimic joints included
+    pub joint_positions: HashMap<String, f64>,
+    /// Pose of the target link relative to the root link
+    pub link_pose: na::Isometry3<f64>,
+    pub position_error: f64,
+    pub orientation_error: f64,
+}
+
+/// Result of an IK solve; the last iteration holds the solved pose.
+#[derive(Debug, Clone)]
+pub struct IkSolution {
+    pub converged: bool,
+    /// Every iteration, starting with the initial pose
+    pub trace: Vec<IkIteration>,
+}
+
+impl IkSolution {
+    pub fn final_iteration(&self) -> &IkIteration {
+        self.trace
+            .last()
+            .expect("the trace holds at least the initial pose")
+    }
+
+    /// Solved positions of all joints, mimic joints included.
+    pub fn joint_positions(&self) -> &HashMap<String, f64> {
+        &self.final_iteration().joint_positions
+    }
+}
+
+/// Clamp a position to the joint's URDF limits, if it has a position range.
+fn clamp_to_limits(info: &JointInfo, position: f64) -> f64 {
+    let bounded = matches!(info.joint_type, JointType::Revolute | JointType::Prismatic);
+    if bounded && info.limit.upper > info.limit.lower {
+        position.clamp(info.limit.lower, info.limit.upper)
+    } else {
+        position
+    }
+}
+
+/// Move `target.link` onto the target by damped least squares over the joints between
+/// the root link and it.
+///
+/// Each iteration steps the joints by `Jᵀ (J Jᵀ + λ² I)⁻¹ e`, with `J` the geometric
+/// Jacobian of the link and `e` its position (and orientation) error, then clamps them to
+/// their limits. Mimic joints in the chain move with the joint they follow. Returns the
+/// best effort with `converged == false` when the target is out of reach.
+pub fn solve_ik(model: &RobotModel, target: &IkTarget, options: &IkOptions) -> Result<IkSolution> {
+    let joint_info_map = model.joint_info_map();
+    let chain = model
+        .joint_chain(&target.link)
+        .ok_or_else(|| anyhow!("IK target link {:?} is not in the robot", target.link))?;
+
+    // The solver's variables: joints moved directly, not through a mimic
+    let variables: Vec<&str> = chain
+        .iter()
+        .copied()
+        .filter(|joint| {
+            let info = &joint_info_map[*joint];
+            info.is_animated() && info.mimic.is_none()
+        })
+        .collect();
+    if variables.is_empty() {
+        bail!(
+            "No movable joint between {:?} and IK target link {:?}",
+            model.root_link(),
+            target.link
+        );
+    }
+
+    // Variable driving each moving joint of the chain, and the gain it is driven with
+    let drivers: Vec<(&JointInfo, usize, f64)> = chain
+        .iter()
+        .filter_map(|joint| {
+            let info = &joint_info_map[*joint];
+            if !info.is_animated() {
+                return None;
+            }
+            let (mut leader, mut gain) = (*joint, 1.0);
+            while let Some(mimic) = joint_info_map.get(leader).and_then(|j| j.mimic.as_ref()) {
+                gain *= mimic.multiplier;
+                leader = &mimic.joint;
+            }
+            let index = variables.iter().position(|v| *v == leader)?;
+            Some((info, index, gain))
+        })
+        .collect();
+
+    let mut positions: HashMap<String, f64> = options.initial_positions.clone();
+    for variable in &variables {
+        let position = positions.get(*variable).copied().unwrap_or(0.0);
+        positions.insert(
+            variable.to_string(),
+            clamp_to_limits(&joint_info_map[*variable], position),
+        );
+    }
+
+    let target_position = na::Vector3::from(target.position);
+    let rows = if target.orientation.is_some() { 6 } else { 3 };
+    let mut trace = Vec::new();
+    let mut converged = false;
+
+    for iteration in 0..=options.max_iterations {
+        let mut joint_positions = positions.clone();
+        joint_positions.extend(resolve_mimic_positions(joint_info_map, &positions));
+        let poses = model.forward_kinematics(&joint_positions);
+        let link_pose = poses[&target.link];
+
+        let position_error = target_position - link_pose.translation.vector;
+        let orientation_error = target
+            .orientation
+            .map(|q| (q * link_pose.rotation.inverse()).scaled_axis())
+            .unwrap_or_else(na::Vector3::zeros);
+
+        trace.push(IkIteration {
+            joint_positions,
+            link_pose,
+            position_error: position_error.norm(),
+            orientation_error: orientation_error.norm(),
+        });
+        converged = position_error.norm() < options.position_tolerance
+            && orientation_error.norm() < options.orientation_tolerance;
+        if converged || iteration == options.max_iterations {
+            break;
+        }
+
+        // Geometric Jacobian of the target link's origin
+        let mut jacobian = na::DMatrix::<f64>::zeros(rows, variables.len());
+        for (info, index, gain) in &drivers {
+            let Some(axis) = na::Unit::try_new(na::Vector3::from(info.axis), 1e-9) else {
+                continue;
+            };
+            let joint_pose = poses[&info.child_link];
+            let axis = joint_pose.rotation * axis.into_inner();
+            let (linear, angular) = match info.joint_type {
+                JointType::Prismatic => (axis, na::Vector3::zeros()),
+                _ => {
+                    let lever = link_pose.translation.vector - joint_pose.translation.vector;
+                    (axis.cross(&lever), axis)
+                }
+            };
+            for row in 0..3 {
+                jacobian[(row, *index)] += gain * linear[row];
+                if rows == 6 {
+                    jacobian[(row + 3, *index)] += gain * angular[row];
+                }
+            }
+        }
+
+        let mut error = na::DVector::<f64>::zeros(rows);
+        error.fixed_rows_mut::<3>(0).copy_from(&position_error);
+        if rows == 6 {
+            error.fixed_rows_mut::<3>(3).copy_from(&orientation_error);
+        }
+
+        let damped = &jacobian * jacobian.transpose()
+            + na::DMatrix::identity(rows, rows) * options.damping.powi(2);
+        let Some(solved) = damped.lu().solve(&error) else {
+            break;
+        };
+        let mut step = jacobian.transpose() * solved;
+        if step.norm() > options.max_step {
+            step *= options.max_step / step.norm();
+        }
+
+        for (variable, delta) in variables.iter().zip(step.iter()) {
+            let position = positions[*variable] + delta;
+            positions.insert(
+                variable.to_string(),
+                clamp_to_limits(&joint_info_map[*variable], position),
+            );
+        }
+    }
+
+    Ok(IkSolution { converged, trace })
+}
@@ -1,117 +1,802 @@
 // krec_logger.rs
 
-use anyhow::Result;
-use krec::KRec;
+use anyhow::{bail, Result};
+use krec::{ActuatorCommand, ActuatorState, ImuValues, KRec};
 use log::info;
+use nalgebra::{Isometry3, Point3, Translation3, UnitQuaternion, Vector3};
 use rerun::RecordingStream;
-use std::collections::HashMap;
-use std::f64::consts::PI;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
 
+use crate::robot_model::RobotModel;
+use crate::urdf_logger::{
+    load_robot_description, log_urdf_hierarchy, visual_entity_paths, RobotDescription,
+    UrdfLogOptions, CENTER_OF_MASS_RGBA,
+};
+use crate::utils::base_pose_utils::{BasePoseTrack, LegOdometry, PoseKey};
 use crate::utils::debug_log_utils::{debug_log_actuator_state, debug_log_rerun_transform};
-use crate::utils::spatial_transform_utils::{
-    build_z_rotation_3x3, decompose_4x4_to_translation_and_mat3x3,
-    make_4x4_from_rotation_and_translation, mat3x3_mul,
+use crate::utils::inertial_utils::{combined_center_of_mass, mass_point_radius, LinkInertial};
+use crate::utils::joint_limit_utils::{
+    check_joint_limits, JointSample, LimitCheck, LimitKind, LimitStatus, LimitViolationSummary,
+};
+use crate::utils::joint_map_utils::{
+    default_actuator_to_urdf_joint_map, load_actuator_joint_map, recorded_position_to_joint_units,
+    validate_actuator_joint_map,
 };
-use crate::utils::urdf_bfs_utils::build_joint_name_to_joint_info;
-
-// -----------------------------------------------------------------------------
-// Actuator -> Joint map
-// -----------------------------------------------------------------------------
-fn build_actuator_to_urdf_joint_map() -> HashMap<u32, &'static str> {
-    let mut map = HashMap::new();
-    // Left Arm
-    map.insert(11, "Revolute_2");
-    map.insert(12, "Revolute_4");
-    map.insert(13, "Revolute_5");
-    map.insert(14, "Revolute_8");
-    map.insert(15, "Revolute_16");
-    // Right Arm
-    map.insert(21, "Revolute_1");
-    map.insert(22, "Revolute_3");
-    map.insert(23, "Revolute_6");
-    map.insert(24, "Revolute_7");
-    map.insert(25, "Revolute_15");
-    // Left Leg
-    map.insert(31, "L_hip_y");
-    map.insert(32, "L_hip_x");
-    map.insert(33, "L_hip_z");
-    map.insert(34, "L_knee");
-    map.insert(35, "L_ankle_y");
-    // Right Leg
-    map.insert(41, "R_hip_y");
-    map.insert(42, "R_hip_x");
-    map.insert(43, "R_hip_z");
-    map.insert(44, "R_knee");
-    map.insert(45, "R_ankle_y");
-
-    map
-}
-
-/// Log basic scalar values for an actuator (like position, velocity, torque) if present.
+use crate::utils::krec_utils::{
+    actuator_config_markdown, has_inference_steps, header_markdown, set_frame_timelines,
+    ActuatorThresholds, FRAME_IDX_TIMELINE,
+};
+use crate::utils::spatial_transform_utils::decompose_4x4_to_translation_and_mat3x3;
+use crate::utils::trajectory_utils::{trail_color, Trail};
+use crate::utils::urdf_bfs_utils::{join_entity_path, resolve_mimic_positions, JointInfo};
+
+/// Entity prefix of the translucent robot posed from actuator commands.
+const COMMAND_GHOST_PREFIX: &str = "command_ghost";
+/// Tint of the command ghost robot.
+const COMMAND_GHOST_RGBA: [u8; 4] = [80, 160, 255, 90];
+
+/// Log the scalar values of an actuator state (position, velocity, torque, temperature,
+/// voltage, current and online status) that are present.
 fn log_actuator_states(
     rec: &RecordingStream,
     frame_idx: usize,
-    actuator_id: u32,
-    position: Option<f64>,
-    velocity: Option<f64>,
-    torque: Option<f64>,
+    state: &ActuatorState,
 ) -> Result<()> {
+    let actuator_id = state.actuator_id;
+
     // Add debug logging
-    debug_log_actuator_state(frame_idx, actuator_id, position, velocity, torque);
+    debug_log_actuator_state(
+        frame_idx,
+        actuator_id,
+        state.position,
+        state.velocity,
+        state.torque,
+    );
 
-    rec.set_time_sequence("frame_idx", frame_idx as i64);
+    rec.set_time_sequence(FRAME_IDX_TIMELINE, frame_idx as i64);
 
     let base_path = format!("actuators/actuator_{}/state", actuator_id);
 
-    if let Some(pos) = position {
+    let fields = [
+        ("position", state.position),
+        ("velocity", state.velocity),
+        ("torque", state.torque),
+        ("temperature", state.temperature),
+        ("voltage", state.voltage.map(f64::from)),
+        ("current", state.current.map(f64::from)),
+        ("online", Some(if state.online { 1.0 } else { 0.0 })),
+    ];
+
+    for (name, value) in fields {
+        if let Some(value) = value {
+            rec.log(
+                format!("{}/{}", base_path, name),
+                &rerun::components::Scalar::from(value),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Health of an actuator carried between frames, so events are only logged on changes.
+#[derive(Debug, Default)]
+struct ActuatorHealth {
+    offline: bool,
+    violations: HashSet<&'static str>,
+}
+
+/// Log text events when an actuator goes offline/online or crosses a health threshold.
+fn log_actuator_health(
+    rec: &RecordingStream,
+    state: &ActuatorState,
+    thresholds: &ActuatorThresholds,
+    health: &mut ActuatorHealth,
+    track_online: bool,
+) -> Result<()> {
+    let actuator_id = state.actuator_id;
+    let events_path = format!("actuators/actuator_{}/events", actuator_id);
+
+    if track_online && state.online == health.offline {
+        health.offline = !state.online;
+        let (msg, level) = if health.offline {
+            (
+                format!("Actuator {actuator_id} went offline"),
+                rerun::TextLogLevel::WARN,
+            )
+        } else {
+            (
+                format!("Actuator {actuator_id} is back online"),
+                rerun::TextLogLevel::INFO,
+            )
+        };
         rec.log(
-            format!("{}/position", base_path),
-            &rerun::components::Scalar::from(pos),
+            events_path.as_str(),
+            &rerun::archetypes::TextLog::new(msg).with_level(level),
         )?;
     }
-    if let Some(vel) = velocity {
+
+    let violations = thresholds.violations(state);
+    let current: HashSet<&'static str> = violations.iter().map(|(check, _)| *check).collect();
+    for (check, msg) in violations {
+        if !health.violations.contains(check) {
+            log::warn!("{}", msg);
+            rec.log(
+                events_path.as_str(),
+                &rerun::archetypes::TextLog::new(msg).with_level(rerun::TextLogLevel::WARN),
+            )?;
+        }
+    }
+    health.violations = current;
+
+    Ok(())
+}
+
+/// Limit state of a joint carried between frames, so events are only logged on changes.
+#[derive(Debug, Default)]
+struct JointLimitState {
+    status: LimitStatus,
+    exceeded: HashSet<LimitKind>,
+}
+
+/// Entity under which joint-limit events and the end-of-run summary are logged.
+const JOINT_LIMIT_VIOLATIONS_PATH: &str = "joint_limit_violations";
+
+/// Log text events when a joint starts exceeding a limit, gets near its limits or is back
+/// within them.
+fn log_joint_limit_check(
+    rec: &RecordingStream,
+    joint_name: &str,
+    check: &LimitCheck,
+    state: &mut JointLimitState,
+) -> Result<()> {
+    let events_path = format!("{}/{}/events", JOINT_LIMIT_VIOLATIONS_PATH, joint_name);
+
+    for violation in &check.violations {
+        if !state.exceeded.contains(&violation.kind) {
+            let msg = violation.message(joint_name);
+            log::warn!("{}", msg);
+            rec.log(
+                events_path.as_str(),
+                &rerun::archetypes::TextLog::new(msg).with_level(rerun::TextLogLevel::WARN),
+            )?;
+        }
+    }
+    state.exceeded = check.violations.iter().map(|v| v.kind).collect();
+
+    if check.status == state.status {
+        return Ok(());
+    }
+    let msg = match (state.status, check.status) {
+        (LimitStatus::Exceeded, _) => Some(format!("Joint {joint_name} is back within its limits")),
+        (LimitStatus::Within, LimitStatus::NearLimit) => {
+            Some(format!("Joint {joint_name} is near its limits"))
+        }
+        _ => None,
+    };
+    if let Some(msg) = msg {
+        rec.log(
+            events_path.as_str(),
+            &rerun::archetypes::TextLog::new(msg).with_level(rerun::TextLogLevel::INFO),
+        )?;
+    }
+    state.status = check.status;
+
+    Ok(())
+}
+
+/// Highlight of a link's meshes, in increasing order of priority: a link driven by an
+/// offline actuator whose joint is also past a limit shows as offline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+enum LinkTint {
+    #[default]
+    Neutral,
+    NearLimit,
+    LimitExceeded,
+    Offline,
+}
+
+impl LinkTint {
+    /// Albedo factor multiplied into the link's own colors.
+    fn rgba(self) -> [u8; 4] {
+        match self {
+            LinkTint::Neutral => [255, 255, 255, 255],
+            LinkTint::NearLimit => [255, 170, 0, 255],
+            LinkTint::LimitExceeded => [200, 60, 255, 255],
+            LinkTint::Offline => [255, 60, 60, 255],
+        }
+    }
+}
+
+impl From<LimitStatus> for LinkTint {
+    fn from(status: LimitStatus) -> Self {
+        match status {
+            LimitStatus::Exceeded => LinkTint::LimitExceeded,
+            LimitStatus::NearLimit => LinkTint::NearLimit,
+            LimitStatus::Within => LinkTint::Neutral,
+        }
+    }
+}
+
+/// Tint every link visual from the current actuator health and joint limit states,
+/// logging only the ones that changed since the last frame.
+fn log_link_tints(
+    rec: &RecordingStream,
+    actuator_health: &HashMap<u32, ActuatorHealth>,
+    actuator_visual_paths: &HashMap<u32, Vec<String>>,
+    joint_limit_states: &HashMap<String, JointLimitState>,
+    joint_visual_paths: &HashMap<String, Vec<String>>,
+    logged_tints: &mut HashMap<String, LinkTint>,
+) -> Result<()> {
+    let offline = actuator_health
+        .iter()
+        .filter(|(_, health)| health.offline)
+        .filter_map(|(actuator_id, _)| actuator_visual_paths.get(actuator_id))
+        .map(|paths| (paths, LinkTint::Offline));
+    let limits = joint_limit_states.iter().filter_map(|(joint_name, state)| {
+        Some((
+            joint_visual_paths.get(joint_name)?,
+            LinkTint::from(state.status),
+        ))
+    });
+    let mut tints: HashMap<&str, LinkTint> = HashMap::new();
+    for (paths, tint) in offline.chain(limits) {
+        for path in paths {
+            let entry = tints.entry(path.as_str()).or_default();
+            *entry = (*entry).max(tint);
+        }
+    }
+
+    // Links tinted before, including ones going back to neutral
+    for (path, logged) in logged_tints.iter_mut() {
+        let tint = tints.remove(path.as_str()).unwrap_or_default();
+        if tint != *logged {
+            rec.log(
+                path.as_str(),
+                &rerun::components::AlbedoFactor::from(tint.rgba()),
+            )?;
+            *logged = tint;
+        }
+    }
+    for (path, tint) in tints.into_iter().filter(|(_, t)| *t != LinkTint::Neutral) {
+        rec.log(path, &rerun::components::AlbedoFactor::from(tint.rgba()))?;
+        logged_tints.insert(path.to_string(), tint);
+    }
+
+    Ok(())
+}
+
+/// Log the commanded position/velocity/torque of an actuator, plus the tracking error
+/// (command minus measured state) for every field the state also has.
+fn log_actuator_command(
+    rec: &RecordingStream,
+    command: &ActuatorCommand,
+    state: Option<&ActuatorState>,
+) -> Result<()> {
+    let base_path = format!("actuators/actuator_{}", command.actuator_id);
+
+    let fields = [
+        (
+            "position",
+            command.position as f64,
+            state.and_then(|s| s.position),
+        ),
+        (
+            "velocity",
+            command.velocity as f64,
+            state.and_then(|s| s.velocity),
+        ),
+        (
+            "torque",
+            command.torque as f64,
+            state.and_then(|s| s.torque),
+        ),
+    ];
+
+    for (name, commanded, measured) in fields {
+        rec.log(
+            format!("{}/command/{}", base_path, name),
+            &rerun::components::Scalar::from(commanded),
+        )?;
+        if let Some(measured) = measured {
+            rec.log(
+                format!("{}/error/{}", base_path, name),
+                &rerun::components::Scalar::from(commanded - measured),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Log the animated transform of a joint's child link at the given joint position.
+///
+/// Fixed (and multi-DOF) joints keep the pose logged from the URDF.
+pub(crate) fn log_joint_transform(
+    rec: &RecordingStream,
+    entity_prefix: &str,
+    joint_info: &JointInfo,
+    joint_position: f64,
+) -> Result<()> {
+    if !joint_info.is_animated() {
+        return Ok(());
+    }
+    let entity_path = join_entity_path(entity_prefix, &joint_info.entity_path);
+
+    let tf4x4 = joint_info.local_transform(joint_position);
+    let (translation, mat3x3) = decompose_4x4_to_translation_and_mat3x3(tf4x4);
+    let tf = rerun::archetypes::Transform3D::from_translation(translation).with_mat3x3(mat3x3);
+
+    debug_log_rerun_transform(
+        &entity_path,
+        None,
+        joint_info.axis.map(|a| a * joint_position),
+        translation,
+        mat3x3,
+        "Actuator animation transform",
+    );
+    rec.log(entity_path, &tf)?;
+
+    Ok(())
+}
+
+/// Log accelerometer, gyroscope and magnetometer readings (and the raw orientation)
+/// as scalar time series under `imu/`.
+fn log_imu_values(rec: &RecordingStream, imu: &ImuValues) -> Result<()> {
+    let vectors = [
+        ("accel", imu.accel.as_ref()),
+        ("gyro", imu.gyro.as_ref()),
+        ("mag", imu.mag.as_ref()),
+    ];
+    for (name, vec) in vectors {
+        let Some(v) = vec else { continue };
+        for (axis, value) in [("x", v.x), ("y", v.y), ("z", v.z)] {
+            rec.log(
+                format!("imu/{}/{}", name, axis),
+                &rerun::components::Scalar::from(value),
+            )?;
+        }
+    }
+
+    if let Some(q) = &imu.quaternion {
+        for (axis, value) in [("x", q.x), ("y", q.y), ("z", q.z), ("w", q.w)] {
+            rec.log(
+                format!("imu/quaternion/{}", axis),
+                &rerun::components::Scalar::from(value),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The recorded IMU orientation, if the recorder filled it in.
+fn imu_orientation(imu: &ImuValues) -> Option<UnitQuaternion<f64>> {
+    let q = imu.quaternion.as_ref()?;
+    // An all-zero quaternion means the recorder did not fill it in
+    if q.x == 0.0 && q.y == 0.0 && q.z == 0.0 && q.w == 0.0 {
+        return None;
+    }
+    Some(UnitQuaternion::from_quaternion(nalgebra::Quaternion::new(
+        q.w, q.x, q.y, q.z,
+    )))
+}
+
+/// Place the root link at its world pose, e.g. the IMU tilt or a recorded base pose.
+fn log_root_pose(rec: &RecordingStream, root_path: &str, pose: &Isometry3<f64>) -> Result<()> {
+    let translation = pose.translation.vector.map(|v| v as f32);
+    let q = pose.rotation;
+    rec.log(
+        root_path,
+        &rerun::archetypes::Transform3D::from_translation_rotation(
+            [translation.x, translation.y, translation.z],
+            rerun::Quaternion::from_xyzw([q.i as f32, q.j as f32, q.k as f32, q.w as f32]),
+        ),
+    )?;
+    Ok(())
+}
+
+/// Log the whole-robot center of mass for the current pose under `center_of_mass/`:
+/// the 3D point, its projection on the ground with a plumb line, and x/y/z scalars.
+///
+/// The ground is the world floor `floor_z` when the base moves through the world (base
+/// pose files and leg odometry put the floor at z = 0); with the base fixed at the
+/// origin there is no floor to speak of, so the lowest link origin is used instead.
+fn log_center_of_mass(
+    rec: &RecordingStream,
+    link_inertials: &[(String, LinkInertial)],
+    link_poses: &HashMap<String, Isometry3<f64>>,
+    root_pose: &Isometry3<f64>,
+    floor_z: Option<f32>,
+) -> Result<()> {
+    let world_pose = |link: &str| root_pose * link_poses[link];
+
+    let parts = link_inertials.iter().map(|(link, inertial)| {
+        let world = world_pose(link) * Point3::from(inertial.center_of_mass);
+        (inertial.mass, [world.x, world.y, world.z])
+    });
+    let Some((total_mass, com)) = combined_center_of_mass(parts) else {
+        return Ok(());
+    };
+
+    let ground_z = floor_z.unwrap_or_else(|| {
+        link_poses
+            .keys()
+            .map(|link| world_pose(link).translation.vector.z as f32)
+            .fold(f32::INFINITY, f32::min)
+    });
+    let point = com.map(|v| v as f32);
+    let ground = [point[0], point[1], ground_z];
+
+    rec.log(
+        "center_of_mass/point",
+        &rerun::archetypes::Points3D::new([point])
+            .with_radii([mass_point_radius(total_mass) as f32])
+            .with_colors([CENTER_OF_MASS_RGBA]),
+    )?;
+    rec.log(
+        "center_of_mass/ground_projection",
+        &rerun::archetypes::Points3D::new([ground])
+            .with_radii([0.02])
+            .with_colors([CENTER_OF_MASS_RGBA]),
+    )?;
+    rec.log(
+        "center_of_mass/plumb_line",
+        &rerun::archetypes::LineStrips3D::new([[point, ground]]).with_colors([CENTER_OF_MASS_RGBA]),
+    )?;
+    for (axis, value) in ["x", "y", "z"].into_iter().zip(com) {
         rec.log(
-            format!("{}/velocity", base_path),
-            &rerun::components::Scalar::from(vel),
+            format!("center_of_mass/{}", axis),
+            &rerun::components::Scalar::from(value),
         )?;
     }
-    if let Some(tor) = torque {
+
+    Ok(())
+}
+
+/// Log each traced link's world position under `link_trajectories/<link>/`: the current
+/// point, its trail over the last frames and `x`/`y`/`z` scalars.
+///
+/// Unbounded trails are only logged once all frames are in, see `log_full_link_trails`:
+/// re-logging a strip that grows every frame would take quadratic space.
+fn log_link_trails(
+    rec: &RecordingStream,
+    link_trails: &mut [(String, Trail)],
+    link_poses: &HashMap<String, Isometry3<f64>>,
+    root_pose: &Isometry3<f64>,
+) -> Result<()> {
+    for (index, (link, trail)) in link_trails.iter_mut().enumerate() {
+        let world = (root_pose * link_poses[link.as_str()]).translation.vector;
+        let position = [world.x as f32, world.y as f32, world.z as f32];
+        trail.push(position);
+
+        let color = trail_color(index);
         rec.log(
-            format!("{}/torque", base_path),
-            &rerun::components::Scalar::from(tor),
+            format!("link_trajectories/{}/position", link),
+            &rerun::archetypes::Points3D::new([position])
+                .with_radii([0.01])
+                .with_colors([color]),
+        )?;
+        if trail.is_bounded() {
+            rec.log(
+                format!("link_trajectories/{}/trail", link),
+                &rerun::archetypes::LineStrips3D::new([trail.points()]).with_colors([color]),
+            )?;
+        }
+        for (axis, value) in ["x", "y", "z"].into_iter().zip(position) {
+            rec.log(
+                format!("link_trajectories/{}/{}", link, axis),
+                &rerun::components::Scalar::from(value as f64),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Log the unbounded link trails as static line strips of every recorded position.
+fn log_full_link_trails(rec: &RecordingStream, link_trails: &[(String, Trail)]) -> Result<()> {
+    for (index, (link, trail)) in link_trails.iter().enumerate() {
+        if trail.is_bounded() || trail.is_empty() {
+            continue;
+        }
+        rec.log_static(
+            format!("link_trajectories/{}/trail", link),
+            &rerun::archetypes::LineStrips3D::new([trail.points()])
+                .with_colors([trail_color(index)]),
         )?;
     }
 
     Ok(())
 }
 
-/// Parse and log a KREC file, optionally using URDF joint information for transforms
+/// Log the KREC header as a markdown document under `krec/header`, and each actuator's
+/// configuration on its `actuators/actuator_N` entity.
+fn log_krec_header(rec: &RecordingStream, krec: &KRec) -> Result<()> {
+    rec.log_static(
+        "krec/header",
+        &rerun::archetypes::TextDocument::from_markdown(header_markdown(
+            &krec.header,
+            krec.frames.len(),
+        )),
+    )?;
+
+    for config in &krec.header.actuator_configs {
+        rec.log_static(
+            format!("actuators/actuator_{}", config.actuator_id),
+            &rerun::archetypes::TextDocument::from_markdown(actuator_config_markdown(config)),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Where the world pose of a floating base comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BasePoseSource {
+    /// A CSV of base poses by frame or time, e.g. exported from motion capture.
+    File(String),
+    /// The IMU orientation, with the position from leg kinematics assuming the lowest of
+    /// these links (e.g. the feet) stands on flat ground.
+    Imu { stance_links: Vec<String> },
+}
+
+/// Options controlling how a KREC recording is logged.
+#[derive(Debug, Clone, Default)]
+pub struct KrecLogOptions {
+    /// Actuator -> URDF joint mapping file; without one the built-in GPR mapping is used.
+    pub joint_map_path: Option<String>,
+    /// Apply the recorded IMU orientation to the root link.
+    pub imu_orientation: bool,
+    /// Log a second, translucent robot posed from the actuator commands.
+    pub command_ghost: bool,
+    /// Thresholds on temperature/voltage/current that trigger warning events.
+    pub thresholds: ActuatorThresholds,
+    /// Don't log the camera footage of KREC files embedded in a video.
+    pub skip_video: bool,
+    /// Log the whole-robot center of mass and its ground projection for every frame.
+    pub center_of_mass: bool,
+    /// Check recorded positions, velocities and torques against the URDF joint limits.
+    pub check_joint_limits: bool,
+    /// Fraction of a joint's range (or velocity/effort limit) within which it counts as
+    /// near its limit.
+    pub near_limit_margin: f64,
+    /// Links whose world position is traced over the recording.
+    pub trace_links: Vec<String>,
+    /// Number of frames shown in each link's trail; 0 keeps the whole recording.
+    pub trail_length: usize,
+    /// Move the root link through the world instead of keeping it fixed at the origin.
+    pub base_pose: Option<BasePoseSource>,
+}
+
+/// Parse and log a KREC file, optionally using URDF joint information for transforms.
 pub fn parse_and_log_krec(
     krec: &KRec,
     urdf_path: Option<&str>,
+    urdf_options: &UrdfLogOptions,
+    options: &KrecLogOptions,
     rec: &RecordingStream,
 ) -> Result<()> {
-    // We'll replicate the python actuator->joint map
-    let actuator_map = build_actuator_to_urdf_joint_map();
+    let description = match urdf_path {
+        Some(path) => Some(load_robot_description(path, urdf_options)?),
+        None => None,
+    };
+    log_krec(krec, description.as_ref(), urdf_options, options, rec)
+}
 
-    let joint_info_map = if let Some(urdf_path) = urdf_path {
-        build_joint_name_to_joint_info(urdf_path)?
-    } else {
-        HashMap::new()
+/// Log a KREC file, animating an already loaded robot description if given.
+pub(crate) fn log_krec(
+    krec: &KRec,
+    description: Option<&RobotDescription>,
+    urdf_options: &UrdfLogOptions,
+    options: &KrecLogOptions,
+    rec: &RecordingStream,
+) -> Result<()> {
+    log_krec_header(rec, krec)?;
+
+    let actuator_map = match &options.joint_map_path {
+        Some(path) => {
+            info!("Loading actuator->joint map from {}", path);
+            load_actuator_joint_map(Path::new(path))?
+        }
+        None => default_actuator_to_urdf_joint_map(),
+    };
+
+    let robot = description.map(|d| &d.robot);
+    let model = description.map(RobotModel::from_description).transpose()?;
+
+    let no_joints = HashMap::new();
+    let (joint_info_map, link_names): (&HashMap<String, JointInfo>, &[String]) = match &model {
+        Some(model) => (model.joint_info_map(), model.link_names()),
+        None => (&no_joints, &[]),
+    };
+
+    // The root link's entity path is its name, first in BFS order
+    let root_path = link_names.first().cloned();
+
+    let base_pose_source = match &options.base_pose {
+        Some(_) if robot.is_none() => {
+            log::warn!("A floating base needs a URDF, ignoring --base-pose");
+            None
+        }
+        source => source.as_ref(),
+    };
+    let base_pose_track = match base_pose_source {
+        Some(BasePoseSource::File(path)) => {
+            info!("Loading base poses from {}", path);
+            Some(BasePoseTrack::load(Path::new(path))?)
+        }
+        _ => None,
+    };
+    let stance_links: &[String] = match base_pose_source {
+        Some(BasePoseSource::Imu { stance_links }) => {
+            if stance_links.is_empty() {
+                bail!("Estimating the base pose from the IMU needs at least one stance link");
+            }
+            let unknown: Vec<&str> = stance_links
+                .iter()
+                .filter(|link| !link_names.contains(link))
+                .map(String::as_str)
+                .collect();
+            if !unknown.is_empty() {
+                bail!("Stance links not found in URDF: {}", unknown.join(", "));
+            }
+            stance_links
+        }
+        _ => &[],
+    };
+    let mut leg_odometry = LegOdometry::new();
+    // Base-pose files keyed by time count from the first frame
+    let first_video_timestamp = krec.frames.first().map_or(0, |f| f.video_timestamp);
+    // The last orientation the IMU reported
+    let mut imu_rotation: Option<UnitQuaternion<f64>> = None;
+
+    // Links with mass, for the whole-robot center of mass
+    let link_inertials: Vec<(String, LinkInertial)> = match robot {
+        Some(robot) if options.center_of_mass => robot
+            .links
+            .iter()
+            .filter(|link| link_names.contains(&link.name))
+            .map(|link| (link.name.clone(), LinkInertial::from_urdf(&link.inertial)))
+            .filter(|(_, inertial)| inertial.mass > 0.0)
+            .collect(),
+        _ => Vec::new(),
+    };
+    // Links to trace, each with its trail of world positions
+    let mut link_trails: Vec<(String, Trail)> = Vec::new();
+    if !options.trace_links.is_empty() {
+        if robot.is_some() {
+            let unknown: Vec<&str> = options
+                .trace_links
+                .iter()
+                .filter(|link| !link_names.contains(link))
+                .map(String::as_str)
+                .collect();
+            if !unknown.is_empty() {
+                bail!("Links to trace not found in URDF: {}", unknown.join(", "));
+            }
+            link_trails = options
+                .trace_links
+                .iter()
+                .map(|link| (link.clone(), Trail::new(options.trail_length)))
+                .collect();
+        } else {
+            log::warn!("Link trajectories need a URDF, ignoring --trace-links");
+        }
+    }
+    // Joint positions persist until an actuator reports a new one, like the logged transforms
+    let mut current_positions: HashMap<String, f64> = HashMap::new();
+    let mut root_pose = Isometry3::identity();
+
+    // Only meaningful with a URDF: without one nothing gets animated anyway. The built-in
+    // GPR map isn't checked, so other robots still play with per-actuator warnings; a map
+    // the user wrote for this robot has to match it
+    if description.is_some() && options.joint_map_path.is_some() {
+        let actuator_ids: BTreeSet<u32> = krec
+            .frames
+            .iter()
+            .flat_map(|frame| frame.actuator_states.iter().map(|s| s.actuator_id))
+            .collect();
+        let urdf_joint_names: HashSet<String> = joint_info_map.keys().cloned().collect();
+        validate_actuator_joint_map(&actuator_map, &actuator_ids, &urdf_joint_names)?;
+    }
+
+    // Visual entity paths of the link each actuator moves, for tinting offline actuators
+    let mut actuator_visual_paths: HashMap<u32, Vec<String>> = HashMap::new();
+    if let Some(robot) = robot {
+        for (actuator_id, joint_name) in &actuator_map {
+            let Some(joint_info) = joint_info_map.get(joint_name) else {
+                continue;
+            };
+            if let Some(link) = robot.links.iter().find(|l| l.name == joint_info.child_link) {
+                actuator_visual_paths.insert(
+                    *actuator_id,
+                    visual_entity_paths(link, &joint_info.entity_path),
+                );
+            }
+        }
+    }
+    let mut actuator_health: HashMap<u32, ActuatorHealth> = HashMap::new();
+
+    // Visual entity paths of each joint's child link, for highlighting limit violations
+    let mut joint_visual_paths: HashMap<String, Vec<String>> = HashMap::new();
+    if let Some(robot) = robot.filter(|_| options.check_joint_limits) {
+        for (joint_name, joint_info) in joint_info_map {
+            if let Some(link) = robot.links.iter().find(|l| l.name == joint_info.child_link) {
+                joint_visual_paths.insert(
+                    joint_name.clone(),
+                    visual_entity_paths(link, &joint_info.entity_path),
+                );
+            }
+        }
+    }
+    let mut joint_limit_states: HashMap<String, JointLimitState> = HashMap::new();
+    // Tint last logged on each link visual
+    let mut link_tints: HashMap<String, LinkTint> = HashMap::new();
+    let mut limit_summary = LimitViolationSummary::default();
+    // Recorders that never fill in `online` would otherwise show every actuator as offline
+    let track_online = krec
+        .frames
+        .iter()
+        .any(|frame| frame.actuator_states.iter().any(|s| s.online));
+
+    let command_ghost = match description {
+        Some(description) if options.command_ghost => {
+            info!(
+                "Logging command ghost robot under '{}'",
+                COMMAND_GHOST_PREFIX
+            );
+            log_urdf_hierarchy(
+                description,
+                urdf_options,
+                COMMAND_GHOST_PREFIX,
+                Some(COMMAND_GHOST_RGBA),
+                rec,
+            )?;
+            true
+        }
+        _ => false,
     };
 
     let mut frames_processed = 0;
+    let log_inference_steps = has_inference_steps(krec);
 
     // Iterate frames
     for (frame_idx, frame) in krec.frames.iter().enumerate() {
-        // Set Rerun time-sequence so transforms appear "animated"
-        rec.set_time_sequence("frame_idx", frame_idx as i64);
+        // Set Rerun timelines so transforms appear "animated"
+        set_frame_timelines(
+            rec,
+            krec.header.start_timestamp,
+            frame_idx,
+            frame,
+            log_inference_steps,
+        );
+
+        if let Some(imu) = &frame.imu_values {
+            log_imu_values(rec, imu)?;
+            if let Some(rotation) = imu_orientation(imu) {
+                imu_rotation = Some(rotation);
+            }
+        }
 
         let mut frame_had_valid_data = false;
+        let mut joint_positions: HashMap<String, f64> = HashMap::new();
+        // Recorded velocity and torque of each joint, for the limit checks
+        let mut joint_samples: HashMap<String, JointSample> = HashMap::new();
 
         for state in &frame.actuator_states {
             let actuator_id = state.actuator_id;
 
+            log_actuator_states(rec, frame_idx, state)?;
+            log_actuator_health(
+                rec,
+                state,
+                &options.thresholds,
+                actuator_health.entry(actuator_id).or_default(),
+                track_online,
+            )?;
+
             // 1) Early-exit from "missing" joint_name
             let Some(joint_name) = actuator_map.get(&actuator_id) else {
                 log::warn!(
@@ -123,7 +808,7 @@ pub fn parse_and_log_krec(
             };
 
             // 2) Early-exit from "missing" joint_info
-            let Some(joint_info) = joint_info_map.get(*joint_name) else {
+            let Some(joint_info) = joint_info_map.get(joint_name) else {
                 log::warn!(
                     "Frame {}: Joint '{}' not found in URDF joint info map, skipping",
                     frame_idx,
@@ -133,7 +818,7 @@ pub fn parse_and_log_krec(
             };
 
             // 3) Early-exit from missing position
-            let Some(pos_deg) = state.position else {
+            let Some(recorded_position) = state.position else {
                 log::warn!(
                     "Frame {}: No position data for actuator {}, skipping",
                     frame_idx,
@@ -142,47 +827,186 @@ pub fn parse_and_log_krec(
                 continue;
             };
 
-            // Now do the transform logic
-            let angle_rad = pos_deg * (PI / 180.0);
-            let new_rotation = build_z_rotation_3x3(angle_rad);
-            let final_rotation = mat3x3_mul(joint_info.base_rotation, new_rotation);
-            let tf4x4 = make_4x4_from_rotation_and_translation(
-                final_rotation,
-                joint_info.origin_translation,
+            joint_positions.insert(
+                joint_name.clone(),
+                recorded_position_to_joint_units(joint_info, recorded_position),
             );
-
-            // now log the transform
-            let (translation, mat3x3) = decompose_4x4_to_translation_and_mat3x3(tf4x4);
-            let tf =
-                rerun::archetypes::Transform3D::from_translation(translation).with_mat3x3(mat3x3);
-
-            debug_log_rerun_transform(
-                &joint_info.entity_path,
-                None,
-                [0.0, 0.0, angle_rad],
-                translation,
-                mat3x3,
-                "Actuator animation transform",
+            joint_samples.insert(
+                joint_name.clone(),
+                JointSample {
+                    position: None,
+                    // Velocities are recorded in the same (degree based) units as positions
+                    velocity: state
+                        .velocity
+                        .map(|v| recorded_position_to_joint_units(joint_info, v)),
+                    effort: state.torque,
+                },
             );
-            rec.log(&*joint_info.entity_path, &tf)?;
-
-            // Optionally log basic actuator states
-            log_actuator_states(
-                rec,
-                frame_idx,
-                actuator_id,
-                state.position,
-                state.velocity,
-                state.torque,
-            )?;
 
             frame_had_valid_data = true;
         }
 
+        // Followers are driven from their leader's recorded position
+        let mimic_positions = resolve_mimic_positions(joint_info_map, &joint_positions);
+        joint_positions.extend(mimic_positions);
+
+        for (joint_name, joint_position) in &joint_positions {
+            log_joint_transform(rec, "", &joint_info_map[joint_name], *joint_position)?;
+        }
+
+        if options.check_joint_limits {
+            for (joint_name, joint_position) in &joint_positions {
+                let joint_info = &joint_info_map[joint_name];
+                let sample = JointSample {
+                    position: Some(*joint_position),
+                    ..joint_samples.get(joint_name).copied().unwrap_or_default()
+                };
+                let check = check_joint_limits(
+                    &joint_info.joint_type,
+                    &joint_info.limit,
+                    &sample,
+                    options.near_limit_margin,
+                );
+                for violation in &check.violations {
+                    limit_summary.record(joint_name, violation, frame_idx);
+                }
+                log_joint_limit_check(
+                    rec,
+                    joint_name,
+                    &check,
+                    joint_limit_states.entry(joint_name.clone()).or_default(),
+                )?;
+            }
+        }
+        log_link_tints(
+            rec,
+            &actuator_health,
+            &actuator_visual_paths,
+            &joint_limit_states,
+            &joint_visual_paths,
+            &mut link_tints,
+        )?;
+
+        let link_poses = match &model {
+            Some(model)
+                if !link_inertials.is_empty()
+                    || !link_trails.is_empty()
+                    || !stance_links.is_empty() =>
+            {
+                current_positions.extend(joint_positions.iter().map(|(k, v)| (k.clone(), *v)));
+                model.forward_kinematics(&current_positions)
+            }
+            _ => HashMap::new(),
+        };
+
+        // The IMU tilt, unless a floating-base source says otherwise
+        let imu_tilt = imu_rotation.filter(|_| options.imu_orientation);
+        let base_pose = match base_pose_source {
+            Some(BasePoseSource::File(_)) => base_pose_track.as_ref().map(|track| {
+                let key = match track.key() {
+                    PoseKey::Frame => frame_idx as f64,
+                    PoseKey::Time => {
+                        frame.video_timestamp.saturating_sub(first_video_timestamp) as f64 / 1e9
+                    }
+                };
+                let pose = track.pose_at(key);
+                let rotation = pose
+                    .orientation
+                    .or(imu_tilt)
+                    .unwrap_or_else(UnitQuaternion::identity);
+                Isometry3::from_parts(pose.position.into(), rotation)
+            }),
+            Some(BasePoseSource::Imu { .. }) => {
+                let rotation = imu_rotation.unwrap_or_else(UnitQuaternion::identity);
+                let stance_positions: Vec<(String, Vector3<f64>)> = stance_links
+                    .iter()
+                    .map(|link| (link.clone(), link_poses[link].translation.vector))
+                    .collect();
+                let position = leg_odometry.update(&rotation, &stance_positions);
+                Some(Isometry3::from_parts(position.into(), rotation))
+            }
+            None => {
+                imu_tilt.map(|rotation| Isometry3::from_parts(Translation3::identity(), rotation))
+            }
+        };
+        if let (Some(pose), Some(root_path)) = (base_pose, &root_path) {
+            log_root_pose(rec, root_path, &pose)?;
+            // The ghost follows the measured base, so only the joints differ
+            if command_ghost {
+                let ghost_root_path = join_entity_path(COMMAND_GHOST_PREFIX, root_path);
+                log_root_pose(rec, &ghost_root_path, &pose)?;
+            }
+            root_pose = pose;
+        }
+
+        if !link_inertials.is_empty() {
+            // Only a base pose source places the robot relative to the floor
+            let floor_z = base_pose_source.map(|_| 0.0);
+            log_center_of_mass(rec, &link_inertials, &link_poses, &root_pose, floor_z)?;
+        }
+        log_link_trails(rec, &mut link_trails, &link_poses, &root_pose)?;
+
+        for command in &frame.actuator_commands {
+            let state = frame
+                .actuator_states
+                .iter()
+                .find(|s| s.actuator_id == command.actuator_id);
+            log_actuator_command(rec, command, state)?;
+        }
+
+        if command_ghost {
+            let mut command_positions: HashMap<String, f64> = HashMap::new();
+            for command in &frame.actuator_commands {
+                let Some(joint_name) = actuator_map.get(&command.actuator_id) else {
+                    continue;
+                };
+                let Some(joint_info) = joint_info_map.get(joint_name) else {
+                    continue;
+                };
+                command_positions.insert(
+                    joint_name.clone(),
+                    recorded_position_to_joint_units(joint_info, command.position as f64),
+                );
+            }
+            let mimic_positions = resolve_mimic_positions(joint_info_map, &command_positions);
+            command_positions.extend(mimic_positions);
+
+            for (joint_name, joint_position) in &command_positions {
+                log_joint_transform(
+                    rec,
+                    COMMAND_GHOST_PREFIX,
+                    &joint_info_map[joint_name],
+                    *joint_position,
+                )?;
+            }
+        }
+
         if frame_had_valid_data {
             frames_processed += 1;
         }
     }
+    log_full_link_trails(rec, &link_trails)?;
+
+    if options.check_joint_limits && robot.is_some() {
+        rec.log_static(
+            format!("{}/summary", JOINT_LIMIT_VIOLATIONS_PATH),
+            &rerun::archetypes::TextDocument::from_markdown(
+                limit_summary.markdown(krec.frames.len()),
+            ),
+        )?;
+        for (joint_name, kind, stats) in limit_summary.iter() {
+            log::warn!(
+                "Joint {} exceeded its {} limit in {} of {} frames (first at frame {}, by up to {:.4} {})",
+                joint_name,
+                kind.name(),
+                stats.frames,
+                krec.frames.len(),
+                stats.first_frame,
+                stats.max_excess,
+                stats.unit
+            );
+        }
+    }
 
     info!(
         "Successfully logged {} KREC frames to rerun",
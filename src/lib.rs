@@ -1,51 +1,86 @@
 // src/lib.rs
 
 use anyhow::Result;
-use krec::KRec;
 use log::{info, warn};
-use rerun::RecordingStreamBuilder;
+use rerun::{RecordingStream, RecordingStreamBuilder};
+use std::path::Path;
 
 // Re-export other functions/types if you want them public
-pub use crate::krec_logger::parse_and_log_krec;
-pub use crate::urdf_logger::parse_and_log_urdf_hierarchy;
+pub use crate::ik_logger::log_ik_solution;
+pub use crate::ik_solver::{solve_ik, IkIteration, IkOptions, IkSolution, IkTarget};
+pub use crate::krec_logger::{parse_and_log_krec, BasePoseSource, KrecLogOptions};
+pub use crate::robot_model::RobotModel;
+pub use crate::urdf_logger::{parse_and_log_urdf_hierarchy, InertiaDisplay, UrdfLogOptions};
+pub use crate::utils::krec_utils::load_krec;
+pub use crate::video_logger::parse_and_log_krec_video;
 
+use crate::krec_logger::log_krec;
+use crate::urdf_logger::{load_robot_description, log_robot_description};
+use crate::utils::krec_utils::is_video_container;
+
+mod ik_logger;
+mod ik_solver;
 mod krec_logger;
+mod robot_model;
 mod urdf_logger;
 pub mod utils;
+mod video_logger;
+
+/// Start a Rerun recording, saved to `output_path` if given, otherwise shown in a viewer.
+fn start_recording(output_path: Option<&str>) -> Result<RecordingStream> {
+    let builder = RecordingStreamBuilder::new("krecviz");
+    if let Some(path) = output_path {
+        info!("Creating recording that will be saved to {}", path);
+        Ok(builder.save(path)?)
+    } else {
+        Ok(builder.spawn()?)
+    }
+}
 
 pub fn viz(
     urdf_path: Option<&str>,
     krec_path: Option<&str>,
     output_path: Option<&str>,
+    urdf_options: &UrdfLogOptions,
+    krec_options: &KrecLogOptions,
 ) -> Result<()> {
     // 1) Start a Rerun recording
-    let builder = RecordingStreamBuilder::new("krecviz");
-    let rec = if let Some(path) = output_path {
-        info!("Creating recording that will be saved to {}", path);
-        builder.save(path)?
-    } else {
-        builder.spawn()?
-    };
+    let rec = start_recording(output_path)?;
 
-    // 2) If we have a URDF, parse & log it
-    if let Some(path) = urdf_path {
+    // 2) If we have a URDF, parse & log it, loading it once for the KREC too
+    let description = if let Some(path) = urdf_path {
         info!("Loading URDF from {}", path);
-        parse_and_log_urdf_hierarchy(path, &rec)?;
+        let description = load_robot_description(path, urdf_options)?;
+        log_robot_description(&description, urdf_options, &rec)?;
+        Some(description)
     } else {
         warn!("No URDF path provided!");
         rec.log(
             "/no_urdf_found",
             &rerun::TextDocument::new("No URDF provided"),
         )?;
-    }
+        None
+    };
 
     // 3) If we have a KREC, parse it
     if let Some(path) = krec_path {
         info!("Loading KREC from {}", path);
-        let loaded_krec = KRec::load(path)
-            .map_err(|e| anyhow::anyhow!("Failed to load KREC from {:?}: {:?}", path, e))?;
+        let loaded_krec = load_krec(path)?;
         info!("Loaded KREC with {} frames", loaded_krec.frames.len());
-        parse_and_log_krec(&loaded_krec, urdf_path, &rec)?;
+        log_krec(
+            &loaded_krec,
+            description.as_ref(),
+            urdf_options,
+            krec_options,
+            &rec,
+        )?;
+
+        if is_video_container(Path::new(path)) && !krec_options.skip_video {
+            // The telemetry is already logged, so a broken video shouldn't fail the run
+            if let Err(e) = parse_and_log_krec_video(path, &loaded_krec, &rec) {
+                warn!("Failed to log camera video from {}: {}", path, e);
+            }
+        }
     } else {
         warn!("No KREC path provided, no telemetry will be logged!");
     }
@@ -56,3 +91,29 @@ pub fn viz(
 
     Ok(())
 }
+
+/// Solve inverse kinematics for `target` and log the robot, the target and every solver
+/// iteration (on the `ik_iteration` timeline) to Rerun.
+pub fn viz_ik(
+    urdf_path: &str,
+    output_path: Option<&str>,
+    urdf_options: &UrdfLogOptions,
+    target: &IkTarget,
+    ik_options: &IkOptions,
+) -> Result<IkSolution> {
+    let rec = start_recording(output_path)?;
+
+    info!("Loading URDF from {}", urdf_path);
+    let description = load_robot_description(urdf_path, urdf_options)?;
+    log_robot_description(&description, urdf_options, &rec)?;
+
+    let model = RobotModel::from_description(&description)?;
+    let solution = solve_ik(&model, target, ik_options)?;
+    log_ik_solution(&model, target, &solution, &rec)?;
+
+    if output_path.is_some() {
+        info!("Successfully saved recording");
+    }
+
+    Ok(solution)
+}
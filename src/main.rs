@@ -1,25 +1,267 @@
 // src/main.rs
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
 use env_logger::{Builder, Env};
+use log::{info, warn};
+use nalgebra::UnitQuaternion;
 
-use krecviz::viz;
+use krecviz::utils::krec_summary_utils::KrecSummary;
+use krecviz::utils::krec_utils::ActuatorThresholds;
+use krecviz::{
+    load_krec, viz, viz_ik, BasePoseSource, IkOptions, IkTarget, InertiaDisplay, KrecLogOptions,
+    UrdfLogOptions,
+};
 
 #[derive(Parser, Debug)]
 #[command(name = "krecviz")]
 struct Args {
+    /// Without a subcommand, the robot and KREC recording are visualized
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Path to the URDF file
-    #[arg(long)]
+    #[arg(long, global = true)]
     urdf: Option<String>,
 
+    /// Path to an MJCF (MuJoCo XML) model, used instead of a URDF
+    #[arg(long, global = true, conflicts_with = "urdf")]
+    mjcf: Option<String>,
+
+    /// Directory searched for `package://` mesh URIs (repeatable, searched before ROS_PACKAGE_PATH)
+    #[arg(long = "package-path", value_name = "DIR", global = true)]
+    package_paths: Vec<String>,
+
+    /// Value for a xacro `$(arg NAME)`, as NAME:=VALUE (repeatable)
+    #[arg(long = "xacro-arg", value_name = "NAME:=VALUE", value_parser = parse_xacro_arg, global = true)]
+    xacro_args: Vec<(String, String)>,
+
+    /// Link the robot hangs from, when the URDF has several links without a parent joint
+    #[arg(long, value_name = "LINK", global = true)]
+    root_link: Option<String>,
+
+    /// Fail on URDF tree problems (several roots, cycles, unknown or orphaned links) instead
+    /// of warning and leaving links out
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Also show each link's collision geometry (translucent, under `collision_N`)
+    #[arg(long)]
+    show_collisions: bool,
+
+    /// Show each link's center of mass and inertia, as a `box` (default) or `ellipsoid`
+    #[arg(long, value_name = "SHAPE", num_args = 0..=1, default_missing_value = "box")]
+    show_inertia: Option<InertiaDisplay>,
+
+    /// Draw every joint's frame, axis and limit range
+    #[arg(long)]
+    show_joint_overlays: bool,
+
     /// Path to the KREC file
     #[arg(long)]
     krec: Option<String>,
 
     /// Path to .rrd output (if you want to save)
-    #[arg(long)]
+    #[arg(long, global = true)]
     output: Option<String>,
+
+    /// Path to an actuator -> URDF joint mapping file (.toml, .json or .yaml)
+    #[arg(long)]
+    joint_map: Option<String>,
+
+    /// Rotate the root link by the recorded IMU orientation
+    #[arg(long)]
+    imu_orientation: bool,
+
+    /// Plot the whole-robot center of mass and its ground projection for every frame
+    #[arg(long)]
+    center_of_mass: bool,
+
+    /// Check recorded joint positions, velocities and torques against the URDF limits
+    #[arg(long)]
+    check_joint_limits: bool,
+
+    /// Fraction of a joint's range (or velocity/effort limit) that counts as near the limit
+    #[arg(long, value_name = "FRACTION", default_value_t = 0.05)]
+    near_limit_margin: f64,
+
+    /// Trace the world position of these links (comma-separated) as trails and x/y/z plots
+    #[arg(long, value_name = "LINKS", value_delimiter = ',')]
+    trace_links: Vec<String>,
+
+    /// Number of frames shown in each traced link's trail (0 keeps the whole recording)
+    #[arg(long, value_name = "FRAMES", default_value_t = 200)]
+    trail_length: usize,
+
+    /// Move the root link through the world: a CSV of base poses (e.g. exported from motion
+    /// capture), or `imu` to combine the IMU orientation with planted --stance-links
+    #[arg(long, value_name = "FILE|imu")]
+    base_pose: Option<String>,
+
+    /// Links (comma-separated) that take turns standing on the ground, for `--base-pose imu`
+    #[arg(
+        long,
+        value_name = "LINKS",
+        value_delimiter = ',',
+        requires = "base_pose"
+    )]
+    stance_links: Vec<String>,
+
+    /// Show a translucent second robot posed from the actuator commands
+    #[arg(long)]
+    command_ghost: bool,
+
+    /// Don't show the camera footage of a KREC embedded in a video
+    #[arg(long)]
+    no_video: bool,
+
+    /// Warn when an actuator temperature exceeds this value (°C)
+    #[arg(long)]
+    max_temperature: Option<f64>,
+
+    /// Warn when an actuator supply voltage drops below this value (V)
+    #[arg(long)]
+    min_voltage: Option<f32>,
+
+    /// Warn when an actuator supply voltage exceeds this value (V)
+    #[arg(long)]
+    max_voltage: Option<f32>,
+
+    /// Warn when an actuator current exceeds this value in magnitude (A)
+    #[arg(long)]
+    max_current: Option<f32>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Solve inverse kinematics for a link and show the solved pose and the solver iterations
+    Ik(IkArgs),
+    /// Print a summary of a KREC recording without opening the viewer
+    Inspect(InspectArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct IkArgs {
+    /// Link to move onto the target
+    #[arg(long)]
+    link: String,
+
+    /// Target position relative to the root link (m)
+    #[arg(long, num_args = 3, value_names = ["X", "Y", "Z"], allow_negative_numbers = true, required = true)]
+    target: Vec<f64>,
+
+    /// Target orientation relative to the root link, as roll, pitch and yaw (rad)
+    #[arg(long, num_args = 3, value_names = ["ROLL", "PITCH", "YAW"], allow_negative_numbers = true)]
+    target_rpy: Option<Vec<f64>>,
+
+    /// Starting position of a joint, as NAME=VALUE (repeatable, others start at zero)
+    #[arg(long = "initial", value_name = "NAME=VALUE", value_parser = parse_joint_position)]
+    initial_positions: Vec<(String, f64)>,
+
+    /// Maximum number of solver iterations
+    #[arg(long, default_value_t = 100)]
+    max_iterations: usize,
+
+    /// Damping factor of the least-squares steps
+    #[arg(long, default_value_t = 0.05)]
+    damping: f64,
+
+    /// Position error under which the target counts as reached (m)
+    #[arg(long, default_value_t = 1e-4)]
+    tolerance: f64,
+
+    /// Orientation error under which a --target-rpy target counts as reached (rad)
+    #[arg(long, default_value_t = 1e-3)]
+    orientation_tolerance: f64,
+}
+
+#[derive(clap::Args, Debug)]
+struct InspectArgs {
+    /// KREC file, or video with an embedded KREC
+    krec: String,
+
+    /// Print the summary as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+/// Parse a `name=value` joint position.
+fn parse_joint_position(arg: &str) -> Result<(String, f64), String> {
+    let (name, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=VALUE, got {arg:?}"))?;
+    let value = value
+        .parse()
+        .map_err(|e| format!("invalid position {value:?} for joint {name:?}: {e}"))?;
+    Ok((name.to_string(), value))
+}
+
+/// Solve the `ik` subcommand's target and report the solved joint positions.
+fn run_ik(
+    ik_args: IkArgs,
+    urdf_path: Option<&str>,
+    output_path: Option<&str>,
+    urdf_options: &UrdfLogOptions,
+) -> Result<()> {
+    let urdf_path = urdf_path.ok_or_else(|| anyhow!("krecviz ik needs --urdf or --mjcf"))?;
+
+    let target = IkTarget {
+        link: ik_args.link,
+        position: [ik_args.target[0], ik_args.target[1], ik_args.target[2]],
+        orientation: ik_args
+            .target_rpy
+            .map(|rpy| UnitQuaternion::from_euler_angles(rpy[0], rpy[1], rpy[2])),
+    };
+    let ik_options = IkOptions {
+        max_iterations: ik_args.max_iterations,
+        damping: ik_args.damping,
+        position_tolerance: ik_args.tolerance,
+        orientation_tolerance: ik_args.orientation_tolerance,
+        initial_positions: ik_args.initial_positions.into_iter().collect(),
+        ..Default::default()
+    };
+
+    let solution = viz_ik(urdf_path, output_path, urdf_options, &target, &ik_options)?;
+
+    let final_iteration = solution.final_iteration();
+    if solution.converged {
+        info!(
+            "Reached the {} target in {} iterations",
+            target.link,
+            solution.trace.len() - 1
+        );
+    } else {
+        warn!(
+            "Could not reach the {} target: {:.4} m / {:.4} rad off",
+            target.link, final_iteration.position_error, final_iteration.orientation_error
+        );
+    }
+    let mut joint_positions: Vec<_> = solution.joint_positions().iter().collect();
+    joint_positions.sort_by(|a, b| a.0.cmp(b.0));
+    for (joint_name, position) in joint_positions {
+        info!("{} = {:.6}", joint_name, position);
+    }
+
+    Ok(())
+}
+
+/// Print the `inspect` subcommand's summary of a KREC recording.
+fn run_inspect(inspect_args: &InspectArgs) -> Result<()> {
+    let krec = load_krec(&inspect_args.krec)?;
+    let summary = KrecSummary::from_krec(&krec);
+    if inspect_args.json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else {
+        print!("{}", summary.text());
+    }
+    Ok(())
+}
+
+/// Parse a `name:=value` xacro argument, as given to ROS tools.
+fn parse_xacro_arg(arg: &str) -> Result<(String, String), String> {
+    arg.split_once(":=")
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected NAME:=VALUE, got {arg:?}"))
 }
 
 fn main() -> Result<()> {
@@ -29,10 +271,58 @@ fn main() -> Result<()> {
     // Parse CLI args
     let args = Args::parse();
 
+    let urdf_options = UrdfLogOptions {
+        package_paths: args.package_paths,
+        xacro_args: args.xacro_args.into_iter().collect(),
+        show_collisions: args.show_collisions,
+        show_inertia: args.show_inertia,
+        show_joint_overlays: args.show_joint_overlays,
+        root_link: args.root_link,
+        strict: args.strict,
+    };
+
+    let base_pose = match args.base_pose.as_deref() {
+        Some("imu") => Some(BasePoseSource::Imu {
+            stance_links: args.stance_links,
+        }),
+        Some(path) => Some(BasePoseSource::File(path.to_string())),
+        None => None,
+    };
+
+    let krec_options = KrecLogOptions {
+        joint_map_path: args.joint_map,
+        imu_orientation: args.imu_orientation,
+        command_ghost: args.command_ghost,
+        thresholds: ActuatorThresholds {
+            max_temperature: args.max_temperature,
+            min_voltage: args.min_voltage,
+            max_voltage: args.max_voltage,
+            max_current: args.max_current,
+        },
+        skip_video: args.no_video,
+        center_of_mass: args.center_of_mass,
+        check_joint_limits: args.check_joint_limits,
+        near_limit_margin: args.near_limit_margin,
+        trace_links: args.trace_links,
+        trail_length: args.trail_length,
+        base_pose,
+    };
+
+    let robot_path = args.urdf.as_deref().or(args.mjcf.as_deref());
+    match args.command {
+        Some(Command::Ik(ik_args)) => {
+            return run_ik(ik_args, robot_path, args.output.as_deref(), &urdf_options)
+        }
+        Some(Command::Inspect(inspect_args)) => return run_inspect(&inspect_args),
+        None => {}
+    }
+
     // Call viz from the library
     viz(
-        args.urdf.as_deref(),
+        robot_path,
         args.krec.as_deref(),
         args.output.as_deref(),
+        &urdf_options,
+        &krec_options,
     )
 }
@@ -0,0 +1,167 @@
+// robot_model.rs
+
+use anyhow::Result;
+use krec::KRecFrame;
+use nalgebra as na;
+use std::collections::HashMap;
+use urdf_rs::Robot;
+
+use crate::urdf_logger::{
+    check_robot_tree, load_robot_description, RobotDescription, UrdfLogOptions,
+};
+use crate::utils::joint_map_utils::recorded_position_to_joint_units;
+use crate::utils::urdf_bfs_utils::{
+    build_joint_info_map, build_link_bfs_map, compute_link_poses, resolve_mimic_positions,
+    validate_mimic_joints, JointInfo,
+};
+
+/// Kinematic tree of a robot, for computing link poses outside the viewer.
+///
+/// ```no_run
+/// use krecviz::{RobotModel, UrdfLogOptions};
+/// use std::collections::HashMap;
+///
+/// let model = RobotModel::load("path/to/robot.urdf", &UrdfLogOptions::default())?;
+/// let positions = HashMap::from([("knee".to_string(), 0.5)]);
+/// let poses = model.forward_kinematics(&positions);
+/// println!("foot at {}", poses["foot"].translation.vector);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct RobotModel {
+    /// Links in BFS order from the root
+    bfs_order: Vec<String>,
+    joint_info_map: HashMap<String, JointInfo>,
+    /// Joint name by child link, for walking the tree
+    joint_by_child: HashMap<String, String>,
+}
+
+impl RobotModel {
+    /// Build the model of an already parsed robot.
+    ///
+    /// Its link/joint tree is checked as when loading a description: `options.root_link`
+    /// overrides the root, `options.strict` turns tree problems into errors, and links
+    /// that don't hang from the root are left out.
+    pub fn from_robot(robot: &Robot, options: &UrdfLogOptions) -> Result<Self> {
+        let (robot, root_link) = check_robot_tree(robot, options)?;
+        Self::from_tree(&robot, &root_link)
+    }
+
+    /// Load a URDF, xacro or MJCF robot description.
+    pub fn load(path: &str, options: &UrdfLogOptions) -> Result<Self> {
+        Self::from_description(&load_robot_description(path, options)?)
+    }
+
+    /// Build the model of a loaded description, whose tree is already checked.
+    pub(crate) fn from_description(description: &RobotDescription) -> Result<Self> {
+        Self::from_tree(&description.robot, &description.root_link)
+    }
+
+    /// Build the model of a robot that is a single tree hanging from `root_link`.
+    fn from_tree(robot: &Robot, root_link: &str) -> Result<Self> {
+        let (_link_bfs_map, bfs_order) = build_link_bfs_map(robot, root_link);
+        let joint_info_map = build_joint_info_map(robot, root_link);
+        validate_mimic_joints(&joint_info_map)?;
+
+        let joint_by_child = joint_info_map
+            .iter()
+            .map(|(name, info)| (info.child_link.clone(), name.clone()))
+            .collect();
+
+        Ok(RobotModel {
+            bfs_order,
+            joint_info_map,
+            joint_by_child,
+        })
+    }
+
+    /// Name of the root link, the frame all poses are expressed in.
+    pub fn root_link(&self) -> &str {
+        &self.bfs_order[0]
+    }
+
+    /// Names of all links, in BFS order from the root.
+    pub fn link_names(&self) -> &[String] {
+        &self.bfs_order
+    }
+
+    /// Names of all joints, sorted.
+    pub fn joint_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.joint_info_map.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    /// Parsed joint information (type, axis, limits, mimic) of a joint.
+    pub fn joint(&self, joint_name: &str) -> Option<&JointInfo> {
+        self.joint_info_map.get(joint_name)
+    }
+
+    pub(crate) fn joint_info_map(&self) -> &HashMap<String, JointInfo> {
+        &self.joint_info_map
+    }
+
+    /// Joints from the root link down to `link_name`, or `None` for an unknown link.
+    pub fn joint_chain(&self, link_name: &str) -> Option<Vec<&str>> {
+        if !self.bfs_order.iter().any(|link| link == link_name) {
+            return None;
+        }
+        let mut chain = Vec::new();
+        let mut link = link_name;
+        // Bounded in case of links with several parent joints
+        while let Some(joint_name) = self.joint_by_child.get(link) {
+            if chain.len() > self.joint_info_map.len() {
+                break;
+            }
+            chain.push(joint_name.as_str());
+            link = &self.joint_info_map[joint_name].parent_link;
+        }
+        chain.reverse();
+        Some(chain)
+    }
+
+    /// Pose of every link relative to the root link for the given joint positions
+    /// (radians for revolute/continuous joints, meters for prismatic ones).
+    ///
+    /// Mimic joints follow their leader unless given a position themselves; other joints
+    /// without a position stay at zero.
+    pub fn forward_kinematics(
+        &self,
+        joint_positions: &HashMap<String, f64>,
+    ) -> HashMap<String, na::Isometry3<f64>> {
+        let mut positions = resolve_mimic_positions(&self.joint_info_map, joint_positions);
+        positions.extend(joint_positions.iter().map(|(k, v)| (k.clone(), *v)));
+        compute_link_poses(&self.bfs_order, &self.joint_info_map, &positions)
+    }
+
+    /// Pose of a single link relative to the root link, or `None` for an unknown link.
+    pub fn link_pose(
+        &self,
+        link_name: &str,
+        joint_positions: &HashMap<String, f64>,
+    ) -> Option<na::Isometry3<f64>> {
+        self.forward_kinematics(joint_positions).remove(link_name)
+    }
+
+    /// Joint positions recorded in a KREC frame, converted to the joints' units.
+    ///
+    /// `actuator_map` maps actuator IDs to joint names, e.g. from
+    /// `utils::joint_map_utils::load_actuator_joint_map`;
+    /// actuators without a mapped joint or a recorded position are skipped.
+    pub fn joint_positions_from_krec_frame(
+        &self,
+        frame: &KRecFrame,
+        actuator_map: &HashMap<u32, String>,
+    ) -> HashMap<String, f64> {
+        frame
+            .actuator_states
+            .iter()
+            .filter_map(|state| {
+                let joint_name = actuator_map.get(&state.actuator_id)?;
+                let joint_info = self.joint_info_map.get(joint_name)?;
+                let position = recorded_position_to_joint_units(joint_info, state.position?);
+                Some((joint_name.clone(), position))
+            })
+            .collect()
+    }
+}
@@ -1,25 +1,173 @@
 use std::collections::HashMap;
-use std::fs;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
-use anyhow::Result;
-use log::info;
+use anyhow::{anyhow, bail, Result};
+use log::{info, warn};
 use rerun::{
-    archetypes::{Mesh3D, Transform3D, ViewCoordinates},
+    archetypes::{
+        Arrows3D, Boxes3D, Ellipsoids3D, LineStrips3D, Mesh3D, Points3D, Transform3D,
+        ViewCoordinates,
+    },
     datatypes::ImageFormat,
     RecordingStream,
 };
-use urdf_rs::{self, Geometry, Link, Material, Robot};
+use urdf_rs::{self, Geometry, JointType, Link, Material, Robot};
 
 use crate::utils::debug_log_utils::{debug_log_rerun_mesh, debug_log_rerun_transform};
 use crate::utils::geometry_utils::{
-    apply_4x4_to_mesh3d, create_box_mesh, create_cylinder_mesh, create_sphere_mesh,
-    float_rgba_to_u8, load_image_as_rerun_buffer, load_stl_as_mesh3d,
+    apply_4x4_to_mesh3d, apply_scale_to_mesh3d, create_box_mesh, create_capsule_mesh,
+    create_cylinder_mesh, create_sphere_mesh, float_rgba_to_u8, load_image_as_rerun_buffer,
+    load_mesh_file,
 };
+use crate::utils::inertial_utils::{mass_point_radius, LinkInertial};
+use crate::utils::joint_overlay_utils::{
+    prismatic_limit_segment, revolute_limit_outline, unit_axis,
+};
+use crate::utils::mjcf_utils::{is_mjcf, mjcf_to_urdf};
+use crate::utils::resource_path_utils::ResourceResolver;
 use crate::utils::spatial_transform_utils::{
-    build_4x4_from_xyz_rpy, decompose_4x4_to_translation_and_mat3x3,
+    build_4x4_from_xyz_rpy, decompose_4x4_to_translation_and_mat3x3, transform_point_4x4,
 };
-use crate::utils::urdf_bfs_utils::{build_link_bfs_map, LinkBfsData};
+use crate::utils::urdf_bfs_utils::{build_link_bfs_map, join_entity_path, LinkBfsData};
+use crate::utils::urdf_tree_utils::{check_tree, prune_to_tree};
+use crate::utils::xacro_utils::{expand_xacro, is_xacro};
+
+/// Options controlling how a URDF is loaded and logged.
+#[derive(Debug, Clone, Default)]
+pub struct UrdfLogOptions {
+    /// Extra roots searched for `package://` mesh URIs, before `ROS_PACKAGE_PATH`.
+    pub package_paths: Vec<String>,
+    /// Values for `$(arg ...)` when expanding a xacro file, overriding `<xacro:arg>` defaults.
+    pub xacro_args: HashMap<String, String>,
+    /// Also log each link's collision geometry, under `<link>/collision_N`.
+    pub show_collisions: bool,
+    /// Log each link's center of mass and its inertia drawn as this shape, under
+    /// `<link>/inertial`.
+    pub show_inertia: Option<InertiaDisplay>,
+    /// Draw every joint's frame, axis and limit range.
+    pub show_joint_overlays: bool,
+    /// Link the robot hangs from, instead of the first link without a parent joint.
+    pub root_link: Option<String>,
+    /// Fail on problems in the link/joint tree instead of warning and leaving links out.
+    pub strict: bool,
+}
+
+/// Shape drawn for a link's inertia: the uniform solid with the same mass and inertia,
+/// along the principal axes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InertiaDisplay {
+    Box,
+    Ellipsoid,
+}
+
+impl FromStr for InertiaDisplay {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "box" => Ok(Self::Box),
+            "ellipsoid" => Ok(Self::Ellipsoid),
+            _ => Err(format!("expected 'box' or 'ellipsoid', got {s:?}")),
+        }
+    }
+}
+
+/// Translucent orange, so collision shapes stand out from the visuals they overlap.
+const COLLISION_RGBA: [u8; 4] = [255, 140, 0, 110];
+/// Color of center of mass markers.
+pub(crate) const CENTER_OF_MASS_RGBA: [u8; 4] = [255, 220, 0, 255];
+/// Color of the inertia boxes/ellipsoids.
+const INERTIA_RGBA: [u8; 4] = [0, 200, 255, 255];
+/// Length of the joint frame triad arrows (m); joint axis arrows are 1.5 times longer
+/// and revolute limit arcs have this radius.
+const JOINT_OVERLAY_SIZE: f32 = 0.08;
+/// Color of joint axis arrows and limit ranges.
+const JOINT_OVERLAY_RGBA: [u8; 4] = [255, 0, 255, 255];
+
+/// A robot loaded from a URDF or MJCF file.
+pub(crate) struct RobotDescription {
+    /// Only what hangs from `root_link`, so every link has a single path from it.
+    pub robot: Robot,
+    /// The link the tree hangs from.
+    pub root_link: String,
+    /// Resolves mesh and texture file names found in the description.
+    pub resolver: ResourceResolver,
+    /// Radii of MJCF ellipsoid visuals, which `robot` holds as unit spheres.
+    pub ellipsoids: HashMap<(String, usize), [f64; 3]>,
+    /// Same as `ellipsoids`, for collisions.
+    pub collision_ellipsoids: HashMap<(String, usize), [f64; 3]>,
+}
+
+/// Load a robot description, expanding xacro and converting MJCF (a `<mujoco>` root)
+/// to URDF on the way.
+pub(crate) fn load_robot_description(
+    path: &str,
+    options: &UrdfLogOptions,
+) -> Result<RobotDescription> {
+    let mut contents = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read robot description {path:?}: {e}"))?;
+
+    let base_dir = Path::new(path)
+        .parent()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let resolver = ResourceResolver::new(&base_dir, &options.package_paths);
+
+    if path.ends_with(".xacro") || is_xacro(&contents) {
+        info!("Expanding xacro file {}", path);
+        contents = expand_xacro(&contents, &resolver, &options.xacro_args)
+            .map_err(|e| anyhow!("Failed to expand xacro {path:?}: {e}"))?;
+    }
+
+    let (robot, ellipsoids, collision_ellipsoids) = if is_mjcf(&contents) {
+        info!("Converting MJCF model {} to URDF", path);
+        let conversion =
+            mjcf_to_urdf(&contents).map_err(|e| anyhow!("Failed to convert MJCF {path:?}: {e}"))?;
+        let robot = urdf_rs::read_from_string(&conversion.urdf)
+            .map_err(|e| anyhow!("Failed to load converted MJCF {path:?}: {e}"))?;
+        (
+            robot,
+            conversion.ellipsoids,
+            conversion.collision_ellipsoids,
+        )
+    } else {
+        let robot = urdf_rs::read_from_string(&contents)
+            .map_err(|e| anyhow!("Failed to parse URDF {path:?}: {e}"))?;
+        (robot, HashMap::new(), HashMap::new())
+    };
+    let (robot, root_link) = check_robot_tree(&robot, options)
+        .map_err(|e| anyhow!("Invalid robot description {path:?}: {e}"))?;
+
+    Ok(RobotDescription {
+        robot,
+        root_link,
+        resolver,
+        ellipsoids,
+        collision_ellipsoids,
+    })
+}
+
+/// Check the link/joint tree and keep only what hangs from the root link, so every link
+/// has a single path from it. Returns the pruned robot and its root link.
+pub(crate) fn check_robot_tree(robot: &Robot, options: &UrdfLogOptions) -> Result<(Robot, String)> {
+    let report = check_tree(robot, options.root_link.as_deref())?;
+    if !report.issues.is_empty() {
+        let issues: String = report
+            .issues
+            .iter()
+            .map(|issue| format!("\n  - {issue}"))
+            .collect();
+        if options.strict {
+            bail!("link/joint tree problems:{issues}");
+        }
+        warn!(
+            "Link/joint tree problems, showing what hangs from root link '{}':{}",
+            report.root, issues
+        );
+    }
+    Ok((prune_to_tree(robot, &report.root), report.root))
+}
 
 // -----------------------------------------------------------------------------
 // Minimal info (color & texture path) from a URDF Material.
@@ -32,21 +180,79 @@ struct RrMaterialInfo {
     texture_path: Option<PathBuf>,
 }
 
+/// Entity paths of a link's visuals, as logged by `log_link_meshes_at_identity`.
+pub(crate) fn visual_entity_paths(link: &Link, link_entity_path: &str) -> Vec<String> {
+    (0..link.visual.len())
+        .map(|i| format!("{}/visual_{}", link_entity_path, i))
+        .collect()
+}
+
+/// Build the mesh of a URDF geometry, in the geometry's own frame.
+///
+/// `ellipsoid` holds the radii of an MJCF ellipsoid stored as a unit sphere.
+fn geometry_to_mesh3d(
+    geometry: &Geometry,
+    ellipsoid: Option<&[f64; 3]>,
+    resolver: &ResourceResolver,
+) -> Mesh3D {
+    match geometry {
+        Geometry::Mesh { filename, scale } => {
+            let mut mesh = match resolver.resolve(filename) {
+                Ok(abs_path) => match load_mesh_file(&abs_path) {
+                    Ok(m) => m,
+                    Err(e) => {
+                        eprintln!("Error loading mesh {abs_path:?}: {e}");
+                        Mesh3D::new(Vec::<[f32; 3]>::new())
+                    }
+                },
+                Err(e) => {
+                    eprintln!("Error loading mesh: {e}");
+                    Mesh3D::new(Vec::<[f32; 3]>::new())
+                }
+            };
+            if let Some(scale) = scale {
+                apply_scale_to_mesh3d(
+                    &mut mesh,
+                    [scale[0] as f32, scale[1] as f32, scale[2] as f32],
+                );
+            }
+            mesh
+        }
+        Geometry::Box { size } => create_box_mesh([size[0], size[1], size[2]]),
+        Geometry::Cylinder { radius, length } => create_cylinder_mesh(*radius, *length),
+        Geometry::Capsule { radius, length } => create_capsule_mesh(*radius, *length),
+        Geometry::Sphere { radius } => {
+            let mut mesh = create_sphere_mesh(*radius);
+            if let Some(radii) = ellipsoid {
+                apply_scale_to_mesh3d(
+                    &mut mesh,
+                    [radii[0] as f32, radii[1] as f32, radii[2] as f32],
+                );
+            }
+            mesh
+        }
+    }
+}
+
 /// Logs a link’s meshes in Rerun using the “baked” transform (including BFS).
+///
+/// With a `tint_rgba`, every visual is drawn in that single color instead of its material.
 pub fn log_link_meshes_at_identity(
     link: &Link,
     link_bfs_map: &HashMap<String, LinkBfsData>,
-    urdf_dir: &Path,
+    description: &RobotDescription,
     all_mat_map: &HashMap<String, &Material>,
     rec: &RecordingStream,
-    _robot: &Robot,
+    entity_prefix: &str,
+    tint_rgba: Option<[u8; 4]>,
 ) -> Result<()> {
     // Get the entity path from BFS data
     let link_bfs_data = link_bfs_map
         .get(&link.name)
         .unwrap_or_else(|| panic!("No BFS data for link '{}'", link.name));
 
-    let entity_path = link_bfs_data.link_only_path.clone();
+    let entity_path = join_entity_path(entity_prefix, &link_bfs_data.link_only_path);
+    let resolver = &description.resolver;
 
     // 2) For each visual in this link, create and log geometry
     for (i, vis) in link.visual.iter().enumerate() {
@@ -57,50 +263,16 @@ pub fn log_link_meshes_at_identity(
         if let Some(m) = &vis.material {
             if m.color.is_none() && m.texture.is_none() {
                 if let Some(global_mat) = all_mat_map.get(&m.name) {
-                    mat_info = parse_urdf_material(global_mat, urdf_dir);
+                    mat_info = parse_urdf_material(global_mat, resolver);
                 }
             } else {
-                mat_info = parse_urdf_material(m, urdf_dir);
+                mat_info = parse_urdf_material(m, resolver);
             }
         }
 
         // Build geometry info
-        let mut mesh3d = match &vis.geometry {
-            Geometry::Mesh { filename, scale: _ } => {
-                let joined = urdf_dir.join(filename);
-                // canonicalize will remove things like "../"
-                let abs_path = match fs::canonicalize(&joined) {
-                    Ok(resolved) => resolved,
-                    Err(_) => joined, // If canonicalize fails, use joined as fallback
-                };
-
-                if abs_path
-                    .extension()
-                    .and_then(|e| e.to_str())
-                    .map(|s| s.to_lowercase())
-                    .as_deref()
-                    == Some("stl")
-                {
-                    match load_stl_as_mesh3d(&abs_path) {
-                        Ok(m) => m,
-                        Err(e) => {
-                            eprintln!("Error loading STL {abs_path:?}: {e}");
-                            Mesh3D::new(Vec::<[f32; 3]>::new())
-                        }
-                    }
-                } else {
-                    eprintln!("Currently only .stl files are handled: {abs_path:?}");
-                    Mesh3D::new(Vec::<[f32; 3]>::new())
-                }
-            }
-            Geometry::Box { size } => create_box_mesh([size[0], size[1], size[2]]),
-            Geometry::Cylinder { radius, length } => create_cylinder_mesh(*radius, *length),
-            Geometry::Sphere { radius } => create_sphere_mesh(*radius),
-            _ => {
-                eprintln!("Unsupported geometry type");
-                Mesh3D::new(Vec::<[f32; 3]>::new())
-            }
-        };
+        let ellipsoid = description.ellipsoids.get(&(link.name.clone(), i));
+        let mut mesh3d = geometry_to_mesh3d(&vis.geometry, ellipsoid, resolver);
 
         // Transform the geometry by the local visual.origin:
         let origin = &vis.origin;
@@ -111,8 +283,8 @@ pub fn log_link_meshes_at_identity(
         // Bake geometry
         apply_4x4_to_mesh3d(&mut mesh3d, local_tf_4x4);
 
-        // optional color
-        if let Some(rgba) = mat_info.color_rgba {
+        // optional color, unless the mesh file brought its own
+        if let (Some(rgba), None) = (mat_info.color_rgba, &mesh3d.vertex_colors) {
             let col_u8 = float_rgba_to_u8(rgba);
             let n_verts = mesh3d.vertex_positions.len();
             let mut all_colors = Vec::with_capacity(n_verts);
@@ -121,7 +293,16 @@ pub fn log_link_meshes_at_identity(
             }
             mesh3d = mesh3d.with_vertex_colors(all_colors);
         }
-        if let Some(tex_path) = &mat_info.texture_path {
+        if let Some(tint) = tint_rgba {
+            let n_verts = mesh3d.vertex_positions.len();
+            mesh3d = mesh3d
+                .with_vertex_colors(vec![tint; n_verts])
+                .with_albedo_factor(tint);
+            mesh3d.albedo_texture_buffer = None;
+            mesh3d.albedo_texture_format = None;
+        } else if let (Some(tex_path), None) =
+            (&mat_info.texture_path, &mesh3d.albedo_texture_buffer)
+        {
             match load_image_as_rerun_buffer(tex_path) {
                 Ok(img_buf) => {
                     let (w, h) = image::image_dimensions(tex_path).unwrap_or((1, 1));
@@ -147,25 +328,236 @@ pub fn log_link_meshes_at_identity(
     Ok(())
 }
 
+/// Logs a link's collision shapes under `<link>/collision_N`, drawn translucent in
+/// `COLLISION_RGBA` so they can be compared with the visuals.
+pub(crate) fn log_link_collisions_at_identity(
+    link: &Link,
+    link_bfs_map: &HashMap<String, LinkBfsData>,
+    description: &RobotDescription,
+    rec: &RecordingStream,
+    entity_prefix: &str,
+) -> Result<()> {
+    let link_bfs_data = link_bfs_map
+        .get(&link.name)
+        .unwrap_or_else(|| panic!("No BFS data for link '{}'", link.name));
+    let entity_path = join_entity_path(entity_prefix, &link_bfs_data.link_only_path);
+
+    for (i, collision) in link.collision.iter().enumerate() {
+        let collision_entity_path = format!("{}/collision_{}", entity_path, i);
+
+        let ellipsoid = description
+            .collision_ellipsoids
+            .get(&(link.name.clone(), i));
+        let mut mesh3d = geometry_to_mesh3d(&collision.geometry, ellipsoid, &description.resolver);
+
+        let origin = &collision.origin;
+        let xyz = [origin.xyz[0], origin.xyz[1], origin.xyz[2]];
+        let rpy = [origin.rpy[0], origin.rpy[1], origin.rpy[2]];
+        apply_4x4_to_mesh3d(&mut mesh3d, build_4x4_from_xyz_rpy(xyz, rpy));
+
+        // Drop whatever the mesh file brought, collisions all share one style
+        let n_verts = mesh3d.vertex_positions.len();
+        mesh3d = mesh3d
+            .with_vertex_colors(vec![COLLISION_RGBA; n_verts])
+            .with_albedo_factor(COLLISION_RGBA);
+        mesh3d.albedo_texture_buffer = None;
+        mesh3d.albedo_texture_format = None;
+        mesh3d.vertex_texcoords = None;
+
+        debug_log_rerun_mesh(
+            &collision_entity_path,
+            Some(link_bfs_data),
+            collision.origin.rpy,
+            collision.origin.xyz,
+            &mesh3d.vertex_positions,
+            "Stage1 collision logging",
+        );
+
+        rec.log(collision_entity_path.as_str(), &mesh3d)?;
+    }
+
+    Ok(())
+}
+
+/// Logs a link's center of mass under `<link>/inertial/center_of_mass`, as a point whose
+/// size grows with the mass, and its equivalent inertia box or ellipsoid under
+/// `<link>/inertial/inertia`. Massless links are skipped.
+pub(crate) fn log_link_inertial_at_identity(
+    link: &Link,
+    link_bfs_map: &HashMap<String, LinkBfsData>,
+    display: InertiaDisplay,
+    rec: &RecordingStream,
+    entity_prefix: &str,
+) -> Result<()> {
+    let inertial = LinkInertial::from_urdf(&link.inertial);
+    if inertial.mass <= 0.0 {
+        return Ok(());
+    }
+    let link_bfs_data = link_bfs_map
+        .get(&link.name)
+        .unwrap_or_else(|| panic!("No BFS data for link '{}'", link.name));
+    let entity_path = format!(
+        "{}/inertial",
+        join_entity_path(entity_prefix, &link_bfs_data.link_only_path)
+    );
+
+    let center = inertial.center_of_mass.map(|v| v as f32);
+    rec.log(
+        format!("{}/center_of_mass", entity_path),
+        &Points3D::new([center])
+            .with_radii([mass_point_radius(inertial.mass) as f32])
+            .with_colors([CENTER_OF_MASS_RGBA])
+            .with_labels([format!("{:.3} kg", inertial.mass)]),
+    )?;
+
+    let q = inertial.principal_axes;
+    let rotation = rerun::Quaternion::from_xyzw([q.i as f32, q.j as f32, q.k as f32, q.w as f32]);
+    let inertia_path = format!("{}/inertia", entity_path);
+    match display {
+        InertiaDisplay::Box => {
+            let half_sizes = inertial.equivalent_box_half_sizes().map(|v| v as f32);
+            rec.log(
+                inertia_path,
+                &Boxes3D::from_centers_and_half_sizes([center], [half_sizes])
+                    .with_quaternions([rotation])
+                    .with_colors([INERTIA_RGBA]),
+            )?;
+        }
+        InertiaDisplay::Ellipsoid => {
+            let radii = inertial.equivalent_ellipsoid_radii().map(|v| v as f32);
+            rec.log(
+                inertia_path,
+                &Ellipsoids3D::from_centers_and_half_sizes([center], [radii])
+                    .with_quaternions([rotation])
+                    .with_colors([INERTIA_RGBA]),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Logs joint overlays for every joint of the tree:
+/// - under the child link, `joint_frame` (an x/y/z triad at the joint origin, which follows
+///   the joint as it moves) and `joint_axis` (an arrow along the joint axis);
+/// - under the parent link, `joint_limits/<joint>`: the limit range of revolute joints as
+///   an arc about the axis and of prismatic joints as a segment along it.
+pub(crate) fn log_joint_overlays(
+    robot: &Robot,
+    link_bfs_map: &HashMap<String, LinkBfsData>,
+    rec: &RecordingStream,
+    entity_prefix: &str,
+) -> Result<()> {
+    let size = JOINT_OVERLAY_SIZE;
+
+    for joint in &robot.joints {
+        let (Some(parent), Some(child)) = (
+            link_bfs_map.get(&joint.parent.link),
+            link_bfs_map.get(&joint.child.link),
+        ) else {
+            continue;
+        };
+        let child_path = join_entity_path(entity_prefix, &child.link_only_path);
+        let parent_path = join_entity_path(entity_prefix, &parent.link_only_path);
+
+        // The child link frame is the joint frame
+        rec.log(
+            format!("{}/joint_frame", child_path),
+            &Arrows3D::from_vectors([[size, 0.0, 0.0], [0.0, size, 0.0], [0.0, 0.0, size]])
+                .with_colors([[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]]),
+        )?;
+
+        let axis = [joint.axis.xyz[0], joint.axis.xyz[1], joint.axis.xyz[2]];
+        let moves = matches!(
+            joint.joint_type,
+            JointType::Revolute | JointType::Continuous | JointType::Prismatic
+        );
+        let Some(unit) = unit_axis(axis).filter(|_| moves) else {
+            continue;
+        };
+        rec.log(
+            format!("{}/joint_axis", child_path),
+            &Arrows3D::from_vectors([unit.map(|a| a as f32 * size * 1.5)])
+                .with_colors([JOINT_OVERLAY_RGBA])
+                .with_labels([joint.name.clone()]),
+        )?;
+
+        // Limits are drawn in the parent frame, so they stay put while the joint moves
+        let (lower, upper) = (joint.limit.lower, joint.limit.upper);
+        let outline = match joint.joint_type {
+            JointType::Revolute => revolute_limit_outline(axis, lower, upper, f64::from(size), 32),
+            JointType::Prismatic => prismatic_limit_segment(axis, lower, upper)
+                .map(Vec::from)
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        if outline.is_empty() {
+            continue;
+        }
+        let origin = build_4x4_from_xyz_rpy(
+            [
+                joint.origin.xyz[0],
+                joint.origin.xyz[1],
+                joint.origin.xyz[2],
+            ],
+            [
+                joint.origin.rpy[0],
+                joint.origin.rpy[1],
+                joint.origin.rpy[2],
+            ],
+        );
+        let outline: Vec<[f32; 3]> = outline
+            .into_iter()
+            .map(|p| transform_point_4x4(origin, p))
+            .collect();
+        rec.log(
+            format!("{}/joint_limits/{}", parent_path, joint.name),
+            &LineStrips3D::new([outline]).with_colors([JOINT_OVERLAY_RGBA]),
+        )?;
+    }
+
+    Ok(())
+}
+
 // ----------------------------------------------------------------------------
 // Exported function for main.rs usage
-pub fn parse_and_log_urdf_hierarchy(urdf_path: &str, rec: &RecordingStream) -> Result<()> {
+pub fn parse_and_log_urdf_hierarchy(
+    urdf_path: &str,
+    options: &UrdfLogOptions,
+    rec: &RecordingStream,
+) -> Result<()> {
+    let description = load_robot_description(urdf_path, options)?;
+    log_robot_description(&description, options, rec)
+}
+
+/// Log an already loaded robot description at the root of the recording.
+pub(crate) fn log_robot_description(
+    description: &RobotDescription,
+    options: &UrdfLogOptions,
+    rec: &RecordingStream,
+) -> Result<()> {
     // Set the coordinate system to right-handed with Z up
     rec.log("", &ViewCoordinates::RIGHT_HAND_Z_UP)?;
 
-    let robot = urdf_rs::read_file(urdf_path)
-        .map_err(|e| anyhow::anyhow!("Failed to parse URDF {urdf_path:?}: {e}"))?;
+    log_urdf_hierarchy(description, options, "", None, rec)
+}
+
+/// Log a copy of the robot under `entity_prefix`, optionally drawn in a single tint
+/// (used e.g. for the "ghost" robot posed from actuator commands).
+pub(crate) fn log_urdf_hierarchy(
+    description: &RobotDescription,
+    options: &UrdfLogOptions,
+    entity_prefix: &str,
+    tint_rgba: Option<[u8; 4]>,
+    rec: &RecordingStream,
+) -> Result<()> {
+    let robot = &description.robot;
 
     // Build BFS data once
-    let (link_bfs_map, bfs_order) = build_link_bfs_map(&robot);
+    let (link_bfs_map, bfs_order) = build_link_bfs_map(robot, &description.root_link);
 
     info!("Logging URDF geometry at identity");
 
-    let urdf_dir = Path::new(urdf_path)
-        .parent()
-        .map(PathBuf::from)
-        .unwrap_or_else(|| PathBuf::from("."));
-
     // Gather materials
     let mut mat_map = HashMap::new();
     for m in &robot.materials {
@@ -174,7 +566,29 @@ pub fn parse_and_log_urdf_hierarchy(urdf_path: &str, rec: &RecordingStream) -> R
 
     // Log geometry for each link
     for link in &robot.links {
-        log_link_meshes_at_identity(link, &link_bfs_map, &urdf_dir, &mat_map, rec, &robot)?;
+        log_link_meshes_at_identity(
+            link,
+            &link_bfs_map,
+            description,
+            &mat_map,
+            rec,
+            entity_prefix,
+            tint_rgba,
+        )?;
+        // Collisions and inertias belong to the main robot, not to tinted copies of it
+        if tint_rgba.is_some() {
+            continue;
+        }
+        if options.show_collisions {
+            log_link_collisions_at_identity(link, &link_bfs_map, description, rec, entity_prefix)?;
+        }
+        if let Some(display) = options.show_inertia {
+            log_link_inertial_at_identity(link, &link_bfs_map, display, rec, entity_prefix)?;
+        }
+    }
+
+    if options.show_joint_overlays && tint_rgba.is_none() {
+        log_joint_overlays(robot, &link_bfs_map, rec, entity_prefix)?;
     }
 
     // Stage 2: Apply transforms in BFS order
@@ -185,9 +599,10 @@ pub fn parse_and_log_urdf_hierarchy(urdf_path: &str, rec: &RecordingStream) -> R
         let (translation, mat3x3) =
             decompose_4x4_to_translation_and_mat3x3(link_data.local_transform);
         let tf = Transform3D::from_translation(translation).with_mat3x3(mat3x3);
+        let entity_path = join_entity_path(entity_prefix, &link_data.link_only_path);
 
         debug_log_rerun_transform(
-            &link_data.link_only_path,
+            &entity_path,
             Some(link_data),
             link_data.local_rpy,
             translation,
@@ -195,7 +610,7 @@ pub fn parse_and_log_urdf_hierarchy(urdf_path: &str, rec: &RecordingStream) -> R
             "Stage2 BFS apply transform",
         );
 
-        rec.log(&*link_data.link_only_path, &tf)?;
+        rec.log(entity_path, &tf)?;
     }
 
     Ok(())
@@ -203,7 +618,7 @@ pub fn parse_and_log_urdf_hierarchy(urdf_path: &str, rec: &RecordingStream) -> R
 
 // ----------------------------------------------------------------------------
 // parse_urdf_material
-fn parse_urdf_material(mat: &Material, urdf_dir: &Path) -> RrMaterialInfo {
+fn parse_urdf_material(mat: &Material, resolver: &ResourceResolver) -> RrMaterialInfo {
     let mut info = RrMaterialInfo::default();
     if let Some(c) = &mat.color {
         info.color_rgba = Some([
@@ -214,9 +629,9 @@ fn parse_urdf_material(mat: &Material, urdf_dir: &Path) -> RrMaterialInfo {
         ]);
     }
     if let Some(tex) = &mat.texture {
-        let tex_path = urdf_dir.join(&tex.filename);
-        if tex_path.exists() {
-            info.texture_path = Some(tex_path);
+        match resolver.resolve(&tex.filename) {
+            Ok(tex_path) => info.texture_path = Some(tex_path),
+            Err(e) => eprintln!("Warning: texture: {e}"),
         }
     }
     info
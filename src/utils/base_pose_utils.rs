@@ -0,0 +1,215 @@
+// base_pose_utils.rs
+
+use anyhow::{anyhow, bail, Result};
+use nalgebra as na;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// How the samples of a base-pose file are matched to KREC frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoseKey {
+    /// The `frame` column holds KREC frame indices
+    Frame,
+    /// The `time` column holds seconds since the first KREC frame
+    Time,
+}
+
+/// Pose of the robot's root link in the world at one instant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BasePose {
+    pub position: na::Vector3<f64>,
+    /// `None` when the source only has positions
+    pub orientation: Option<na::UnitQuaternion<f64>>,
+}
+
+/// Base poses loaded from a CSV (e.g. motion-capture export), interpolated between samples.
+///
+/// The header names the columns: `frame` or `time` (seconds), `x`, `y`, `z` (meters) and
+/// optionally `qx`, `qy`, `qz`, `qw` or `roll`, `pitch`, `yaw` (radians). Other columns are
+/// ignored, as are blank lines and lines starting with `#`.
+#[derive(Debug, Clone)]
+pub struct BasePoseTrack {
+    key: PoseKey,
+    /// Samples sorted by key
+    samples: Vec<(f64, BasePose)>,
+}
+
+impl BasePoseTrack {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read base pose file {path:?}: {e}"))?;
+        Self::from_csv_str(&text).map_err(|e| anyhow!("Invalid base pose file {path:?}: {e}"))
+    }
+
+    pub fn from_csv_str(text: &str) -> Result<Self> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let (_, header) = lines.next().ok_or_else(|| anyhow!("missing header line"))?;
+        let columns: HashMap<String, usize> = header
+            .split(',')
+            .enumerate()
+            .map(|(i, name)| (name.trim().to_lowercase(), i))
+            .collect();
+        let column = |names: &[&str]| names.iter().find_map(|name| columns.get(*name).copied());
+
+        let (key, key_column) = match (column(&["frame", "frame_idx"]), column(&["time", "t"])) {
+            (Some(frame), _) => (PoseKey::Frame, frame),
+            (None, Some(time)) => (PoseKey::Time, time),
+            (None, None) => bail!("needs a `frame` or `time` column"),
+        };
+        let position_columns = ["x", "y", "z"]
+            .map(|name| column(&[name]).ok_or_else(|| anyhow!("missing `{name}` column")));
+        let [x, y, z] = position_columns;
+        let (x, y, z) = (x?, y?, z?);
+        let quaternion_columns = ["qx", "qy", "qz", "qw"].map(|name| column(&[name]));
+        let rpy_columns = ["roll", "pitch", "yaw"].map(|name| column(&[name]));
+
+        let mut samples = Vec::new();
+        for (line_number, line) in lines {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let value = |index: usize| -> Result<f64> {
+                let field = fields
+                    .get(index)
+                    .ok_or_else(|| anyhow!("line {line_number}: missing column {}", index + 1))?;
+                field
+                    .parse()
+                    .map_err(|e| anyhow!("line {line_number}: invalid number {field:?}: {e}"))
+            };
+
+            let orientation = if let [Some(qx), Some(qy), Some(qz), Some(qw)] = quaternion_columns {
+                let q = na::Quaternion::new(value(qw)?, value(qx)?, value(qy)?, value(qz)?);
+                if q.norm() < 1e-9 {
+                    bail!("line {line_number}: zero quaternion");
+                }
+                Some(na::UnitQuaternion::from_quaternion(q))
+            } else if let [Some(roll), Some(pitch), Some(yaw)] = rpy_columns {
+                Some(na::UnitQuaternion::from_euler_angles(
+                    value(roll)?,
+                    value(pitch)?,
+                    value(yaw)?,
+                ))
+            } else {
+                None
+            };
+
+            let pose = BasePose {
+                position: na::Vector3::new(value(x)?, value(y)?, value(z)?),
+                orientation,
+            };
+            samples.push((value(key_column)?, pose));
+        }
+        if samples.is_empty() {
+            bail!("no samples");
+        }
+        samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Ok(BasePoseTrack { key, samples })
+    }
+
+    pub fn key(&self) -> PoseKey {
+        self.key
+    }
+
+    /// The base pose at a frame index or time (per [`PoseKey`]), interpolated linearly
+    /// between samples and held before the first and after the last one.
+    pub fn pose_at(&self, key_value: f64) -> BasePose {
+        let after = self.samples.partition_point(|(k, _)| *k <= key_value);
+        if after == 0 {
+            return self.samples[0].1;
+        }
+        if after == self.samples.len() {
+            return self.samples[after - 1].1;
+        }
+
+        let (k0, p0) = &self.samples[after - 1];
+        let (k1, p1) = &self.samples[after];
+        let t = if k1 > k0 {
+            (key_value - k0) / (k1 - k0)
+        } else {
+            0.0
+        };
+        BasePose {
+            position: p0.position.lerp(&p1.position, t),
+            orientation: match (p0.orientation, p1.orientation) {
+                (Some(q0), Some(q1)) => Some(q0.slerp(&q1, t)),
+                (q0, _) => q0,
+            },
+        }
+    }
+}
+
+/// How much lower (m) another stance link must get than the planted one to take over.
+pub const STANCE_SWITCH_MARGIN: f64 = 0.005;
+
+/// Base position estimated from the leg kinematics, assuming the lowest stance link (e.g.
+/// a foot) stays planted on flat ground at z = 0 until another one gets lower.
+#[derive(Debug, Clone, Default)]
+pub struct LegOdometry {
+    base_position: na::Vector3<f64>,
+    /// The planted link and its world position
+    anchor: Option<(String, na::Vector3<f64>)>,
+}
+
+impl LegOdometry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name of the link currently assumed planted.
+    pub fn stance_link(&self) -> Option<&str> {
+        self.anchor.as_ref().map(|(link, _)| link.as_str())
+    }
+
+    /// Update the base position for the current base orientation (e.g. from the IMU) and
+    /// the stance links' positions relative to the base.
+    ///
+    /// The planted link keeps its world position. A link that gets lower takes over and is
+    /// put on the ground, which also starts the first update with the lowest link at z = 0
+    /// below the origin.
+    pub fn update(
+        &mut self,
+        orientation: &na::UnitQuaternion<f64>,
+        stance_positions: &[(String, na::Vector3<f64>)],
+    ) -> na::Vector3<f64> {
+        let offset = |link: &str| {
+            stance_positions
+                .iter()
+                .find(|(l, _)| l == link)
+                .map(|(_, p)| orientation * p)
+        };
+
+        // Keep the planted link where it is
+        let planted = self
+            .anchor
+            .as_ref()
+            .and_then(|(link, world)| Some((link, world, offset(link)?)));
+        if let Some((_, world, planted_offset)) = &planted {
+            self.base_position = *world - planted_offset;
+        }
+
+        let Some((link, lowest_offset)) = stance_positions
+            .iter()
+            .map(|(link, p)| (link, orientation * p))
+            .min_by(|a, b| a.1.z.total_cmp(&b.1.z))
+        else {
+            return self.base_position;
+        };
+        let world = self.base_position + lowest_offset;
+        let take_over = match &planted {
+            Some((planted_link, planted_world, _)) => {
+                *planted_link != link && world.z < planted_world.z - STANCE_SWITCH_MARGIN
+            }
+            None => true,
+        };
+        if take_over {
+            self.base_position.z -= world.z;
+            self.anchor = Some((link.clone(), na::Vector3::new(world.x, world.y, 0.0)));
+        }
+
+        self.base_position
+    }
+}
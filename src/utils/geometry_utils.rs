@@ -1,14 +1,17 @@
 // geometry_utils.rs
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use nalgebra as na;
-use parry3d::shape::{Ball as ParrySphere, Cuboid as ParryCuboid, Cylinder as ParryCylinder};
+use parry3d::shape::{
+    Ball as ParrySphere, Capsule as ParryCapsule, Cuboid as ParryCuboid, Cylinder as ParryCylinder,
+};
 use rerun::{
     archetypes::Mesh3D,
     components::{ImageBuffer, Position3D, TriangleIndices, Vector3D},
-    datatypes::Blob,
+    datatypes::{Blob, ImageFormat},
 };
-use std::fs::OpenOptions;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
 use std::io::BufReader;
 use std::path::Path;
 
@@ -99,9 +102,45 @@ pub fn apply_4x4_to_mesh3d(mesh: &mut Mesh3D, tf_arr: [f32; 16]) {
     }
 }
 
-/// Load an STL file from disk and convert to a `Mesh3D`.
+/// Scale a mesh along its own x/y/z axes, as given by a URDF `<mesh scale="...">`.
 ///
-/// Currently only handles `.stl`.
+/// Normals are transformed by the inverse-transpose of the scale so they stay perpendicular
+/// to the surface. A negative scale mirrors the mesh, so the triangle winding is flipped to
+/// keep the faces pointing outwards.
+pub fn apply_scale_to_mesh3d(mesh: &mut Mesh3D, scale: [f32; 3]) {
+    if scale == [1.0, 1.0, 1.0] {
+        return;
+    }
+
+    for v_rerun in &mut mesh.vertex_positions {
+        let p = v_rerun.0.0;
+        *v_rerun = Position3D::from([p[0] * scale[0], p[1] * scale[1], p[2] * scale[2]]);
+    }
+
+    if scale[0] * scale[1] * scale[2] < 0.0 {
+        if let Some(triangles) = &mut mesh.triangle_indices {
+            for tri in triangles {
+                tri.0.0.swap(1, 2);
+            }
+        }
+    }
+
+    if scale.contains(&0.0) {
+        // Degenerate scale, the inverse-transpose does not exist
+        compute_vertex_normals(mesh);
+        return;
+    }
+    if let Some(ref mut normals_rerun) = mesh.vertex_normals {
+        for n_rerun in normals_rerun {
+            let n = n_rerun.0.0;
+            let n_na = na::Vector3::new(n[0] / scale[0], n[1] / scale[1], n[2] / scale[2]);
+            let arr: [f32; 3] = n_na.try_normalize(1e-9).unwrap_or(n_na).into();
+            *n_rerun = Vector3D::from(arr);
+        }
+    }
+}
+
+/// Load an STL file (binary or ASCII) from disk and convert to a `Mesh3D`.
 pub fn load_stl_as_mesh3d(abs_path: &Path) -> Result<Mesh3D> {
     let f = OpenOptions::new()
         .read(true)
@@ -109,14 +148,6 @@ pub fn load_stl_as_mesh3d(abs_path: &Path) -> Result<Mesh3D> {
         .map_err(|e| anyhow::anyhow!("Failed to open {abs_path:?}: {e}"))?;
     let mut buf = BufReader::new(f);
 
-    let ext_lower = abs_path
-        .extension()
-        .and_then(|e| e.to_str())
-        .map(|s| s.to_lowercase());
-    if ext_lower.as_deref() != Some("stl") {
-        return Err(anyhow::anyhow!("Currently only .stl handled for {abs_path:?}"));
-    }
-
     match stl_io::read_stl(&mut buf) {
         Ok(stl) => {
             // stl.vertices is Vec<stl_io::Vertex>
@@ -154,6 +185,898 @@ pub fn load_stl_as_mesh3d(abs_path: &Path) -> Result<Mesh3D> {
     }
 }
 
+/// Mesh file formats understood by `load_mesh_file`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshFormat {
+    Stl,
+    Obj,
+    Dae,
+    Gltf,
+    Glb,
+}
+
+impl MeshFormat {
+    /// Guess the format from a file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "stl" => Some(Self::Stl),
+            "obj" => Some(Self::Obj),
+            "dae" => Some(Self::Dae),
+            "gltf" => Some(Self::Gltf),
+            "glb" => Some(Self::Glb),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from the first bytes of a file.
+    pub fn from_magic(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"glTF") {
+            return Some(Self::Glb);
+        }
+        // Binary STL: 80-byte header, triangle count, 50 bytes per triangle
+        if bytes.len() >= 84 {
+            let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+            if bytes.len() == 84 + 50 * count {
+                return Some(Self::Stl);
+            }
+        }
+
+        let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]);
+        let text = head.trim_start_matches('\u{feff}').trim_start();
+        if text.starts_with('<') && head.contains("COLLADA") {
+            return Some(Self::Dae);
+        }
+        if text.starts_with('{') && head.contains("\"asset\"") {
+            return Some(Self::Gltf);
+        }
+        if text.starts_with("solid") {
+            return Some(Self::Stl);
+        }
+        let is_obj_line = |line: &str| {
+            let keyword = line.split_whitespace().next().unwrap_or("");
+            matches!(
+                keyword,
+                "v" | "vn" | "vt" | "f" | "o" | "g" | "s" | "mtllib" | "usemtl"
+            )
+        };
+        if text.lines().any(is_obj_line) {
+            return Some(Self::Obj);
+        }
+        None
+    }
+
+    /// Determine the format of a mesh file, by extension first and by content otherwise.
+    pub fn detect(path: &Path) -> Result<Self> {
+        if let Some(format) = Self::from_path(path) {
+            return Ok(format);
+        }
+        let bytes = fs::read(path).map_err(|e| anyhow!("Failed to read {path:?}: {e}"))?;
+        Self::from_magic(&bytes).ok_or_else(|| anyhow!("Unrecognized mesh format for {path:?}"))
+    }
+}
+
+/// Base color texture of a mesh, decoded to RGBA8.
+#[derive(Debug, Clone)]
+struct MeshTexture {
+    /// Identifies the texture source, so submeshes sharing a texture can be merged.
+    key: String,
+    image: image::RgbaImage,
+}
+
+/// Vertex data and appearance read from a mesh file, before conversion to a `Mesh3D`.
+///
+/// `normals`, `colors` and `texcoords` are either empty or have one entry per position.
+#[derive(Debug, Default)]
+struct MeshBuffers {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    colors: Vec<[u8; 4]>,
+    texcoords: Vec<[f32; 2]>,
+    indices: Vec<[u32; 3]>,
+    texture: Option<MeshTexture>,
+    /// Set once two submeshes with different textures were merged.
+    conflicting_textures: bool,
+}
+
+impl MeshBuffers {
+    /// Append another submesh, filling in attributes that only one side has.
+    fn append(&mut self, other: MeshBuffers) {
+        let n_self = self.positions.len();
+        let n_other = other.positions.len();
+        if n_other == 0 {
+            return;
+        }
+
+        // Normals missing on either side are recomputed for the whole mesh later
+        if n_self > 0 && (self.normals.is_empty() != other.normals.is_empty()) {
+            self.normals.clear();
+        } else if n_self == 0 || !self.normals.is_empty() {
+            self.normals.extend(other.normals);
+        }
+
+        if !self.colors.is_empty() || !other.colors.is_empty() {
+            self.colors.resize(n_self, [255, 255, 255, 255]);
+            if other.colors.is_empty() {
+                self.colors.resize(n_self + n_other, [255, 255, 255, 255]);
+            } else {
+                self.colors.extend(other.colors);
+            }
+        }
+
+        if !self.texcoords.is_empty() || !other.texcoords.is_empty() {
+            self.texcoords.resize(n_self, [0.0, 0.0]);
+            if other.texcoords.is_empty() {
+                self.texcoords.resize(n_self + n_other, [0.0, 0.0]);
+            } else {
+                self.texcoords.extend(other.texcoords);
+            }
+        }
+
+        match (&self.texture, other.texture) {
+            (None, Some(tex)) if !self.conflicting_textures => self.texture = Some(tex),
+            (Some(ours), Some(theirs)) if ours.key != theirs.key => {
+                self.texture = None;
+                self.conflicting_textures = true;
+            }
+            _ => {}
+        }
+
+        let offset = n_self as u32;
+        self.indices.extend(
+            other
+                .indices
+                .iter()
+                .map(|tri| [tri[0] + offset, tri[1] + offset, tri[2] + offset]),
+        );
+        self.positions.extend(other.positions);
+    }
+
+    /// Transform positions by a 4×4 matrix, and normals by its inverse-transpose.
+    fn transform(&mut self, matrix: &na::Matrix4<f32>) {
+        if *matrix == na::Matrix4::identity() {
+            return;
+        }
+        for p in &mut self.positions {
+            *p = matrix.transform_point(&na::Point3::from(*p)).coords.into();
+        }
+        let linear: na::Matrix3<f32> = matrix.fixed_view::<3, 3>(0, 0).into_owned();
+        let normal_matrix = linear
+            .try_inverse()
+            .map(|inv| inv.transpose())
+            .unwrap_or(linear);
+        for n in &mut self.normals {
+            let v = normal_matrix * na::Vector3::from(*n);
+            *n = v.try_normalize(1e-9).unwrap_or(v).into();
+        }
+        // Mirroring transforms flip the winding order
+        if linear.determinant() < 0.0 {
+            for tri in &mut self.indices {
+                tri.swap(1, 2);
+            }
+        }
+    }
+}
+
+/// Scale every channel of an RGBA color by a float RGBA factor.
+fn modulate_rgba(color: [u8; 4], factor: [f32; 4]) -> [u8; 4] {
+    let mut out = [0u8; 4];
+    for i in 0..4 {
+        out[i] = (color[i] as f32 * factor[i]).round().clamp(0.0, 255.0) as u8;
+    }
+    out
+}
+
+/// Load a texture image from disk, identified by its path.
+fn load_texture_file(path: &Path) -> Option<MeshTexture> {
+    match image::open(path) {
+        Ok(img) => Some(MeshTexture {
+            key: path.to_string_lossy().into_owned(),
+            image: img.to_rgba8(),
+        }),
+        Err(e) => {
+            eprintln!("Warning: texture load {path:?}: {e}");
+            None
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Wavefront OBJ
+// -----------------------------------------------------------------------------
+
+/// Read a Wavefront OBJ file (and its `.mtl` materials) into mesh buffers.
+fn read_obj(abs_path: &Path) -> Result<MeshBuffers> {
+    let (models, materials) = tobj::load_obj(abs_path, &tobj::GPU_LOAD_OPTIONS)
+        .map_err(|e| anyhow!("Failed to read OBJ {abs_path:?}: {e}"))?;
+    let materials = materials.unwrap_or_else(|e| {
+        eprintln!("Warning: materials of {abs_path:?}: {e}");
+        Vec::new()
+    });
+    let obj_dir = abs_path.parent().unwrap_or_else(|| Path::new("."));
+
+    let mut buffers = MeshBuffers::default();
+    for model in models {
+        let mesh = model.mesh;
+        let n_verts = mesh.positions.len() / 3;
+        let material = mesh.material_id.and_then(|id| materials.get(id));
+
+        let mut sub = MeshBuffers {
+            positions: mesh
+                .positions
+                .chunks_exact(3)
+                .map(|p| [p[0], p[1], p[2]])
+                .collect(),
+            indices: mesh
+                .indices
+                .chunks_exact(3)
+                .map(|t| [t[0], t[1], t[2]])
+                .collect(),
+            ..Default::default()
+        };
+        if mesh.normals.len() == n_verts * 3 {
+            sub.normals = mesh
+                .normals
+                .chunks_exact(3)
+                .map(|n| [n[0], n[1], n[2]])
+                .collect();
+        }
+        if mesh.texcoords.len() == n_verts * 2 {
+            // OBJ puts the texture origin at the bottom left
+            sub.texcoords = mesh
+                .texcoords
+                .chunks_exact(2)
+                .map(|t| [t[0], 1.0 - t[1]])
+                .collect();
+        }
+
+        let diffuse = material.and_then(|m| {
+            m.diffuse
+                .map(|d| [d[0], d[1], d[2], m.dissolve.unwrap_or(1.0)])
+        });
+        if mesh.vertex_color.len() == n_verts * 3 || diffuse.is_some() {
+            let factor = diffuse.unwrap_or([1.0; 4]);
+            sub.colors = (0..n_verts)
+                .map(|i| {
+                    let base = if mesh.vertex_color.len() == n_verts * 3 {
+                        [
+                            mesh.vertex_color[i * 3],
+                            mesh.vertex_color[i * 3 + 1],
+                            mesh.vertex_color[i * 3 + 2],
+                            1.0,
+                        ]
+                    } else {
+                        [1.0; 4]
+                    };
+                    modulate_rgba(
+                        [255; 4],
+                        [
+                            base[0] * factor[0],
+                            base[1] * factor[1],
+                            base[2] * factor[2],
+                            base[3] * factor[3],
+                        ],
+                    )
+                })
+                .collect();
+        }
+        if let Some(tex_name) = material.and_then(|m| m.diffuse_texture.as_ref()) {
+            sub.texture = load_texture_file(&obj_dir.join(tex_name));
+        }
+
+        buffers.append(sub);
+    }
+    Ok(buffers)
+}
+
+// -----------------------------------------------------------------------------
+// glTF 2.0 / GLB
+// -----------------------------------------------------------------------------
+
+/// Convert a decoded glTF image to RGBA8.
+fn gltf_image_to_rgba(data: &gltf::image::Data) -> Option<image::RgbaImage> {
+    use gltf::image::Format;
+    let pixels: Vec<u8> = match data.format {
+        Format::R8G8B8A8 => data.pixels.clone(),
+        Format::R8G8B8 => data
+            .pixels
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        Format::R8G8 => data
+            .pixels
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        Format::R8 => data.pixels.iter().flat_map(|&p| [p, p, p, 255]).collect(),
+        _ => return None,
+    };
+    image::RgbaImage::from_raw(data.width, data.height, pixels)
+}
+
+/// Read a `.gltf` or `.glb` file into mesh buffers, baking in the node transforms.
+fn read_gltf(abs_path: &Path) -> Result<MeshBuffers> {
+    let (document, buffers, images) =
+        gltf::import(abs_path).map_err(|e| anyhow!("Failed to read glTF {abs_path:?}: {e}"))?;
+
+    let mut out = MeshBuffers::default();
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+        .ok_or_else(|| anyhow!("glTF file {abs_path:?} has no scene"))?;
+
+    let mut stack: Vec<(gltf::Node, na::Matrix4<f32>)> = scene
+        .nodes()
+        .map(|node| (node, na::Matrix4::identity()))
+        .collect();
+    while let Some((node, parent_tf)) = stack.pop() {
+        let local = na::Matrix4::from(node.transform().matrix());
+        let world = parent_tf * local;
+        for child in node.children() {
+            stack.push((child, world));
+        }
+        let Some(mesh) = node.mesh() else {
+            continue;
+        };
+
+        for primitive in mesh.primitives() {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                continue;
+            }
+            let reader = primitive.reader(|b| Some(&buffers[b.index()]));
+            let Some(positions) = reader.read_positions() else {
+                continue;
+            };
+            let positions: Vec<[f32; 3]> = positions.collect();
+            let n_verts = positions.len();
+
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(idx) => idx.into_u32().collect(),
+                None => (0..n_verts as u32).collect(),
+            };
+            let mut sub = MeshBuffers {
+                positions,
+                indices: indices
+                    .chunks_exact(3)
+                    .map(|t| [t[0], t[1], t[2]])
+                    .collect(),
+                ..Default::default()
+            };
+            if let Some(normals) = reader.read_normals() {
+                sub.normals = normals.collect();
+            }
+            if let Some(texcoords) = reader.read_tex_coords(0) {
+                sub.texcoords = texcoords.into_f32().collect();
+            }
+
+            let pbr = primitive.material().pbr_metallic_roughness();
+            let factor = pbr.base_color_factor();
+            let vertex_colors: Option<Vec<[u8; 4]>> =
+                reader.read_colors(0).map(|c| c.into_rgba_u8().collect());
+            if vertex_colors.is_some() || factor != [1.0; 4] {
+                sub.colors = match vertex_colors {
+                    Some(colors) => colors
+                        .into_iter()
+                        .map(|c| modulate_rgba(c, factor))
+                        .collect(),
+                    None => vec![modulate_rgba([255; 4], factor); n_verts],
+                };
+            }
+            if let Some(info) = pbr.base_color_texture() {
+                let source = info.texture().source().index();
+                sub.texture = images
+                    .get(source)
+                    .and_then(gltf_image_to_rgba)
+                    .map(|image| MeshTexture {
+                        key: format!("{}#image{source}", abs_path.display()),
+                        image,
+                    });
+            }
+
+            sub.transform(&world);
+            out.append(sub);
+        }
+    }
+    Ok(out)
+}
+
+// -----------------------------------------------------------------------------
+// COLLADA (.dae)
+// -----------------------------------------------------------------------------
+
+/// Strip the leading `#` of a COLLADA URL reference.
+fn dae_ref(url: &str) -> &str {
+    url.strip_prefix('#').unwrap_or(url)
+}
+
+/// Parse the whitespace-separated numbers of an element's text.
+fn dae_floats(node: roxmltree::Node) -> Vec<f32> {
+    node.text()
+        .unwrap_or("")
+        .split_whitespace()
+        .filter_map(|s| s.parse().ok())
+        .collect()
+}
+
+/// First child element with the given tag name.
+fn dae_child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    tag: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children()
+        .find(|c| c.is_element() && c.tag_name().name() == tag)
+}
+
+/// Child elements with the given tag name.
+fn dae_children<'a, 'input: 'a>(
+    node: roxmltree::Node<'a, 'input>,
+    tag: &'a str,
+) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |c| c.is_element() && c.tag_name().name() == tag)
+}
+
+/// Diffuse color and texture of a COLLADA material.
+#[derive(Debug, Default, Clone)]
+struct DaeMaterial {
+    color: Option<[f32; 4]>,
+    texture: Option<MeshTexture>,
+}
+
+/// A parsed COLLADA document with lookups by element id.
+struct DaeDocument<'a, 'input> {
+    ids: HashMap<&'a str, roxmltree::Node<'a, 'input>>,
+    dir: &'a Path,
+    /// Scale from document units to meters.
+    unit: f32,
+    /// Rotation from the document's `<up_axis>` to Z up.
+    up_axis: na::Matrix4<f32>,
+}
+
+impl<'a, 'input> DaeDocument<'a, 'input> {
+    fn new(doc: &'a roxmltree::Document<'input>, dir: &'a Path) -> Self {
+        let ids = doc
+            .descendants()
+            .filter_map(|n| n.attribute("id").map(|id| (id, n)))
+            .collect();
+        let unit = doc
+            .descendants()
+            .find(|n| n.has_tag_name("unit"))
+            .and_then(|n| n.attribute("meter"))
+            .and_then(|m| m.parse().ok())
+            .unwrap_or(1.0);
+        let up_axis = match doc
+            .descendants()
+            .find(|n| n.has_tag_name("up_axis"))
+            .and_then(|n| n.text())
+            .map(str::trim)
+        {
+            // Y up (Blender's default): y -> z, z -> -y
+            Some("Y_UP") => na::Matrix4::new(
+                1.0, 0.0, 0.0, 0.0, //
+                0.0, 0.0, -1.0, 0.0, //
+                0.0, 1.0, 0.0, 0.0, //
+                0.0, 0.0, 0.0, 1.0,
+            ),
+            // X up, -Y right: x -> z, y -> -x, z -> -y
+            Some("X_UP") => na::Matrix4::new(
+                0.0, -1.0, 0.0, 0.0, //
+                0.0, 0.0, -1.0, 0.0, //
+                1.0, 0.0, 0.0, 0.0, //
+                0.0, 0.0, 0.0, 1.0,
+            ),
+            _ => na::Matrix4::identity(),
+        };
+        Self {
+            ids,
+            dir,
+            unit,
+            up_axis,
+        }
+    }
+
+    /// Transform from document coordinates to Z-up meters.
+    fn to_z_up_meters(&self) -> na::Matrix4<f32> {
+        self.up_axis * na::Matrix4::new_scaling(self.unit)
+    }
+
+    fn get(&self, url: &str) -> Option<roxmltree::Node<'a, 'input>> {
+        self.ids.get(dae_ref(url)).copied()
+    }
+
+    /// Float data and stride of a `<source>`.
+    fn source(&self, url: &str) -> Option<(Vec<f32>, usize)> {
+        let source = self.get(url)?;
+        let data = dae_floats(dae_child(source, "float_array")?);
+        let stride = dae_child(source, "technique_common")
+            .and_then(|t| dae_child(t, "accessor"))
+            .and_then(|a| a.attribute("stride"))
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(1);
+        Some((data, stride))
+    }
+
+    /// Resolve a material id to its diffuse color and texture.
+    fn material(&self, material_url: &str) -> DaeMaterial {
+        let mut out = DaeMaterial::default();
+        let Some(effect) = self
+            .get(material_url)
+            .and_then(|m| dae_child(m, "instance_effect"))
+            .and_then(|ie| ie.attribute("url"))
+            .and_then(|url| self.get(url))
+        else {
+            return out;
+        };
+        let Some(diffuse) = effect.descendants().find(|n| n.has_tag_name("diffuse")) else {
+            return out;
+        };
+
+        if let Some(color) = dae_child(diffuse, "color") {
+            let c = dae_floats(color);
+            if c.len() >= 3 {
+                out.color = Some([c[0], c[1], c[2], c.get(3).copied().unwrap_or(1.0)]);
+            }
+        }
+        if let Some(sampler) = dae_child(diffuse, "texture").and_then(|t| t.attribute("texture")) {
+            out.texture = self
+                .texture_image_path(effect, sampler)
+                .and_then(|p| load_texture_file(&p));
+        }
+        out
+    }
+
+    /// Follow `sampler2D -> surface -> image` (or a direct image id) to an image file path.
+    fn texture_image_path(
+        &self,
+        effect: roxmltree::Node,
+        sampler_sid: &str,
+    ) -> Option<std::path::PathBuf> {
+        let find_param = |sid: &str| {
+            effect
+                .descendants()
+                .find(|n| n.has_tag_name("newparam") && n.attribute("sid") == Some(sid))
+        };
+        let mut image_id = sampler_sid.to_string();
+        if let Some(surface_sid) = find_param(sampler_sid)
+            .and_then(|p| p.descendants().find(|n| n.has_tag_name("source")))
+            .and_then(|s| s.text())
+        {
+            if let Some(init) = find_param(surface_sid.trim())
+                .and_then(|p| p.descendants().find(|n| n.has_tag_name("init_from")))
+                .and_then(|i| i.text())
+            {
+                image_id = init.trim().to_string();
+            }
+        }
+        let file = self
+            .get(&image_id)
+            .and_then(|img| img.descendants().find(|n| n.has_tag_name("init_from")))
+            .and_then(|i| i.text())?
+            .trim();
+        let file = file.strip_prefix("file://").unwrap_or(file);
+        Some(self.dir.join(file))
+    }
+
+    /// Read every triangle primitive of a `<geometry>`, with materials bound by symbol.
+    fn geometry(
+        &self,
+        geometry: roxmltree::Node,
+        bindings: &HashMap<String, String>,
+    ) -> MeshBuffers {
+        let mut out = MeshBuffers::default();
+        let Some(mesh) = dae_child(geometry, "mesh") else {
+            return out;
+        };
+
+        for prim in mesh
+            .children()
+            .filter(|c| matches!(c.tag_name().name(), "triangles" | "polylist" | "polygons"))
+        {
+            let material_symbol = prim.attribute("material").unwrap_or("");
+            let material = bindings
+                .get(material_symbol)
+                .map(|url| self.material(url))
+                .unwrap_or_default();
+            out.append(self.primitive(prim, &material));
+        }
+        out
+    }
+
+    /// Read one `<triangles>`, `<polylist>` or `<polygons>` element, fan-triangulating polygons.
+    fn primitive(&self, prim: roxmltree::Node, material: &DaeMaterial) -> MeshBuffers {
+        // (semantic, offset, data, stride)
+        let mut inputs: Vec<(String, usize, Vec<f32>, usize)> = Vec::new();
+        for input in dae_children(prim, "input") {
+            let semantic = input.attribute("semantic").unwrap_or("");
+            let offset: usize = input
+                .attribute("offset")
+                .and_then(|o| o.parse().ok())
+                .unwrap_or(0);
+            let Some(source) = input.attribute("source") else {
+                continue;
+            };
+            if semantic == "VERTEX" {
+                // <vertices> groups per-vertex inputs (POSITION and sometimes NORMAL/COLOR)
+                if let Some(vertices) = self.get(source) {
+                    for v_input in dae_children(vertices, "input") {
+                        let v_semantic = v_input.attribute("semantic").unwrap_or("");
+                        if let Some((data, stride)) =
+                            v_input.attribute("source").and_then(|s| self.source(s))
+                        {
+                            inputs.push((v_semantic.to_string(), offset, data, stride));
+                        }
+                    }
+                }
+            } else if semantic == "TEXCOORD" && inputs.iter().any(|(s, ..)| s == "TEXCOORD") {
+                // Only the first UV set is used
+                continue;
+            } else if let Some((data, stride)) = self.source(source) {
+                inputs.push((semantic.to_string(), offset, data, stride));
+            }
+        }
+        let index_stride = inputs.iter().map(|(_, o, ..)| o + 1).max().unwrap_or(1);
+
+        // Polygon corner counts; `<p>` lists for `<polygons>`
+        let p_lists: Vec<Vec<usize>> = dae_children(prim, "p")
+            .map(|p| {
+                p.text()
+                    .unwrap_or("")
+                    .split_whitespace()
+                    .filter_map(|s| s.parse().ok())
+                    .collect()
+            })
+            .collect();
+        let polygons: Vec<Vec<usize>> = match prim.tag_name().name() {
+            "triangles" => p_lists
+                .concat()
+                .chunks_exact(index_stride * 3)
+                .map(|c| c.to_vec())
+                .collect(),
+            "polylist" => {
+                let vcounts: Vec<usize> = dae_child(prim, "vcount")
+                    .map(|v| {
+                        v.text()
+                            .unwrap_or("")
+                            .split_whitespace()
+                            .filter_map(|s| s.parse().ok())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let all = p_lists.concat();
+                let mut start = 0;
+                let mut polys = Vec::with_capacity(vcounts.len());
+                for n in vcounts {
+                    let end = start + n * index_stride;
+                    if end > all.len() {
+                        break;
+                    }
+                    polys.push(all[start..end].to_vec());
+                    start = end;
+                }
+                polys
+            }
+            _ => p_lists,
+        };
+
+        let mut out = MeshBuffers::default();
+        let has = |semantic: &str| inputs.iter().any(|(s, ..)| s == semantic);
+        let (has_normals, has_texcoords, has_colors) =
+            (has("NORMAL"), has("TEXCOORD"), has("COLOR"));
+        let mut vertex_ids: HashMap<Vec<usize>, u32> = HashMap::new();
+
+        for poly in polygons {
+            let corners: Vec<&[usize]> = poly.chunks_exact(index_stride).collect();
+            let mut corner_ids = Vec::with_capacity(corners.len());
+            for corner in corners {
+                if let Some(&id) = vertex_ids.get(corner) {
+                    corner_ids.push(id);
+                    continue;
+                }
+                let mut position = [0.0; 3];
+                let mut normal = [0.0, 0.0, 1.0];
+                let mut texcoord = [0.0, 0.0];
+                let mut color = [1.0; 4];
+                for (semantic, offset, data, stride) in &inputs {
+                    let base = corner[*offset] * stride;
+                    let value = |i: usize| data.get(base + i).copied().unwrap_or(0.0);
+                    match semantic.as_str() {
+                        "POSITION" => position = [value(0), value(1), value(2)],
+                        "NORMAL" => normal = [value(0), value(1), value(2)],
+                        // COLLADA puts the texture origin at the bottom left
+                        "TEXCOORD" => texcoord = [value(0), 1.0 - value(1)],
+                        "COLOR" => {
+                            color = [
+                                value(0),
+                                value(1),
+                                value(2),
+                                if *stride >= 4 { value(3) } else { 1.0 },
+                            ]
+                        }
+                        _ => {}
+                    }
+                }
+
+                let id = out.positions.len() as u32;
+                out.positions.push(position);
+                if has_normals {
+                    out.normals.push(normal);
+                }
+                if has_texcoords {
+                    out.texcoords.push(texcoord);
+                }
+                if has_colors || material.color.is_some() {
+                    let factor = material.color.unwrap_or([1.0; 4]);
+                    out.colors.push(modulate_rgba(
+                        [255; 4],
+                        [
+                            color[0] * factor[0],
+                            color[1] * factor[1],
+                            color[2] * factor[2],
+                            color[3] * factor[3],
+                        ],
+                    ));
+                }
+                vertex_ids.insert(corner.to_vec(), id);
+                corner_ids.push(id);
+            }
+            for i in 1..corner_ids.len().saturating_sub(1) {
+                out.indices
+                    .push([corner_ids[0], corner_ids[i], corner_ids[i + 1]]);
+            }
+        }
+        out.texture = material.texture.clone();
+        out
+    }
+
+    /// Local transform of a `<node>` from its `matrix`, `translate`, `rotate` and `scale` children.
+    fn node_transform(&self, node: roxmltree::Node) -> na::Matrix4<f32> {
+        let mut tf = na::Matrix4::identity();
+        for child in node.children().filter(|c| c.is_element()) {
+            let v = dae_floats(child);
+            let step = match (child.tag_name().name(), v.len()) {
+                ("matrix", 16) => na::Matrix4::from_row_slice(&v),
+                ("translate", 3) => {
+                    na::Matrix4::new_translation(&na::Vector3::new(v[0], v[1], v[2]))
+                }
+                ("rotate", 4) => {
+                    // A zero axis has no rotation to apply
+                    let Some(axis) = na::Unit::try_new(na::Vector3::new(v[0], v[1], v[2]), 1e-9)
+                    else {
+                        continue;
+                    };
+                    na::Matrix4::from_axis_angle(&axis, v[3].to_radians())
+                }
+                ("scale", 3) => {
+                    na::Matrix4::new_nonuniform_scaling(&na::Vector3::new(v[0], v[1], v[2]))
+                }
+                _ => continue,
+            };
+            tf *= step;
+        }
+        tf
+    }
+}
+
+/// Read a COLLADA file into mesh buffers, baking in the visual scene's node transforms.
+fn read_dae(abs_path: &Path) -> Result<MeshBuffers> {
+    let text =
+        fs::read_to_string(abs_path).map_err(|e| anyhow!("Failed to read {abs_path:?}: {e}"))?;
+    let xml = roxmltree::Document::parse(&text)
+        .map_err(|e| anyhow!("Failed to parse COLLADA {abs_path:?}: {e}"))?;
+    let dir = abs_path.parent().unwrap_or_else(|| Path::new("."));
+    let doc = DaeDocument::new(&xml, dir);
+
+    let scene_root = xml
+        .descendants()
+        .find(|n| n.has_tag_name("instance_visual_scene"))
+        .and_then(|n| n.attribute("url"))
+        .and_then(|url| doc.get(url))
+        .or_else(|| xml.descendants().find(|n| n.has_tag_name("visual_scene")));
+
+    let mut out = MeshBuffers::default();
+    let Some(scene_root) = scene_root else {
+        // No scene: take every geometry as-is
+        for geometry in xml.descendants().filter(|n| n.has_tag_name("geometry")) {
+            out.append(doc.geometry(geometry, &HashMap::new()));
+        }
+        out.transform(&doc.to_z_up_meters());
+        return Ok(out);
+    };
+
+    // Each entry carries the `instance_node` targets above it, so a node instancing one of
+    // its ancestors is skipped instead of expanding forever
+    let mut stack: Vec<(roxmltree::Node, na::Matrix4<f32>, Vec<roxmltree::NodeId>)> =
+        dae_children(scene_root, "node")
+            .map(|n| (n, na::Matrix4::identity(), Vec::new()))
+            .collect();
+    while let Some((node, parent_tf, instanced)) = stack.pop() {
+        let world = parent_tf * doc.node_transform(node);
+        for child in dae_children(node, "node") {
+            stack.push((child, world, instanced.clone()));
+        }
+        for instance in dae_children(node, "instance_node") {
+            let Some(target) = instance.attribute("url").and_then(|u| doc.get(u)) else {
+                continue;
+            };
+            if instanced.contains(&target.id()) || node.ancestors().any(|a| a == target) {
+                eprintln!("Warning: skipping cyclic <instance_node> in {abs_path:?}");
+                continue;
+            }
+            let mut path = instanced.clone();
+            path.push(target.id());
+            stack.push((target, world, path));
+        }
+        for instance in dae_children(node, "instance_geometry") {
+            let Some(geometry) = instance.attribute("url").and_then(|u| doc.get(u)) else {
+                continue;
+            };
+            let bindings: HashMap<String, String> = instance
+                .descendants()
+                .filter(|n| n.has_tag_name("instance_material"))
+                .filter_map(|n| {
+                    Some((
+                        n.attribute("symbol")?.to_string(),
+                        n.attribute("target")?.to_string(),
+                    ))
+                })
+                .collect();
+            let mut sub = doc.geometry(geometry, &bindings);
+            sub.transform(&world);
+            out.append(sub);
+        }
+    }
+    // Positions and node translations are in document units and axes
+    out.transform(&doc.to_z_up_meters());
+    Ok(out)
+}
+
+/// Convert mesh buffers to a `Mesh3D`, computing normals when the file had none.
+fn mesh_buffers_to_mesh3d(buffers: MeshBuffers) -> Result<Mesh3D> {
+    let n_verts = buffers.positions.len();
+    let has_texcoords = !buffers.texcoords.is_empty();
+    let mut mesh = Mesh3D::new(buffers.positions).with_triangle_indices(buffers.indices);
+
+    if !buffers.colors.is_empty() {
+        mesh = mesh.with_vertex_colors(buffers.colors);
+    }
+    if has_texcoords {
+        mesh = mesh.with_vertex_texcoords(buffers.texcoords);
+        if let Some(texture) = buffers.texture {
+            let (w, h) = texture.image.dimensions();
+            mesh = mesh.with_albedo_texture(
+                ImageFormat::rgba8([w, h]),
+                ImageBuffer(Blob::from(texture.image.into_raw())),
+            );
+        }
+    }
+    if n_verts > 0 && buffers.normals.len() == n_verts {
+        mesh = mesh.with_vertex_normals(buffers.normals);
+    } else {
+        compute_vertex_normals(&mut mesh);
+    }
+
+    mesh.sanity_check()?;
+    Ok(mesh)
+}
+
+/// Load a mesh file (STL, OBJ, COLLADA or glTF/GLB) and convert to a `Mesh3D`.
+///
+/// The format comes from the extension, or from the file contents when the extension is
+/// missing or unknown. Submeshes are merged into one `Mesh3D`; their material colors are
+/// baked into the vertex colors, and a base color texture is kept when all of them share it.
+pub fn load_mesh_file(abs_path: &Path) -> Result<Mesh3D> {
+    let buffers = match MeshFormat::detect(abs_path)? {
+        MeshFormat::Stl => return load_stl_as_mesh3d(abs_path),
+        MeshFormat::Obj => read_obj(abs_path)?,
+        MeshFormat::Dae => read_dae(abs_path)?,
+        MeshFormat::Gltf | MeshFormat::Glb => read_gltf(abs_path)?,
+    };
+    if buffers.conflicting_textures {
+        eprintln!("Warning: {abs_path:?} uses several textures, only material colors are kept");
+    }
+    mesh_buffers_to_mesh3d(buffers)
+}
+
 /// Convert float RGBA ([0.0..1.0]) to 8-bit RGBA ([0..255]).
 pub fn float_rgba_to_u8(rgba: [f32; 4]) -> [u8; 4] {
     [
@@ -221,6 +1144,26 @@ pub fn create_cylinder_mesh(radius: f64, length: f64) -> Mesh3D {
     mesh
 }
 
+/// Create a capsule mesh from radius and the length of its cylindrical part, along +Z
+pub fn create_capsule_mesh(radius: f64, length: f64) -> Mesh3D {
+    let capsule = ParryCapsule::new_z((length / 2.0) as f32, radius as f32);
+    let (raw_v_parry, raw_i_parry) = capsule.to_trimesh(30, 10);
+
+    let positions: Vec<Position3D> = raw_v_parry
+        .iter()
+        .map(|p_parry| Position3D::from([p_parry.x, p_parry.y, p_parry.z]))
+        .collect();
+    let indices: Vec<TriangleIndices> = raw_i_parry
+        .iter()
+        .map(|tri_parry| TriangleIndices::from([tri_parry[0], tri_parry[1], tri_parry[2]]))
+        .collect();
+    let mut mesh = Mesh3D::new(positions).with_triangle_indices(indices);
+
+    // compute normals
+    compute_vertex_normals(&mut mesh);
+    mesh
+}
+
 /// Create a sphere mesh from radius
 pub fn create_sphere_mesh(radius: f64) -> Mesh3D {
     let radius_f32 = radius as f32;
@@ -0,0 +1,112 @@
+// inertial_utils.rs
+
+use nalgebra as na;
+use std::f64::consts::PI;
+
+/// Density (kg/m³, about steel) of the sphere whose radius shows a link's mass.
+pub const MASS_POINT_DENSITY: f64 = 8000.0;
+
+/// Mass properties of a link, from its URDF `<inertial>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkInertial {
+    pub mass: f64,
+    /// Center of mass in the link frame.
+    pub center_of_mass: [f64; 3],
+    /// Principal moments of inertia, about the center of mass.
+    pub principal_moments: [f64; 3],
+    /// Orientation of the principal axes in the link frame.
+    pub principal_axes: na::UnitQuaternion<f64>,
+}
+
+impl LinkInertial {
+    /// Mass properties from an inertial frame pose (`xyz`, `rpy`) and the inertia
+    /// tensor `[ixx, ixy, ixz, iyy, iyz, izz]` expressed in that frame.
+    pub fn new(mass: f64, xyz: [f64; 3], rpy: [f64; 3], inertia: [f64; 6]) -> Self {
+        let [ixx, ixy, ixz, iyy, iyz, izz] = inertia;
+        let tensor = na::Matrix3::new(ixx, ixy, ixz, ixy, iyy, iyz, ixz, iyz, izz);
+        let eigen = na::SymmetricEigen::new(tensor);
+
+        // Eigenvectors are only defined up to sign; keep a right-handed frame
+        let mut axes = eigen.eigenvectors;
+        if axes.determinant() < 0.0 {
+            axes.set_column(2, &-axes.column(2));
+        }
+        let frame = na::UnitQuaternion::from_euler_angles(rpy[0], rpy[1], rpy[2]);
+        let principal_axes = frame
+            * na::UnitQuaternion::from_rotation_matrix(&na::Rotation3::from_matrix_unchecked(axes));
+
+        Self {
+            mass,
+            center_of_mass: xyz,
+            principal_moments: eigen.eigenvalues.into(),
+            principal_axes,
+        }
+    }
+
+    /// Mass properties of a parsed URDF `<inertial>`.
+    pub fn from_urdf(inertial: &urdf_rs::Inertial) -> Self {
+        let i = &inertial.inertia;
+        Self::new(
+            inertial.mass.value,
+            [
+                inertial.origin.xyz[0],
+                inertial.origin.xyz[1],
+                inertial.origin.xyz[2],
+            ],
+            [
+                inertial.origin.rpy[0],
+                inertial.origin.rpy[1],
+                inertial.origin.rpy[2],
+            ],
+            [i.ixx, i.ixy, i.ixz, i.iyy, i.iyz, i.izz],
+        )
+    }
+
+    /// Half sizes, along the principal axes, of the uniform box with the same mass and
+    /// inertia.
+    pub fn equivalent_box_half_sizes(&self) -> [f64; 3] {
+        // I_x = m / 12 * (y² + z²) for full side lengths x, y, z
+        self.equivalent_extents(6.0).map(|side| side / 2.0)
+    }
+
+    /// Radii, along the principal axes, of the uniform ellipsoid with the same mass and
+    /// inertia.
+    pub fn equivalent_ellipsoid_radii(&self) -> [f64; 3] {
+        // I_x = m / 5 * (b² + c²) for radii a, b, c
+        self.equivalent_extents(2.5)
+    }
+
+    /// `sqrt(factor / m * (I_j + I_k - I_i))` for each principal axis i.
+    ///
+    /// Inertias that break the triangle inequality give a zero extent instead of NaN.
+    fn equivalent_extents(&self, factor: f64) -> [f64; 3] {
+        if self.mass <= 0.0 {
+            return [0.0; 3];
+        }
+        let [a, b, c] = self.principal_moments;
+        [b + c - a, a + c - b, a + b - c].map(|d| (factor / self.mass * d).max(0.0).sqrt())
+    }
+}
+
+/// Radius of a `MASS_POINT_DENSITY` sphere with the given mass, used to size center of
+/// mass markers.
+pub fn mass_point_radius(mass: f64) -> f64 {
+    (3.0 * mass.max(0.0) / (4.0 * PI * MASS_POINT_DENSITY)).cbrt()
+}
+
+/// Total mass and center of mass of point masses `(mass, position)`.
+///
+/// Returns `None` when the total mass is not positive.
+pub fn combined_center_of_mass(
+    parts: impl IntoIterator<Item = (f64, [f64; 3])>,
+) -> Option<(f64, [f64; 3])> {
+    let mut total_mass = 0.0;
+    let mut weighted = [0.0; 3];
+    for (mass, position) in parts {
+        total_mass += mass;
+        for (w, p) in weighted.iter_mut().zip(position) {
+            *w += mass * p;
+        }
+    }
+    (total_mass > 0.0).then(|| (total_mass, weighted.map(|w| w / total_mass)))
+}
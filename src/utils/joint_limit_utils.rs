@@ -0,0 +1,231 @@
+// joint_limit_utils.rs
+
+use std::collections::BTreeMap;
+use urdf_rs::{JointLimit, JointType};
+
+/// Recorded values of a joint in one frame, in the joint's units (rad or m based).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct JointSample {
+    pub position: Option<f64>,
+    pub velocity: Option<f64>,
+    pub effort: Option<f64>,
+}
+
+/// Which part of a URDF `<limit>` is checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LimitKind {
+    Position,
+    Velocity,
+    Effort,
+}
+
+impl LimitKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            LimitKind::Position => "position",
+            LimitKind::Velocity => "velocity",
+            LimitKind::Effort => "effort",
+        }
+    }
+
+    /// Unit of this quantity for a joint of the given type.
+    pub fn unit(self, joint_type: &JointType) -> &'static str {
+        let prismatic = matches!(joint_type, JointType::Prismatic);
+        match (self, prismatic) {
+            (LimitKind::Position, false) => "rad",
+            (LimitKind::Position, true) => "m",
+            (LimitKind::Velocity, false) => "rad/s",
+            (LimitKind::Velocity, true) => "m/s",
+            (LimitKind::Effort, false) => "N·m",
+            (LimitKind::Effort, true) => "N",
+        }
+    }
+}
+
+/// A recorded value beyond its URDF limit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LimitViolation {
+    pub kind: LimitKind,
+    pub value: f64,
+    /// The bound that was crossed.
+    pub limit: f64,
+    /// How far beyond the bound the value is (always positive).
+    pub excess: f64,
+    pub unit: &'static str,
+}
+
+impl LimitViolation {
+    pub fn message(&self, joint_name: &str) -> String {
+        format!(
+            "Joint {joint_name} {} {:.4} {unit} exceeds limit {:.4} {unit} by {:.4} {unit}",
+            self.kind.name(),
+            self.value,
+            self.limit,
+            self.excess,
+            unit = self.unit,
+        )
+    }
+}
+
+/// Worst state of a joint's values relative to its limits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LimitStatus {
+    #[default]
+    Within,
+    /// Within `near_margin` of a limit.
+    NearLimit,
+    Exceeded,
+}
+
+/// Result of checking a sample against a joint's limits.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LimitCheck {
+    pub status: LimitStatus,
+    pub violations: Vec<LimitViolation>,
+}
+
+/// Check a joint sample against the URDF `<limit>`.
+///
+/// Positions are only checked for revolute and prismatic joints with `upper > lower`;
+/// velocity and effort only when their limit is positive (0 means unset), as absolute
+/// values. `near_margin` is the fraction of the position range (or of the velocity/effort
+/// limit) under which a value counts as near its limit.
+pub fn check_joint_limits(
+    joint_type: &JointType,
+    limit: &JointLimit,
+    sample: &JointSample,
+    near_margin: f64,
+) -> LimitCheck {
+    let mut check = LimitCheck::default();
+    let mut near = false;
+
+    let bounded = matches!(joint_type, JointType::Revolute | JointType::Prismatic);
+    if let Some(position) = sample
+        .position
+        .filter(|_| bounded && limit.upper > limit.lower)
+    {
+        let margin = near_margin * (limit.upper - limit.lower);
+        if position < limit.lower {
+            check.violations.push(LimitViolation {
+                kind: LimitKind::Position,
+                value: position,
+                limit: limit.lower,
+                excess: limit.lower - position,
+                unit: LimitKind::Position.unit(joint_type),
+            });
+        } else if position > limit.upper {
+            check.violations.push(LimitViolation {
+                kind: LimitKind::Position,
+                value: position,
+                limit: limit.upper,
+                excess: position - limit.upper,
+                unit: LimitKind::Position.unit(joint_type),
+            });
+        } else if position < limit.lower + margin || position > limit.upper - margin {
+            near = true;
+        }
+    }
+
+    let magnitude_limits = [
+        (LimitKind::Velocity, sample.velocity, limit.velocity),
+        (LimitKind::Effort, sample.effort, limit.effort),
+    ];
+    for (kind, value, max) in magnitude_limits {
+        let Some(value) = value.filter(|_| max > 0.0) else {
+            continue;
+        };
+        if value.abs() > max {
+            check.violations.push(LimitViolation {
+                kind,
+                value,
+                limit: max.copysign(value),
+                excess: value.abs() - max,
+                unit: kind.unit(joint_type),
+            });
+        } else if value.abs() > max * (1.0 - near_margin) {
+            near = true;
+        }
+    }
+
+    check.status = if !check.violations.is_empty() {
+        LimitStatus::Exceeded
+    } else if near {
+        LimitStatus::NearLimit
+    } else {
+        LimitStatus::Within
+    };
+    check
+}
+
+/// How often and how far a joint exceeded one of its limits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViolationStats {
+    /// Number of frames with the limit exceeded.
+    pub frames: usize,
+    pub first_frame: usize,
+    pub max_excess: f64,
+    pub unit: &'static str,
+}
+
+/// Limit violations accumulated over a recording, per joint and limit kind.
+#[derive(Debug, Clone, Default)]
+pub struct LimitViolationSummary {
+    stats: BTreeMap<(String, LimitKind), ViolationStats>,
+}
+
+impl LimitViolationSummary {
+    pub fn record(&mut self, joint_name: &str, violation: &LimitViolation, frame_idx: usize) {
+        let stats = self
+            .stats
+            .entry((joint_name.to_string(), violation.kind))
+            .or_insert(ViolationStats {
+                frames: 0,
+                first_frame: frame_idx,
+                max_excess: 0.0,
+                unit: violation.unit,
+            });
+        stats.frames += 1;
+        stats.max_excess = stats.max_excess.max(violation.excess);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stats.is_empty()
+    }
+
+    pub fn get(&self, joint_name: &str, kind: LimitKind) -> Option<&ViolationStats> {
+        self.stats.get(&(joint_name.to_string(), kind))
+    }
+
+    /// Violations sorted by joint name, then limit kind.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, LimitKind, &ViolationStats)> {
+        self.stats
+            .iter()
+            .map(|((joint, kind), stats)| (joint.as_str(), *kind, stats))
+    }
+
+    /// Markdown table of the violations, for a recording of `num_frames` frames.
+    pub fn markdown(&self, num_frames: usize) -> String {
+        let mut md = String::from("# Joint limit violations\n\n");
+        if self.is_empty() {
+            md.push_str("No joint exceeded its URDF limits.\n");
+            return md;
+        }
+        md.push_str(
+            "| Joint | Limit | Frames | Share | Max excess | First frame |\n|---|---|---|---|---|---|\n",
+        );
+        for (joint, kind, stats) in self.iter() {
+            let share = 100.0 * stats.frames as f64 / num_frames.max(1) as f64;
+            md.push_str(&format!(
+                "| {} | {} | {} | {:.1} % | {:.4} {} | {} |\n",
+                joint,
+                kind.name(),
+                stats.frames,
+                share,
+                stats.max_excess,
+                stats.unit,
+                stats.first_frame,
+            ));
+        }
+        md
+    }
+}
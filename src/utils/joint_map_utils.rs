@@ -0,0 +1,158 @@
+// joint_map_utils.rs
+
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::f64::consts::PI;
+use std::fs;
+use std::path::Path;
+use urdf_rs::JointType;
+
+use crate::utils::urdf_bfs_utils::JointInfo;
+
+/// Supported on-disk formats for an actuator -> URDF joint mapping file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JointMapFormat {
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl JointMapFormat {
+    /// Guess the format from a file extension (`.toml`, `.json`, `.yaml`/`.yml`).
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "toml" => Some(Self::Toml),
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// Layout of a mapping file. Keys are actuator IDs, values are URDF joint names:
+///
+/// ```toml
+/// [actuators]
+/// 11 = "Revolute_2"
+/// 31 = "L_hip_y"
+/// ```
+#[derive(Debug, Deserialize)]
+struct JointMapFile {
+    actuators: HashMap<String, String>,
+}
+
+/// The actuator -> joint map of the GPR humanoid, used when no mapping file is given.
+pub fn default_actuator_to_urdf_joint_map() -> HashMap<u32, String> {
+    let entries: [(u32, &str); 20] = [
+        // Left Arm
+        (11, "Revolute_2"),
+        (12, "Revolute_4"),
+        (13, "Revolute_5"),
+        (14, "Revolute_8"),
+        (15, "Revolute_16"),
+        // Right Arm
+        (21, "Revolute_1"),
+        (22, "Revolute_3"),
+        (23, "Revolute_6"),
+        (24, "Revolute_7"),
+        (25, "Revolute_15"),
+        // Left Leg
+        (31, "L_hip_y"),
+        (32, "L_hip_x"),
+        (33, "L_hip_z"),
+        (34, "L_knee"),
+        (35, "L_ankle_y"),
+        // Right Leg
+        (41, "R_hip_y"),
+        (42, "R_hip_x"),
+        (43, "R_hip_z"),
+        (44, "R_knee"),
+        (45, "R_ankle_y"),
+    ];
+
+    entries
+        .into_iter()
+        .map(|(id, name)| (id, name.to_string()))
+        .collect()
+}
+
+/// Parse the contents of a mapping file in the given format.
+pub fn parse_actuator_joint_map(
+    contents: &str,
+    format: JointMapFormat,
+) -> Result<HashMap<u32, String>> {
+    let file: JointMapFile = match format {
+        JointMapFormat::Toml => toml::from_str(contents)?,
+        JointMapFormat::Json => serde_json::from_str(contents)?,
+        JointMapFormat::Yaml => serde_yaml::from_str(contents)?,
+    };
+
+    let mut map = HashMap::new();
+    for (key, joint_name) in file.actuators {
+        let actuator_id = key
+            .trim()
+            .parse::<u32>()
+            .map_err(|e| anyhow!("Invalid actuator ID {key:?} in joint map: {e}"))?;
+        map.insert(actuator_id, joint_name);
+    }
+    Ok(map)
+}
+
+/// Load an actuator -> joint map from a `.toml`, `.json` or `.yaml` file.
+pub fn load_actuator_joint_map(path: &Path) -> Result<HashMap<u32, String>> {
+    let format = JointMapFormat::from_path(path).ok_or_else(|| {
+        anyhow!("Unsupported joint map format {path:?}, expected .toml, .json or .yaml")
+    })?;
+    let contents =
+        fs::read_to_string(path).map_err(|e| anyhow!("Failed to read joint map {path:?}: {e}"))?;
+    parse_actuator_joint_map(&contents, format)
+        .map_err(|e| anyhow!("Failed to parse joint map {path:?}: {e}"))
+}
+
+/// Check that every recorded actuator is mapped and that every mapped joint exists in the URDF.
+///
+/// Returns an error listing all unmapped actuators and unknown joints at once.
+pub fn validate_actuator_joint_map(
+    map: &HashMap<u32, String>,
+    actuator_ids: &BTreeSet<u32>,
+    urdf_joint_names: &HashSet<String>,
+) -> Result<()> {
+    let unmapped: Vec<u32> = actuator_ids
+        .iter()
+        .filter(|id| !map.contains_key(id))
+        .copied()
+        .collect();
+
+    let mut unknown: Vec<String> = map
+        .values()
+        .filter(|name| !urdf_joint_names.contains(*name))
+        .cloned()
+        .collect();
+    unknown.sort();
+    unknown.dedup();
+
+    if unmapped.is_empty() && unknown.is_empty() {
+        return Ok(());
+    }
+
+    let mut msg = String::from("Actuator -> joint map does not match the recording/URDF:");
+    if !unmapped.is_empty() {
+        msg.push_str(&format!("\n  unmapped actuators: {unmapped:?}"));
+    }
+    if !unknown.is_empty() {
+        msg.push_str(&format!("\n  joints not found in URDF: {unknown:?}"));
+    }
+    bail!(msg)
+}
+
+/// Convert a recorded actuator position into the joint's native units:
+/// degrees become radians for revolute/continuous joints, prismatic positions are
+/// used as-is (meters).
+pub fn recorded_position_to_joint_units(joint_info: &JointInfo, position: f64) -> f64 {
+    match joint_info.joint_type {
+        JointType::Prismatic => position,
+        _ => position * (PI / 180.0),
+    }
+}
@@ -0,0 +1,63 @@
+// joint_overlay_utils.rs
+
+use nalgebra as na;
+
+/// Unit vector along a joint axis, or `None` for a zero-length axis.
+pub fn unit_axis(axis: [f64; 3]) -> Option<[f64; 3]> {
+    na::Unit::try_new(na::Vector3::from(axis), 1e-9).map(|a| [a.x, a.y, a.z])
+}
+
+/// A unit vector perpendicular to `axis` (which must be a unit vector), used as the
+/// zero-angle direction of limit arcs.
+///
+/// Built from the coordinate axis least aligned with `axis`, so joints about z start
+/// their arc along x.
+pub fn perpendicular_unit(axis: [f64; 3]) -> [f64; 3] {
+    let a = na::Vector3::from(axis);
+    let reference = (0..3)
+        .map(|i| na::Vector3::ith(i, 1.0))
+        .min_by(|u, v| a.dot(u).abs().total_cmp(&a.dot(v).abs()))
+        .expect("three candidates");
+    let perpendicular = (reference - a * a.dot(&reference)).normalize();
+    [perpendicular.x, perpendicular.y, perpendicular.z]
+}
+
+/// Outline of a revolute joint's limit range in the joint frame: from the origin out to
+/// the `lower` angle, along an arc of `radius` about `axis` to the `upper` angle, and back.
+///
+/// Returns an empty strip for a zero axis or an empty range.
+pub fn revolute_limit_outline(
+    axis: [f64; 3],
+    lower: f64,
+    upper: f64,
+    radius: f64,
+    segments: usize,
+) -> Vec<[f32; 3]> {
+    let Some(axis) = unit_axis(axis) else {
+        return Vec::new();
+    };
+    if upper <= lower {
+        return Vec::new();
+    }
+    let unit_axis = na::Unit::new_unchecked(na::Vector3::from(axis));
+    let start = na::Vector3::from(perpendicular_unit(axis)) * radius;
+    let segments = segments.max(1);
+
+    let mut outline = Vec::with_capacity(segments + 3);
+    outline.push([0.0; 3]);
+    for i in 0..=segments {
+        let angle = lower + (upper - lower) * i as f64 / segments as f64;
+        let p = na::Rotation3::from_axis_angle(&unit_axis, angle) * start;
+        outline.push([p.x as f32, p.y as f32, p.z as f32]);
+    }
+    outline.push([0.0; 3]);
+    outline
+}
+
+/// Segment covered by a prismatic joint's limits in the joint frame, or `None` for a
+/// zero axis.
+pub fn prismatic_limit_segment(axis: [f64; 3], lower: f64, upper: f64) -> Option<[[f32; 3]; 2]> {
+    let axis = unit_axis(axis)?;
+    let point = |t: f64| axis.map(|a| (a * t) as f32);
+    Some([point(lower), point(upper)])
+}
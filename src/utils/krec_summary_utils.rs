@@ -0,0 +1,332 @@
+// krec_summary_utils.rs
+
+use krec::{KRec, KRecHeader};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A frame interval this many times the median one counts as a gap with dropped frames.
+pub const GAP_INTERVAL_FACTOR: f64 = 1.5;
+
+/// Overview of a KREC recording, as printed by `krecviz inspect`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KrecSummary {
+    pub header: HeaderSummary,
+    pub frames: usize,
+    /// `None` when the frames carry no video timestamps
+    pub timing: Option<TimingSummary>,
+    /// Every actuator in the header, states or commands
+    pub actuator_ids: Vec<u32>,
+    pub actuators: Vec<ActuatorSummary>,
+    pub imu: ImuSummary,
+    /// Frame intervals well above the median one
+    pub gaps: Vec<FrameGap>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HeaderSummary {
+    pub uuid: String,
+    pub task: String,
+    pub robot_platform: String,
+    pub robot_serial: String,
+    /// Nanoseconds since the Unix epoch
+    pub start_timestamp: u64,
+    pub end_timestamp: u64,
+}
+
+/// Timing of the frames from their video timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TimingSummary {
+    /// Time between the first and last frame, in seconds
+    pub duration_s: f64,
+    pub frame_rate_hz: f64,
+    /// The nominal frame interval
+    pub median_interval_ms: f64,
+    pub min_interval_ms: f64,
+    pub max_interval_ms: f64,
+    /// Standard deviation of the frame intervals
+    pub jitter_ms: f64,
+}
+
+/// A stretch between two consecutive frames long enough for frames to be missing.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FrameGap {
+    /// Index of the frame before the gap
+    pub after_frame: usize,
+    pub interval_ms: f64,
+    /// Estimated from the median interval
+    pub missing_frames: usize,
+}
+
+/// Min, max and mean of the values a field took, ignoring frames without one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FieldStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub count: usize,
+}
+
+impl FieldStats {
+    fn new(value: f64) -> Self {
+        FieldStats {
+            min: value,
+            max: value,
+            mean: value,
+            count: 1,
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.count += 1;
+        self.mean += (value - self.mean) / self.count as f64;
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ActuatorSummary {
+    pub actuator_id: u32,
+    /// Name from the header's actuator config
+    pub name: Option<String>,
+    /// Frames with a state for this actuator
+    pub state_frames: usize,
+    /// Frames with a command for this actuator
+    pub command_frames: usize,
+    pub online_frames: usize,
+    /// Stats of each state field (`position`, `velocity`, ...) and commanded value
+    /// (`command_position`, ...)
+    pub fields: BTreeMap<String, FieldStats>,
+}
+
+/// Number of frames carrying each IMU reading.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct ImuSummary {
+    pub frames: usize,
+    pub accel: usize,
+    pub gyro: usize,
+    pub mag: usize,
+    pub quaternion: usize,
+}
+
+impl From<&KRecHeader> for HeaderSummary {
+    fn from(header: &KRecHeader) -> Self {
+        HeaderSummary {
+            uuid: header.uuid.clone(),
+            task: header.task.clone(),
+            robot_platform: header.robot_platform.clone(),
+            robot_serial: header.robot_serial.clone(),
+            start_timestamp: header.start_timestamp,
+            end_timestamp: header.end_timestamp,
+        }
+    }
+}
+
+impl KrecSummary {
+    pub fn from_krec(krec: &KRec) -> Self {
+        let mut actuators: BTreeMap<u32, ActuatorSummary> = BTreeMap::new();
+        for config in &krec.header.actuator_configs {
+            actuator_entry(&mut actuators, config.actuator_id).name = config.name.clone();
+        }
+        let mut imu = ImuSummary::default();
+        for frame in &krec.frames {
+            for state in &frame.actuator_states {
+                let summary = actuator_entry(&mut actuators, state.actuator_id);
+                summary.state_frames += 1;
+                summary.online_frames += usize::from(state.online);
+                let fields = &mut summary.fields;
+                record(fields, "position", state.position);
+                record(fields, "velocity", state.velocity);
+                record(fields, "torque", state.torque);
+                record(fields, "temperature", state.temperature);
+                record(fields, "voltage", state.voltage.map(f64::from));
+                record(fields, "current", state.current.map(f64::from));
+            }
+            for command in &frame.actuator_commands {
+                let summary = actuator_entry(&mut actuators, command.actuator_id);
+                summary.command_frames += 1;
+                let fields = &mut summary.fields;
+                record(fields, "command_position", Some(command.position.into()));
+                record(fields, "command_velocity", Some(command.velocity.into()));
+                record(fields, "command_torque", Some(command.torque.into()));
+            }
+            if let Some(values) = &frame.imu_values {
+                imu.frames += 1;
+                imu.accel += usize::from(values.accel.is_some());
+                imu.gyro += usize::from(values.gyro.is_some());
+                imu.mag += usize::from(values.mag.is_some());
+                imu.quaternion += usize::from(values.quaternion.is_some());
+            }
+        }
+
+        let timestamps: Vec<u64> = krec.frames.iter().map(|f| f.video_timestamp).collect();
+        let (timing, gaps) = frame_timing(&timestamps);
+
+        KrecSummary {
+            header: HeaderSummary::from(&krec.header),
+            frames: krec.frames.len(),
+            timing,
+            actuator_ids: actuators.keys().copied().collect(),
+            actuators: actuators.into_values().collect(),
+            imu,
+            gaps,
+        }
+    }
+
+    /// Plain-text report for the terminal.
+    pub fn text(&self) -> String {
+        let header = &self.header;
+        let mut out = String::new();
+        out.push_str(&format!("UUID:            {}\n", header.uuid));
+        out.push_str(&format!("Task:            {}\n", header.task));
+        out.push_str(&format!("Robot platform:  {}\n", header.robot_platform));
+        out.push_str(&format!("Robot serial:    {}\n", header.robot_serial));
+        out.push_str(&format!("Start timestamp: {} ns\n", header.start_timestamp));
+        out.push_str(&format!("End timestamp:   {} ns\n", header.end_timestamp));
+        out.push_str(&format!("Frames:          {}\n", self.frames));
+
+        match &self.timing {
+            Some(timing) => {
+                out.push_str(&format!("Duration:        {:.3} s\n", timing.duration_s));
+                out.push_str(&format!(
+                    "Frame rate:      {:.2} Hz\n",
+                    timing.frame_rate_hz
+                ));
+                out.push_str(&format!(
+                    "Frame interval:  {:.3} ms median, {:.3}-{:.3} ms, jitter {:.3} ms\n",
+                    timing.median_interval_ms,
+                    timing.min_interval_ms,
+                    timing.max_interval_ms,
+                    timing.jitter_ms
+                ));
+            }
+            None => out.push_str("Duration:        unknown (no video timestamps)\n"),
+        }
+
+        let imu = &self.imu;
+        if imu.frames == 0 {
+            out.push_str("IMU:             none\n");
+        } else {
+            out.push_str(&format!(
+                "IMU:             {} frames (accel {}, gyro {}, mag {}, quaternion {})\n",
+                imu.frames, imu.accel, imu.gyro, imu.mag, imu.quaternion
+            ));
+        }
+
+        if self.gaps.is_empty() {
+            out.push_str("Gaps:            none\n");
+        } else {
+            out.push_str(&format!("Gaps:            {}\n", self.gaps.len()));
+            for gap in &self.gaps {
+                out.push_str(&format!(
+                    "  after frame {}: {:.3} ms, ~{} frames missing\n",
+                    gap.after_frame, gap.interval_ms, gap.missing_frames
+                ));
+            }
+        }
+
+        let ids: Vec<String> = self.actuator_ids.iter().map(u32::to_string).collect();
+        out.push_str(&format!("Actuators:       {}\n", ids.join(", ")));
+        for actuator in &self.actuators {
+            let name = actuator
+                .name
+                .as_deref()
+                .map(|name| format!(" ({name})"))
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "\nActuator {}{}: {} states ({} online), {} commands\n",
+                actuator.actuator_id,
+                name,
+                actuator.state_frames,
+                actuator.online_frames,
+                actuator.command_frames
+            ));
+            for (field, stats) in &actuator.fields {
+                out.push_str(&format!(
+                    "  {:<17} min {:>12.4}  max {:>12.4}  mean {:>12.4}\n",
+                    field, stats.min, stats.max, stats.mean
+                ));
+            }
+        }
+
+        out
+    }
+}
+
+fn actuator_entry(
+    actuators: &mut BTreeMap<u32, ActuatorSummary>,
+    actuator_id: u32,
+) -> &mut ActuatorSummary {
+    actuators
+        .entry(actuator_id)
+        .or_insert_with(|| ActuatorSummary {
+            actuator_id,
+            ..Default::default()
+        })
+}
+
+/// Add a field value to its stats, skipping missing and non-finite values.
+fn record(fields: &mut BTreeMap<String, FieldStats>, name: &str, value: Option<f64>) {
+    let Some(value) = value.filter(|v| v.is_finite()) else {
+        return;
+    };
+    match fields.get_mut(name) {
+        Some(stats) => stats.add(value),
+        None => {
+            fields.insert(name.to_string(), FieldStats::new(value));
+        }
+    }
+}
+
+/// Timing stats and gaps from the frames' video timestamps (ns).
+fn frame_timing(timestamps: &[u64]) -> (Option<TimingSummary>, Vec<FrameGap>) {
+    let intervals_ms: Vec<f64> = timestamps
+        .windows(2)
+        .map(|pair| (pair[1] as f64 - pair[0] as f64) / 1e6)
+        .collect();
+    let (Some(first), Some(last)) = (timestamps.first(), timestamps.last()) else {
+        return (None, Vec::new());
+    };
+    let duration_s = (*last as f64 - *first as f64) / 1e9;
+    if duration_s <= 0.0 {
+        return (None, Vec::new());
+    }
+
+    let mut sorted = intervals_ms.clone();
+    sorted.sort_by(f64::total_cmp);
+    let median = if sorted.len() % 2 == 1 {
+        sorted[sorted.len() / 2]
+    } else {
+        (sorted[sorted.len() / 2 - 1] + sorted[sorted.len() / 2]) / 2.0
+    };
+    let mean = intervals_ms.iter().sum::<f64>() / intervals_ms.len() as f64;
+    let variance =
+        intervals_ms.iter().map(|i| (i - mean).powi(2)).sum::<f64>() / intervals_ms.len() as f64;
+
+    let gaps = if median > 0.0 {
+        intervals_ms
+            .iter()
+            .enumerate()
+            .filter(|(_, interval)| **interval > GAP_INTERVAL_FACTOR * median)
+            .map(|(index, interval)| FrameGap {
+                after_frame: index,
+                interval_ms: *interval,
+                missing_frames: ((interval / median).round() as usize)
+                    .saturating_sub(1)
+                    .max(1),
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let timing = TimingSummary {
+        duration_s,
+        frame_rate_hz: intervals_ms.len() as f64 / duration_s,
+        median_interval_ms: median,
+        min_interval_ms: sorted[0],
+        max_interval_ms: sorted[sorted.len() - 1],
+        jitter_ms: variance.sqrt(),
+    };
+    (Some(timing), gaps)
+}
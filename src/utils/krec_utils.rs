@@ -0,0 +1,222 @@
+// krec_utils.rs
+
+use anyhow::{anyhow, Result};
+use krec::{ActuatorConfig, ActuatorState, KRec, KRecFrame, KRecHeader};
+use log::info;
+use rerun::RecordingStream;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Timeline driven by the frame index, always present.
+pub const FRAME_IDX_TIMELINE: &str = "frame_idx";
+/// Timeline driven by each frame's `video_timestamp`.
+pub const VIDEO_TIME_TIMELINE: &str = "video_time";
+/// Absolute wall-clock time, derived from the header start timestamp.
+pub const WALL_CLOCK_TIMELINE: &str = "wall_clock";
+/// Timeline driven by each frame's `inference_step`, when the recorder filled it in.
+pub const INFERENCE_STEP_TIMELINE: &str = "inference_step";
+
+/// Whether a file is a video container (Matroska/WebM or MP4) rather than a raw KREC.
+///
+/// Checks the magic bytes first and falls back to the file extension.
+pub fn is_video_container(path: &Path) -> bool {
+    let mut magic = [0u8; 12];
+    if let Ok(mut f) = File::open(path) {
+        if f.read_exact(&mut magic).is_ok() {
+            // EBML header (Matroska/WebM) or an ISO BMFF `ftyp` box (MP4/MOV)
+            if magic[..4] == [0x1A, 0x45, 0xDF, 0xA3] || &magic[4..8] == b"ftyp" {
+                return true;
+            }
+        }
+    }
+
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|s| s.to_lowercase());
+    matches!(ext.as_deref(), Some("mkv" | "webm" | "mp4"))
+}
+
+/// Load a KREC from either a raw `.krec` file or a video with the KREC embedded as an
+/// attachment (e.g. `.krec.mkv`), extracting it with ffmpeg in the latter case.
+pub fn load_krec(path: &str) -> Result<KRec> {
+    if !is_video_container(Path::new(path)) {
+        return KRec::load(path)
+            .map_err(|e| anyhow!("Failed to load KREC from {:?}: {:?}", path, e));
+    }
+
+    info!("{} is a video container, extracting embedded KREC", path);
+    let tmp_dir = tempfile::tempdir()?;
+    let extracted = tmp_dir.path().join("extracted.krec");
+    let extracted_str = extracted
+        .to_str()
+        .ok_or_else(|| anyhow!("Non UTF-8 temp path {:?}", extracted))?;
+
+    krec::extract_from_video(path, extracted_str, Some(false))
+        .map_err(|e| anyhow!("Failed to extract KREC from video {:?}: {:?}", path, e))?;
+    KRec::load(extracted_str)
+        .map_err(|e| anyhow!("Failed to load KREC extracted from {:?}: {:?}", path, e))
+}
+
+/// Wall-clock time of a frame in nanoseconds since the Unix epoch.
+///
+/// KREC writers disagree on whether `video_timestamp` is absolute or relative to the
+/// start of the recording, so a timestamp earlier than `start_timestamp` is treated as
+/// an offset from it. Returns `None` if the header carries no start timestamp.
+pub fn frame_wall_clock_nanos(start_timestamp: u64, video_timestamp: u64) -> Option<i64> {
+    if start_timestamp == 0 {
+        return None;
+    }
+    let absolute = if video_timestamp >= start_timestamp {
+        video_timestamp
+    } else {
+        start_timestamp.saturating_add(video_timestamp)
+    };
+    i64::try_from(absolute).ok()
+}
+
+/// Set every KREC timeline on the recording stream for the given frame.
+///
+/// `log_inference_steps` should come from [`has_inference_steps`] so recordings without
+/// inference steps don't get an all-zero timeline.
+pub fn set_frame_timelines(
+    rec: &RecordingStream,
+    start_timestamp: u64,
+    frame_idx: usize,
+    frame: &KRecFrame,
+    log_inference_steps: bool,
+) {
+    rec.set_time_sequence(FRAME_IDX_TIMELINE, frame_idx as i64);
+    rec.set_time_nanos(VIDEO_TIME_TIMELINE, frame.video_timestamp as i64);
+    if let Some(wall_clock_ns) = frame_wall_clock_nanos(start_timestamp, frame.video_timestamp) {
+        rec.set_time_nanos(WALL_CLOCK_TIMELINE, wall_clock_ns);
+    }
+    if log_inference_steps {
+        rec.set_time_sequence(INFERENCE_STEP_TIMELINE, frame.inference_step as i64);
+    }
+}
+
+/// Markdown summary of a KREC header, shown at the start of the recording.
+pub fn header_markdown(header: &KRecHeader, num_frames: usize) -> String {
+    let mut md = String::from("# KREC recording\n\n");
+    md.push_str("| Field | Value |\n|---|---|\n");
+    md.push_str(&format!("| UUID | `{}` |\n", header.uuid));
+    md.push_str(&format!("| Task | {} |\n", header.task));
+    md.push_str(&format!("| Robot platform | {} |\n", header.robot_platform));
+    md.push_str(&format!("| Robot serial | {} |\n", header.robot_serial));
+    md.push_str(&format!(
+        "| Start timestamp | {} ns |\n",
+        header.start_timestamp
+    ));
+    md.push_str(&format!(
+        "| End timestamp | {} ns |\n",
+        header.end_timestamp
+    ));
+    if header.end_timestamp > header.start_timestamp {
+        let duration_s = (header.end_timestamp - header.start_timestamp) as f64 / 1e9;
+        md.push_str(&format!("| Duration | {:.3} s |\n", duration_s));
+    }
+    md.push_str(&format!("| Frames | {} |\n", num_frames));
+
+    if !header.actuator_configs.is_empty() {
+        md.push_str("\n## Actuators\n\n");
+        md.push_str("| ID | Name | kp | kd | ki | Max torque |\n|---|---|---|---|---|---|\n");
+        let mut configs: Vec<&ActuatorConfig> = header.actuator_configs.iter().collect();
+        configs.sort_by_key(|c| c.actuator_id);
+        for config in configs {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} |\n",
+                config.actuator_id,
+                config.name.as_deref().unwrap_or("-"),
+                format_optional(config.kp),
+                format_optional(config.kd),
+                format_optional(config.ki),
+                format_optional(config.max_torque),
+            ));
+        }
+    }
+
+    md
+}
+
+/// Markdown description of a single actuator's configuration.
+pub fn actuator_config_markdown(config: &ActuatorConfig) -> String {
+    let title = match config.name.as_deref() {
+        Some(name) => format!("# Actuator {} ({})\n\n", config.actuator_id, name),
+        None => format!("# Actuator {}\n\n", config.actuator_id),
+    };
+    format!(
+        "{}- kp: {}\n- kd: {}\n- ki: {}\n- max torque: {}\n",
+        title,
+        format_optional(config.kp),
+        format_optional(config.kd),
+        format_optional(config.ki),
+        format_optional(config.max_torque),
+    )
+}
+
+fn format_optional(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{v}"))
+}
+
+/// Whether any frame carries a non-zero `inference_step`.
+pub fn has_inference_steps(krec: &KRec) -> bool {
+    krec.frames.iter().any(|frame| frame.inference_step != 0)
+}
+
+/// Limits on actuator health readings; `None` disables a check.
+#[derive(Debug, Clone, Default)]
+pub struct ActuatorThresholds {
+    /// Maximum temperature in °C
+    pub max_temperature: Option<f64>,
+    /// Minimum supply voltage in V
+    pub min_voltage: Option<f32>,
+    /// Maximum supply voltage in V
+    pub max_voltage: Option<f32>,
+    /// Maximum absolute current in A
+    pub max_current: Option<f32>,
+}
+
+impl ActuatorThresholds {
+    /// Return `(check_name, message)` for every threshold the state exceeds.
+    pub fn violations(&self, state: &ActuatorState) -> Vec<(&'static str, String)> {
+        let mut out = Vec::new();
+        let id = state.actuator_id;
+
+        if let (Some(limit), Some(temp)) = (self.max_temperature, state.temperature) {
+            if temp > limit {
+                out.push((
+                    "over_temperature",
+                    format!("Actuator {id} over temperature: {temp:.1} °C > {limit:.1} °C"),
+                ));
+            }
+        }
+        if let (Some(limit), Some(volt)) = (self.min_voltage, state.voltage) {
+            if volt < limit {
+                out.push((
+                    "under_voltage",
+                    format!("Actuator {id} under voltage: {volt:.2} V < {limit:.2} V"),
+                ));
+            }
+        }
+        if let (Some(limit), Some(volt)) = (self.max_voltage, state.voltage) {
+            if volt > limit {
+                out.push((
+                    "over_voltage",
+                    format!("Actuator {id} over voltage: {volt:.2} V > {limit:.2} V"),
+                ));
+            }
+        }
+        if let (Some(limit), Some(current)) = (self.max_current, state.current) {
+            if current.abs() > limit {
+                out.push((
+                    "over_current",
+                    format!("Actuator {id} over current: {current:.2} A > {limit:.2} A"),
+                ));
+            }
+        }
+
+        out
+    }
+}
@@ -0,0 +1,816 @@
+// mjcf_utils.rs
+
+use anyhow::{anyhow, Result};
+use log::warn;
+use nalgebra as na;
+use roxmltree::Node;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Color MuJoCo gives geoms without `rgba` or material.
+const DEFAULT_GEOM_RGBA: [f64; 4] = [0.5, 0.5, 0.5, 1.0];
+
+/// An MJCF model rewritten as URDF.
+#[derive(Debug, Clone, Default)]
+pub struct MjcfConversion {
+    /// The equivalent URDF document.
+    pub urdf: String,
+    /// Radii of ellipsoid geoms, keyed by (link name, visual index).
+    ///
+    /// URDF has no ellipsoid, so these visuals are written as spheres of radius 1
+    /// that have to be scaled by the radii.
+    pub ellipsoids: HashMap<(String, usize), [f64; 3]>,
+    /// Radii of ellipsoid collision geoms, keyed by (link name, collision index).
+    pub collision_ellipsoids: HashMap<(String, usize), [f64; 3]>,
+}
+
+/// Whether an XML document is an MJCF (MuJoCo) model, i.e. has a `<mujoco>` root.
+pub fn is_mjcf(xml: &str) -> bool {
+    roxmltree::Document::parse(xml)
+        .map(|doc| doc.root_element().has_tag_name("mujoco"))
+        .unwrap_or(false)
+}
+
+/// Convert an MJCF model to URDF.
+///
+/// Every body becomes a link. Its joints become URDF joints to the parent body; a body
+/// with several joints gets intermediate links named `<body>_<joint>_link`. Geoms become
+/// visuals (and collisions when the model separates them with `contype`/`conaffinity`),
+/// with their material or `rgba` inlined. Mesh and texture file names keep the compiler's
+/// `meshdir`/`texturedir` prefix, so they stay relative to the MJCF file.
+///
+/// The pose of the top-level body is dropped, as a URDF root link has none; a `<worldbody>`
+/// with several bodies or its own geoms becomes a `world` link instead.
+pub fn mjcf_to_urdf(xml: &str) -> Result<MjcfConversion> {
+    let doc = roxmltree::Document::parse(xml).map_err(|e| anyhow!("Failed to parse MJCF: {e}"))?;
+    let root = doc.root_element();
+    if !root.has_tag_name("mujoco") {
+        return Err(anyhow!(
+            "Not an MJCF model: root element is <{}>",
+            root.tag_name().name()
+        ));
+    }
+    if child_elements(root, "include").next().is_some() {
+        warn!("MJCF <include> is not supported, included files are ignored");
+    }
+
+    let mut converter = Converter::new(root);
+    let worldbody = child_elements(root, "worldbody")
+        .next()
+        .ok_or_else(|| anyhow!("MJCF model has no <worldbody>"))?;
+
+    let bodies: Vec<Node> = child_elements(worldbody, "body").collect();
+    let world_geoms = child_elements(worldbody, "geom")
+        .filter(|g| converter.geom_type(*g, "main") != "plane")
+        .count();
+
+    if bodies.len() == 1 && world_geoms == 0 {
+        converter.convert_body(bodies[0], None, &na::Isometry3::identity(), "main");
+    } else {
+        let world = converter.unique_link_name("world");
+        converter.write_link(&world, worldbody, &na::Isometry3::identity(), "main");
+        for body in bodies {
+            converter.convert_body(body, Some(&world), &na::Isometry3::identity(), "main");
+        }
+    }
+
+    let name = root.attribute("model").unwrap_or("mjcf_model");
+    let mut urdf = String::new();
+    let _ = writeln!(urdf, r#"<?xml version="1.0"?>"#);
+    let _ = writeln!(urdf, r#"<robot name="{}">"#, xml_escape(name));
+    urdf.push_str(&converter.links);
+    urdf.push_str(&converter.joints);
+    urdf.push_str("</robot>\n");
+
+    Ok(MjcfConversion {
+        urdf,
+        ellipsoids: converter.ellipsoids,
+        collision_ellipsoids: converter.collision_ellipsoids,
+    })
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+
+/// Child elements with the given tag name.
+fn child_elements<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    tag: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |c| c.is_element() && c.tag_name().name() == tag)
+}
+
+/// Parse a whitespace-separated list of numbers.
+fn floats(s: &str) -> Vec<f64> {
+    s.split_whitespace()
+        .filter_map(|v| v.parse().ok())
+        .collect()
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// `base`, or `base_<n>` for the first `n` that isn't in `used` yet, marked as used.
+fn unique_name(used: &mut HashSet<String>, base: &str) -> String {
+    let mut name = base.to_string();
+    let mut n = 1;
+    while !used.insert(name.clone()) {
+        name = format!("{base}_{n}");
+        n += 1;
+    }
+    name
+}
+
+/// `<origin>` element for a pose.
+fn origin_xml(pose: &na::Isometry3<f64>) -> String {
+    let t = pose.translation.vector;
+    let (roll, pitch, yaw) = pose.rotation.euler_angles();
+    format!(
+        r#"<origin xyz="{} {} {}" rpy="{} {} {}"/>"#,
+        t.x, t.y, t.z, roll, pitch, yaw
+    )
+}
+
+/// `compiler` settings that affect how numbers are read.
+struct Compiler {
+    /// Angles are in degrees (the MJCF default) rather than radians.
+    degrees: bool,
+    /// Euler sequence, lower case for intrinsic (rotating) axes, upper case for extrinsic.
+    eulerseq: Vec<char>,
+    meshdir: PathBuf,
+    texturedir: PathBuf,
+}
+
+impl Compiler {
+    fn new(root: Node) -> Self {
+        let compiler = child_elements(root, "compiler").next();
+        let get = |name: &str| compiler.and_then(|c| c.attribute(name));
+        let assetdir = get("assetdir").unwrap_or("");
+        Self {
+            degrees: get("angle") != Some("radian"),
+            eulerseq: get("eulerseq").unwrap_or("xyz").chars().collect(),
+            meshdir: PathBuf::from(get("meshdir").unwrap_or(assetdir)),
+            texturedir: PathBuf::from(get("texturedir").unwrap_or(assetdir)),
+        }
+    }
+
+    fn angle(&self, value: f64) -> f64 {
+        if self.degrees {
+            value.to_radians()
+        } else {
+            value
+        }
+    }
+
+    /// Rotation from an `euler` attribute.
+    fn euler(&self, angles: &[f64]) -> na::UnitQuaternion<f64> {
+        let mut rot = na::UnitQuaternion::identity();
+        for (axis, angle) in self.eulerseq.iter().zip(angles) {
+            let unit = match axis.to_ascii_lowercase() {
+                'x' => na::Vector3::x_axis(),
+                'y' => na::Vector3::y_axis(),
+                _ => na::Vector3::z_axis(),
+            };
+            let step = na::UnitQuaternion::from_axis_angle(&unit, self.angle(*angle));
+            rot = if axis.is_ascii_lowercase() {
+                rot * step
+            } else {
+                step * rot
+            };
+        }
+        rot
+    }
+}
+
+/// `<default>` classes: attribute values per class and element tag, with class inheritance.
+#[derive(Default)]
+struct Defaults {
+    parents: HashMap<String, String>,
+    values: HashMap<(String, String), HashMap<String, String>>,
+}
+
+impl Defaults {
+    fn new(root: Node) -> Self {
+        let mut defaults = Self::default();
+        for top in child_elements(root, "default") {
+            defaults.add_class(top, None);
+        }
+        defaults
+    }
+
+    fn add_class(&mut self, node: Node, parent: Option<&str>) {
+        let class = node
+            .attribute("class")
+            .unwrap_or(if parent.is_none() { "main" } else { "" })
+            .to_string();
+        if let Some(parent) = parent {
+            self.parents.insert(class.clone(), parent.to_string());
+        }
+        for child in node.children().filter(|c| c.is_element()) {
+            if child.has_tag_name("default") {
+                self.add_class(child, Some(&class));
+                continue;
+            }
+            let entry = self
+                .values
+                .entry((class.clone(), child.tag_name().name().to_string()))
+                .or_default();
+            for attr in child.attributes() {
+                entry.insert(attr.name().to_string(), attr.value().to_string());
+            }
+        }
+    }
+
+    /// Default value of `attr` on `tag` elements of `class`, following parent classes.
+    fn get(&self, class: &str, tag: &str, attr: &str) -> Option<&str> {
+        let mut class = Some(class);
+        while let Some(current) = class {
+            if let Some(value) = self
+                .values
+                .get(&(current.to_string(), tag.to_string()))
+                .and_then(|attrs| attrs.get(attr))
+            {
+                return Some(value);
+            }
+            class = self.parents.get(current).map(String::as_str);
+        }
+        None
+    }
+}
+
+struct Converter {
+    compiler: Compiler,
+    defaults: Defaults,
+    /// Mesh asset name -> (file name, scale).
+    meshes: HashMap<String, (String, Option<[f64; 3]>)>,
+    /// Material asset name -> (rgba, texture file name).
+    materials: HashMap<String, ([f64; 4], Option<String>)>,
+    /// Geoms without contacts are visuals and the rest collisions; if the model has no
+    /// contact-free geoms, every geom is both.
+    split_visuals: bool,
+    /// URDF links and joints have separate namespaces, as MJCF bodies and joints do
+    used_link_names: HashSet<String>,
+    used_joint_names: HashSet<String>,
+    links: String,
+    joints: String,
+    ellipsoids: HashMap<(String, usize), [f64; 3]>,
+    collision_ellipsoids: HashMap<(String, usize), [f64; 3]>,
+}
+
+impl Converter {
+    fn new(root: Node) -> Self {
+        let mut converter = Self {
+            compiler: Compiler::new(root),
+            defaults: Defaults::new(root),
+            meshes: HashMap::new(),
+            materials: HashMap::new(),
+            split_visuals: false,
+            used_link_names: HashSet::new(),
+            used_joint_names: HashSet::new(),
+            links: String::new(),
+            joints: String::new(),
+            ellipsoids: HashMap::new(),
+            collision_ellipsoids: HashMap::new(),
+        };
+        converter.read_assets(root);
+        converter.split_visuals = root
+            .descendants()
+            .filter(|n| n.has_tag_name("geom"))
+            .any(|g| converter.is_visual_only(g, &converter.element_class(g)));
+        converter
+    }
+
+    fn read_assets(&mut self, root: Node) {
+        let mut textures: HashMap<String, String> = HashMap::new();
+        for asset in child_elements(root, "asset") {
+            for texture in child_elements(asset, "texture") {
+                if let (Some(name), Some(file)) =
+                    (texture.attribute("name"), texture.attribute("file"))
+                {
+                    let path = self.compiler.texturedir.join(file);
+                    textures.insert(name.to_string(), path.to_string_lossy().into_owned());
+                }
+            }
+        }
+
+        for asset in child_elements(root, "asset") {
+            for mesh in child_elements(asset, "mesh") {
+                let class = mesh.attribute("class").unwrap_or("main");
+                let Some(file) = mesh.attribute("file") else {
+                    continue;
+                };
+                let name = mesh
+                    .attribute("name")
+                    .map(str::to_string)
+                    .unwrap_or_else(|| {
+                        Path::new(file)
+                            .file_stem()
+                            .map(|s| s.to_string_lossy().into_owned())
+                            .unwrap_or_else(|| file.to_string())
+                    });
+                let scale = mesh
+                    .attribute("scale")
+                    .or_else(|| self.defaults.get(class, "mesh", "scale"))
+                    .map(floats)
+                    .filter(|s| s.len() == 3)
+                    .map(|s| [s[0], s[1], s[2]]);
+                let path = self.compiler.meshdir.join(file);
+                self.meshes
+                    .insert(name, (path.to_string_lossy().into_owned(), scale));
+            }
+
+            for material in child_elements(asset, "material") {
+                let Some(name) = material.attribute("name") else {
+                    continue;
+                };
+                let class = material.attribute("class").unwrap_or("main");
+                let rgba = material
+                    .attribute("rgba")
+                    .or_else(|| self.defaults.get(class, "material", "rgba"))
+                    .map(floats)
+                    .filter(|c| c.len() == 4)
+                    .map(|c| [c[0], c[1], c[2], c[3]])
+                    .unwrap_or([1.0; 4]);
+                let texture = material
+                    .attribute("texture")
+                    .and_then(|t| textures.get(t))
+                    .cloned();
+                self.materials.insert(name.to_string(), (rgba, texture));
+            }
+        }
+    }
+
+    /// Class of an element: its own `class`, else the nearest `childclass` above it.
+    fn element_class(&self, node: Node) -> String {
+        if let Some(class) = node.attribute("class") {
+            return class.to_string();
+        }
+        node.ancestors()
+            .find_map(|a| a.attribute("childclass"))
+            .unwrap_or("main")
+            .to_string()
+    }
+
+    /// Attribute of an element, falling back to its default class.
+    fn attr(&self, node: Node, class: &str, name: &str) -> Option<String> {
+        node.attribute(name)
+            .or_else(|| self.defaults.get(class, node.tag_name().name(), name))
+            .map(str::to_string)
+    }
+
+    fn attr_floats(&self, node: Node, class: &str, name: &str) -> Option<Vec<f64>> {
+        node.attribute(name)
+            .or_else(|| self.defaults.get(class, node.tag_name().name(), name))
+            .map(floats)
+    }
+
+    fn geom_type(&self, geom: Node, class: &str) -> String {
+        geom.attribute("type")
+            .or_else(|| self.defaults.get(class, "geom", "type"))
+            .unwrap_or("sphere")
+            .to_string()
+    }
+
+    fn is_visual_only(&self, geom: Node, class: &str) -> bool {
+        let contype = self.attr_floats(geom, class, "contype");
+        let conaffinity = self.attr_floats(geom, class, "conaffinity");
+        let is_zero = |v: Option<Vec<f64>>| v.and_then(|v| v.first().copied()) == Some(0.0);
+        is_zero(contype) && is_zero(conaffinity)
+    }
+
+    fn unique_link_name(&mut self, base: &str) -> String {
+        unique_name(&mut self.used_link_names, base)
+    }
+
+    fn unique_joint_name(&mut self, base: &str) -> String {
+        unique_name(&mut self.used_joint_names, base)
+    }
+
+    /// Pose of an element from `pos` and one of `quat`, `axisangle`, `euler`, `xyaxes`, `zaxis`.
+    fn frame(&self, node: Node, class: &str) -> na::Isometry3<f64> {
+        let pos = self
+            .attr_floats(node, class, "pos")
+            .filter(|p| p.len() == 3)
+            .map(|p| na::Translation3::new(p[0], p[1], p[2]))
+            .unwrap_or_else(na::Translation3::identity);
+
+        let rot = if let Some(q) = self
+            .attr_floats(node, class, "quat")
+            .filter(|q| q.len() == 4)
+        {
+            na::UnitQuaternion::from_quaternion(na::Quaternion::new(q[0], q[1], q[2], q[3]))
+        } else if let Some(a) = self
+            .attr_floats(node, class, "axisangle")
+            .filter(|a| a.len() == 4)
+        {
+            na::UnitQuaternion::from_axis_angle(
+                &na::Unit::new_normalize(na::Vector3::new(a[0], a[1], a[2])),
+                self.compiler.angle(a[3]),
+            )
+        } else if let Some(e) = self
+            .attr_floats(node, class, "euler")
+            .filter(|e| e.len() == 3)
+        {
+            self.compiler.euler(&e)
+        } else if let Some(xy) = self
+            .attr_floats(node, class, "xyaxes")
+            .filter(|v| v.len() == 6)
+        {
+            let x = na::Vector3::new(xy[0], xy[1], xy[2]).normalize();
+            let y = na::Vector3::new(xy[3], xy[4], xy[5]);
+            let y = (y - x * x.dot(&y)).normalize();
+            let z = x.cross(&y);
+            na::UnitQuaternion::from_matrix(&na::Matrix3::from_columns(&[x, y, z]))
+        } else if let Some(z) = self
+            .attr_floats(node, class, "zaxis")
+            .filter(|v| v.len() == 3)
+        {
+            na::UnitQuaternion::rotation_between(
+                &na::Vector3::z(),
+                &na::Vector3::new(z[0], z[1], z[2]),
+            )
+            .unwrap_or_else(|| {
+                na::UnitQuaternion::from_axis_angle(&na::Vector3::x_axis(), std::f64::consts::PI)
+            })
+        } else {
+            na::UnitQuaternion::identity()
+        };
+
+        na::Isometry3::from_parts(pos, rot)
+    }
+
+    /// Convert a body and its subtree. `offset` is the pose of the parent body frame in
+    /// the parent link frame (they differ when the parent's joint is not at its origin).
+    fn convert_body(
+        &mut self,
+        body: Node,
+        parent_link: Option<&str>,
+        offset: &na::Isometry3<f64>,
+        childclass: &str,
+    ) {
+        let childclass = body.attribute("childclass").unwrap_or(childclass);
+        let base_name = body.attribute("name").unwrap_or("body");
+        let name = self.unique_link_name(base_name);
+        let body_pose = offset * self.frame(body, childclass);
+
+        // Pose of this body's frame in its own link frame
+        let mut link_offset = na::Isometry3::identity();
+
+        if let Some(parent) = parent_link {
+            let joints: Vec<Node> = child_elements(body, "joint").collect();
+            if joints.is_empty() {
+                let joint_name = self.unique_joint_name(&format!("{name}_fixed"));
+                self.write_fixed_joint(&joint_name, parent, &name, &body_pose);
+            } else {
+                let mut parent = parent.to_string();
+                let mut previous_pos = na::Vector3::zeros();
+                for (k, joint) in joints.iter().enumerate() {
+                    let class = joint.attribute("class").unwrap_or(childclass);
+                    let joint_name = joint
+                        .attribute("name")
+                        .map(str::to_string)
+                        .unwrap_or_else(|| format!("{name}_joint_{k}"));
+                    let pos = self
+                        .attr_floats(*joint, class, "pos")
+                        .filter(|p| p.len() == 3)
+                        .map(|p| na::Vector3::new(p[0], p[1], p[2]))
+                        .unwrap_or_else(na::Vector3::zeros);
+                    let origin = if k == 0 {
+                        body_pose * na::Translation3::from(pos)
+                    } else {
+                        na::Isometry3::from(na::Translation3::from(pos - previous_pos))
+                    };
+                    let child = if k + 1 == joints.len() {
+                        name.clone()
+                    } else {
+                        let intermediate =
+                            self.unique_link_name(&format!("{name}_{joint_name}_link"));
+                        let _ = writeln!(
+                            self.links,
+                            r#"  <link name="{}"/>"#,
+                            xml_escape(&intermediate)
+                        );
+                        intermediate
+                    };
+                    let joint_name = self.unique_joint_name(&joint_name);
+                    self.write_joint(*joint, class, &joint_name, &parent, &child, &origin);
+                    parent = child;
+                    previous_pos = pos;
+                }
+                link_offset = na::Isometry3::from(na::Translation3::from(-previous_pos));
+            }
+        } else if child_elements(body, "joint").next().is_some() {
+            warn!("Joints of the root body {base_name:?} are ignored");
+        }
+
+        self.write_link(&name, body, &link_offset, childclass);
+
+        for child in child_elements(body, "body") {
+            self.convert_body(child, Some(&name), &link_offset, childclass);
+        }
+    }
+
+    fn write_fixed_joint(
+        &mut self,
+        name: &str,
+        parent: &str,
+        child: &str,
+        origin: &na::Isometry3<f64>,
+    ) {
+        let _ = writeln!(
+            self.joints,
+            r#"  <joint name="{}" type="fixed">
+    {}
+    <parent link="{}"/>
+    <child link="{}"/>
+  </joint>"#,
+            xml_escape(name),
+            origin_xml(origin),
+            xml_escape(parent),
+            xml_escape(child)
+        );
+    }
+
+    fn write_joint(
+        &mut self,
+        joint: Node,
+        class: &str,
+        name: &str,
+        parent: &str,
+        child: &str,
+        origin: &na::Isometry3<f64>,
+    ) {
+        let kind = joint
+            .attribute("type")
+            .or_else(|| self.defaults.get(class, "joint", "type"))
+            .unwrap_or("hinge");
+        let range = self
+            .attr_floats(joint, class, "range")
+            .filter(|r| r.len() == 2);
+        let limited = match self.attr(joint, class, "limited").as_deref() {
+            Some("true") => true,
+            Some("false") => false,
+            _ => range.is_some(),
+        };
+
+        let urdf_type = match kind {
+            "hinge" if limited => "revolute",
+            "hinge" => "continuous",
+            "slide" => "prismatic",
+            other => {
+                warn!("MJCF {other} joint {name:?} has no URDF equivalent, treating it as fixed");
+                self.write_fixed_joint(name, parent, child, origin);
+                return;
+            }
+        };
+
+        let axis = self
+            .attr_floats(joint, class, "axis")
+            .filter(|a| a.len() == 3)
+            .unwrap_or_else(|| vec![0.0, 0.0, 1.0]);
+        let (lower, upper) = match (range, limited) {
+            (Some(r), true) if kind == "hinge" => {
+                (self.compiler.angle(r[0]), self.compiler.angle(r[1]))
+            }
+            (Some(r), true) => (r[0], r[1]),
+            _ => (0.0, 0.0),
+        };
+
+        let _ = writeln!(
+            self.joints,
+            r#"  <joint name="{}" type="{}">
+    {}
+    <parent link="{}"/>
+    <child link="{}"/>
+    <axis xyz="{} {} {}"/>
+    <limit lower="{}" upper="{}" effort="0" velocity="0"/>
+  </joint>"#,
+            xml_escape(name),
+            urdf_type,
+            origin_xml(origin),
+            xml_escape(parent),
+            xml_escape(child),
+            axis[0],
+            axis[1],
+            axis[2],
+            lower,
+            upper
+        );
+    }
+
+    /// Write a `<link>` with the geoms and inertial of `body`, offset by `link_offset`.
+    fn write_link(
+        &mut self,
+        name: &str,
+        body: Node,
+        link_offset: &na::Isometry3<f64>,
+        childclass: &str,
+    ) {
+        let mut xml = format!("  <link name=\"{}\">\n", xml_escape(name));
+
+        if let Some(inertial) = child_elements(body, "inertial").next() {
+            let pose = link_offset * self.frame(inertial, childclass);
+            let mass = inertial
+                .attribute("mass")
+                .and_then(|m| m.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            let diag = inertial.attribute("diaginertia").map(floats);
+            let full = inertial.attribute("fullinertia").map(floats);
+            // fullinertia is ixx iyy izz ixy ixz iyz
+            let i = match (full, diag) {
+                (Some(f), _) if f.len() == 6 => [f[0], f[3], f[4], f[1], f[5], f[2]],
+                (_, Some(d)) if d.len() == 3 => [d[0], 0.0, 0.0, d[1], 0.0, d[2]],
+                _ => [0.0; 6],
+            };
+            let _ = write!(
+                xml,
+                r#"    <inertial>
+      {}
+      <mass value="{}"/>
+      <inertia ixx="{}" ixy="{}" ixz="{}" iyy="{}" iyz="{}" izz="{}"/>
+    </inertial>
+"#,
+                origin_xml(&pose),
+                mass,
+                i[0],
+                i[1],
+                i[2],
+                i[3],
+                i[4],
+                i[5]
+            );
+        }
+
+        let (mut n_visuals, mut n_collisions) = (0, 0);
+        for geom in child_elements(body, "geom") {
+            let class = geom.attribute("class").unwrap_or(childclass).to_string();
+            let Some((geometry, local_pose, ellipsoid)) = self.geom_geometry(geom, &class) else {
+                continue;
+            };
+            let pose = link_offset * local_pose;
+            let visual_only = self.is_visual_only(geom, &class);
+
+            if !self.split_visuals || visual_only {
+                let _ = write!(
+                    xml,
+                    "    <visual>\n      {}\n      <geometry>{}</geometry>\n{}    </visual>\n",
+                    origin_xml(&pose),
+                    geometry,
+                    self.geom_material_xml(geom, &class)
+                );
+                if let Some(radii) = ellipsoid {
+                    self.ellipsoids.insert((name.to_string(), n_visuals), radii);
+                }
+                n_visuals += 1;
+            }
+            if !self.split_visuals || !visual_only {
+                let _ = write!(
+                    xml,
+                    "    <collision>\n      {}\n      <geometry>{}</geometry>\n    </collision>\n",
+                    origin_xml(&pose),
+                    geometry
+                );
+                if let Some(radii) = ellipsoid {
+                    self.collision_ellipsoids
+                        .insert((name.to_string(), n_collisions), radii);
+                }
+                n_collisions += 1;
+            }
+        }
+
+        xml.push_str("  </link>\n");
+        self.links.push_str(&xml);
+    }
+
+    /// URDF `<geometry>` contents and pose of a geom, plus ellipsoid radii for ellipsoids.
+    ///
+    /// Returns `None` for geoms URDF can't show (planes, height fields, unknown meshes).
+    fn geom_geometry(
+        &self,
+        geom: Node,
+        class: &str,
+    ) -> Option<(String, na::Isometry3<f64>, Option<[f64; 3]>)> {
+        let kind = self.geom_type(geom, class);
+        let size = self.attr_floats(geom, class, "size").unwrap_or_default();
+        let size_at = |i: usize| size.get(i).copied().unwrap_or(0.0);
+
+        // `fromto` places the geom's z axis along the segment
+        let fromto = geom
+            .attribute("fromto")
+            .map(floats)
+            .filter(|f| f.len() == 6);
+        let (pose, half_length) = match &fromto {
+            Some(f) => {
+                let from = na::Vector3::new(f[0], f[1], f[2]);
+                let to = na::Vector3::new(f[3], f[4], f[5]);
+                let rot = na::UnitQuaternion::rotation_between(&na::Vector3::z(), &(to - from))
+                    .unwrap_or_else(|| {
+                        na::UnitQuaternion::from_axis_angle(
+                            &na::Vector3::x_axis(),
+                            std::f64::consts::PI,
+                        )
+                    });
+                (
+                    na::Isometry3::from_parts(na::Translation3::from((from + to) / 2.0), rot),
+                    (to - from).norm() / 2.0,
+                )
+            }
+            None => (self.frame(geom, class), size_at(1)),
+        };
+
+        let geometry = match kind.as_str() {
+            "sphere" => format!(r#"<sphere radius="{}"/>"#, size_at(0)),
+            "capsule" => format!(
+                r#"<capsule radius="{}" length="{}"/>"#,
+                size_at(0),
+                2.0 * half_length
+            ),
+            "cylinder" => format!(
+                r#"<cylinder radius="{}" length="{}"/>"#,
+                size_at(0),
+                2.0 * half_length
+            ),
+            "box" => {
+                let half_z = if fromto.is_some() {
+                    half_length
+                } else {
+                    size_at(2)
+                };
+                format!(
+                    r#"<box size="{} {} {}"/>"#,
+                    2.0 * size_at(0),
+                    2.0 * size_at(1),
+                    2.0 * half_z
+                )
+            }
+            "ellipsoid" => {
+                let half_z = if fromto.is_some() {
+                    half_length
+                } else {
+                    size_at(2)
+                };
+                let radii = [size_at(0), size_at(1), half_z];
+                return Some((r#"<sphere radius="1"/>"#.to_string(), pose, Some(radii)));
+            }
+            "mesh" => {
+                let mesh_name = self.attr(geom, class, "mesh")?;
+                let Some((file, scale)) = self.meshes.get(&mesh_name) else {
+                    warn!("MJCF geom uses unknown mesh {mesh_name:?}");
+                    return None;
+                };
+                let scale = scale
+                    .map(|s| format!(r#" scale="{} {} {}""#, s[0], s[1], s[2]))
+                    .unwrap_or_default();
+                format!(r#"<mesh filename="{}"{}/>"#, xml_escape(file), scale)
+            }
+            _ => return None,
+        };
+        Some((geometry, pose, None))
+    }
+
+    /// Inline `<material>` for a geom, from its `rgba` or its material asset.
+    ///
+    /// As in MuJoCo, an `rgba` other than the default grey takes precedence over the material.
+    fn geom_material_xml(&self, geom: Node, class: &str) -> String {
+        let explicit_rgba = self
+            .attr_floats(geom, class, "rgba")
+            .filter(|c| c.len() == 4 && c[..] != DEFAULT_GEOM_RGBA[..]);
+        let material_name = self.attr(geom, class, "material");
+        let material = material_name.as_ref().and_then(|m| self.materials.get(m));
+
+        let (name, rgba, texture) = match (explicit_rgba, material) {
+            (Some(c), _) => ("rgba", [c[0], c[1], c[2], c[3]], None),
+            (None, Some((rgba, texture))) => (
+                material_name.as_deref().unwrap_or("material"),
+                *rgba,
+                texture.as_ref(),
+            ),
+            (None, None) => ("default", DEFAULT_GEOM_RGBA, None),
+        };
+
+        let mut xml = format!(
+            "      <material name=\"{}\">\n        <color rgba=\"{} {} {} {}\"/>\n",
+            xml_escape(name),
+            rgba[0],
+            rgba[1],
+            rgba[2],
+            rgba[3]
+        );
+        if let Some(texture) = texture {
+            let _ = writeln!(
+                xml,
+                r#"        <texture filename="{}"/>"#,
+                xml_escape(texture)
+            );
+        }
+        xml.push_str("      </material>\n");
+        xml
+    }
+}
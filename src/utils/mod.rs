@@ -1,5 +1,18 @@
+pub mod base_pose_utils;
 pub mod debug_log_utils;
 pub mod geometry_utils;
+pub mod inertial_utils;
+pub mod joint_limit_utils;
+pub mod joint_map_utils;
+pub mod joint_overlay_utils;
+pub mod krec_summary_utils;
+pub mod krec_utils;
+pub mod mjcf_utils;
 pub mod repl_utils;
+pub mod resource_path_utils;
 pub mod spatial_transform_utils;
+pub mod trajectory_utils;
 pub mod urdf_bfs_utils;
+pub mod urdf_tree_utils;
+pub mod video_utils;
+pub mod xacro_utils;
@@ -0,0 +1,170 @@
+// resource_path_utils.rs
+
+use anyhow::{bail, Result};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variables holding extra `package://` search roots, `:`-separated
+/// (`;` on Windows), in the order they are searched.
+pub const PACKAGE_PATH_ENV_VARS: [&str; 2] = ["KRECVIZ_PACKAGE_PATH", "ROS_PACKAGE_PATH"];
+
+/// Resolves the file names found in a robot description (meshes, textures, includes)
+/// to paths on disk.
+///
+/// Handles `package://<package>/<path>` URIs, `file://` URIs, absolute paths, and paths
+/// relative to the description file.
+#[derive(Debug, Clone)]
+pub struct ResourceResolver {
+    /// Directory of the description file, used for relative paths.
+    base_dir: PathBuf,
+    /// Roots searched for `package://` URIs.
+    package_paths: Vec<PathBuf>,
+    /// Package name -> directory, from the `package.xml` files under the roots. Built on
+    /// the first `package://` lookup.
+    packages: OnceLock<HashMap<String, PathBuf>>,
+}
+
+impl ResourceResolver {
+    /// A resolver for files referenced from a description in `base_dir`.
+    ///
+    /// `package_paths` are searched first, then the directories listed in
+    /// `PACKAGE_PATH_ENV_VARS`.
+    pub fn new(base_dir: &Path, package_paths: &[String]) -> Self {
+        let mut roots: Vec<PathBuf> = package_paths.iter().map(PathBuf::from).collect();
+        for var in PACKAGE_PATH_ENV_VARS {
+            if let Some(value) = env::var_os(var) {
+                roots.extend(env::split_paths(&value).filter(|p| !p.as_os_str().is_empty()));
+            }
+        }
+        Self::with_package_paths(base_dir, roots)
+    }
+
+    /// A resolver that only searches the given roots, ignoring the environment.
+    pub fn with_package_paths(base_dir: &Path, package_paths: Vec<PathBuf>) -> Self {
+        Self {
+            base_dir: base_dir.to_path_buf(),
+            package_paths,
+            packages: OnceLock::new(),
+        }
+    }
+
+    /// Directory that relative paths are resolved against.
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    /// Every path `uri` could refer to, most likely first.
+    pub fn candidates(&self, uri: &str) -> Vec<PathBuf> {
+        if let Some(rest) = uri.strip_prefix("package://") {
+            let (package, relative) = rest.split_once('/').unwrap_or((rest, ""));
+            let mut candidates = Vec::new();
+            for root in &self.package_paths {
+                candidates.push(root.join(package).join(relative));
+                // The root may be the package directory itself
+                if root.file_name().is_some_and(|name| name == package) {
+                    candidates.push(root.join(relative));
+                }
+            }
+            // Packages nested deeper in a workspace, e.g. `ws/src/<group>/<package>`
+            let packages = self
+                .packages
+                .get_or_init(|| find_packages(&self.package_paths));
+            if let Some(dir) = packages.get(package) {
+                candidates.push(dir.join(relative));
+            }
+            // Descriptions usually live inside their own package, e.g. `<package>/urdf/robot.urdf`
+            for ancestor in self.base_dir.ancestors() {
+                if ancestor.file_name().is_some_and(|name| name == package) {
+                    candidates.push(ancestor.join(relative));
+                }
+            }
+            candidates.dedup();
+            return candidates;
+        }
+
+        if let Some(path) = uri.strip_prefix("file://") {
+            // `file:///abs/path` and the non-standard `file://relative/path`
+            return vec![self.base_dir.join(path)];
+        }
+
+        vec![self.base_dir.join(uri)]
+    }
+
+    /// Resolve `uri` to an existing file.
+    ///
+    /// The error lists every candidate that was tried.
+    pub fn resolve(&self, uri: &str) -> Result<PathBuf> {
+        let candidates = self.candidates(uri);
+        if let Some(found) = candidates.iter().find(|p| p.exists()) {
+            // canonicalize will remove things like "../"
+            return Ok(found.canonicalize().unwrap_or_else(|_| found.clone()));
+        }
+
+        let mut msg = format!("Could not find {uri:?}");
+        if candidates.is_empty() {
+            msg.push_str(", no package search paths (pass --package-path or set ROS_PACKAGE_PATH)");
+        } else {
+            msg.push_str(", tried:");
+            for candidate in &candidates {
+                msg.push_str(&format!("\n  {}", candidate.display()));
+            }
+        }
+        bail!(msg)
+    }
+}
+
+/// Find the packages under `roots` by their `package.xml`, like `rospack` does: directories
+/// are searched recursively, except hidden ones, ones with a `CATKIN_IGNORE` or
+/// `COLCON_IGNORE` marker, and the inside of a package. The first package with a name wins.
+fn find_packages(roots: &[PathBuf]) -> HashMap<String, PathBuf> {
+    let mut packages = HashMap::new();
+    // Canonical directories already searched, so symlink loops end
+    let mut visited = HashSet::new();
+    let mut stack: Vec<PathBuf> = roots.iter().rev().cloned().collect();
+    while let Some(dir) = stack.pop() {
+        if !visited.insert(dir.canonicalize().unwrap_or_else(|_| dir.clone())) {
+            continue;
+        }
+        if dir.join("CATKIN_IGNORE").exists() || dir.join("COLCON_IGNORE").exists() {
+            continue;
+        }
+        let manifest = dir.join("package.xml");
+        if manifest.is_file() {
+            match package_name(&manifest) {
+                Some(name) => {
+                    packages.entry(name).or_insert(dir);
+                }
+                None => eprintln!("Warning: no package name in {manifest:?}"),
+            }
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut subdirs: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        // Depth-first, in name order
+        subdirs.sort_unstable_by(|a, b| b.cmp(a));
+        stack.extend(subdirs);
+    }
+    packages
+}
+
+/// The `<name>` of a `package.xml` manifest.
+fn package_name(manifest: &Path) -> Option<String> {
+    let xml = fs::read_to_string(manifest).ok()?;
+    let doc = roxmltree::Document::parse(&xml).ok()?;
+    doc.root_element()
+        .children()
+        .find(|n| n.has_tag_name("name"))
+        .and_then(|n| n.text())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
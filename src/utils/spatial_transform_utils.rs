@@ -1,6 +1,6 @@
 // spatial_transform_utils_nalgebra.rs
 
-use nalgebra::{Matrix3, Matrix4, Vector3, Rotation3, Isometry3, Translation3}; // Removed Unit for now
+use nalgebra::{Matrix3, Matrix4, Vector3, Point3, Rotation3, Isometry3, Translation3};
 use std::convert::TryInto;
 
 // Helper function to convert a nalgebra matrix to a row-major flat array.
@@ -93,4 +93,16 @@ pub fn make_4x4_from_rotation_and_translation(
     let transform = Isometry3::from_parts(Translation3::from(translation_vec), rotation.into());
     let transform_matrix4 = transform.to_homogeneous();
     matrix4_to_row_major_flat_array(&transform_matrix4)
-}
\ No newline at end of file
+}
+
+/// Convert a double precision pose into a 4×4 transform (row-major output).
+pub fn isometry_to_4x4(pose: &Isometry3<f64>) -> [f32; 16] {
+    matrix4_to_row_major_flat_array(&pose.to_homogeneous().cast::<f32>())
+}
+
+/// Transform a point by a 4×4 transform (row-major input).
+pub fn transform_point_4x4(tf_arr: [f32; 16], point_arr: [f32; 3]) -> [f32; 3] {
+    let transform_matrix4 = Matrix4::from_row_slice(&tf_arr);
+    let point = transform_matrix4.transform_point(&Point3::new(point_arr[0], point_arr[1], point_arr[2]));
+    [point.x, point.y, point.z]
+}
@@ -0,0 +1,66 @@
+// trajectory_utils.rs
+
+use std::collections::VecDeque;
+
+/// The most recent positions of a moving point, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct Trail {
+    points: VecDeque<[f32; 3]>,
+    /// Number of points kept; `None` keeps all of them
+    max_len: Option<usize>,
+}
+
+impl Trail {
+    /// A trail keeping the last `max_len` points, or every point for 0.
+    pub fn new(max_len: usize) -> Self {
+        Trail {
+            points: VecDeque::new(),
+            max_len: (max_len > 0).then_some(max_len),
+        }
+    }
+
+    /// Append a point, dropping the oldest ones beyond the trail length.
+    pub fn push(&mut self, point: [f32; 3]) {
+        self.points.push_back(point);
+        if let Some(max_len) = self.max_len {
+            while self.points.len() > max_len {
+                self.points.pop_front();
+            }
+        }
+    }
+
+    /// Whether old points are dropped, i.e. the trail length isn't 0.
+    pub fn is_bounded(&self) -> bool {
+        self.max_len.is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn last(&self) -> Option<[f32; 3]> {
+        self.points.back().copied()
+    }
+
+    /// The trail's points, oldest first, as one line strip.
+    pub fn points(&self) -> Vec<[f32; 3]> {
+        self.points.iter().copied().collect()
+    }
+}
+
+/// Distinct colors for the trails of several links, cycling after the last one.
+pub fn trail_color(index: usize) -> [u8; 4] {
+    const PALETTE: [[u8; 4]; 6] = [
+        [230, 25, 75, 255],
+        [60, 180, 75, 255],
+        [0, 130, 200, 255],
+        [245, 130, 48, 255],
+        [145, 30, 180, 255],
+        [70, 240, 240, 255],
+    ];
+    PALETTE[index % PALETTE.len()]
+}
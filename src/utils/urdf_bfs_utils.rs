@@ -1,10 +1,11 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use nalgebra as na;
 use std::collections::{HashMap, HashSet, VecDeque};
-use urdf_rs::{Joint, Link, Robot};
+use urdf_rs::{Joint, JointLimit, JointType, Link, Robot};
 
 use crate::utils::debug_log_utils::debug_log_bfs_insertion;
 use crate::utils::spatial_transform_utils::{
-    build_4x4_from_xyz_rpy, identity_4x4, mat4x4_mul, rotation_from_euler_xyz,
+    build_4x4_from_xyz_rpy, identity_4x4, isometry_to_4x4, mat4x4_mul,
 };
 
 /// Complete information about a link's position in the hierarchy and transforms
@@ -38,6 +39,16 @@ pub struct LinkBfsData {
     pub global_transform: [f32; 16],
 }
 
+/// Prepend an entity prefix to a link path, e.g. ("ghost", "base/arm") -> "ghost/base/arm".
+/// An empty prefix leaves the path unchanged.
+pub fn join_entity_path(prefix: &str, path: &str) -> String {
+    if prefix.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}/{}", prefix.trim_end_matches('/'), path)
+    }
+}
+
 /// Build adjacency map from joints to their child links
 pub fn build_adjacency(joints: &[Joint]) -> HashMap<String, Vec<(Joint, String)>> {
     let mut adj = HashMap::new();
@@ -51,29 +62,27 @@ pub fn build_adjacency(joints: &[Joint]) -> HashMap<String, Vec<(Joint, String)>
     adj
 }
 
-/// Find the root link name by identifying the link that isn't a child of any joint
+/// Find the root link name: the first link (in declaration order) that isn't a child of any joint
 pub fn find_root_link_name(links: &[Link], joints: &[Joint]) -> Option<String> {
-    let mut all_links = HashSet::new();
-    let mut child_links = HashSet::new();
-
-    for l in links {
-        all_links.insert(l.name.clone());
-    }
-    for j in joints {
-        child_links.insert(j.child.link.clone());
-    }
+    let child_links: HashSet<&str> = joints.iter().map(|j| j.child.link.as_str()).collect();
 
-    all_links.difference(&child_links).next().cloned()
+    links
+        .iter()
+        .find(|l| !child_links.contains(l.name.as_str()))
+        .map(|l| l.name.clone())
 }
 
-/// Build a BFS-based map from each link's name => LinkBfsData,
+/// Build a BFS-based map from each link's name => LinkBfsData, starting at `root_link`
+/// (e.g. the root picked by `urdf_tree_utils::check_tree`),
 /// and return both the map and the BFS traversal order.
-pub fn build_link_bfs_map(robot: &Robot) -> (HashMap<String, LinkBfsData>, Vec<String>) {
+pub fn build_link_bfs_map(
+    robot: &Robot,
+    root_link: &str,
+) -> (HashMap<String, LinkBfsData>, Vec<String>) {
     let adjacency = build_adjacency(&robot.joints);
 
-    // 1) Find root link
-    let root_link =
-        find_root_link_name(&robot.links, &robot.joints).unwrap_or_else(|| "base".to_string());
+    // 1) Start from the root link
+    let root_link = root_link.to_string();
 
     // 2) BFS from root to discover all link paths and transforms
     let mut link_bfs_map = HashMap::new();
@@ -120,6 +129,10 @@ pub fn build_link_bfs_map(robot: &Robot) -> (HashMap<String, LinkBfsData>, Vec<S
 
             // 3) Now iterate the sorted children in alphabetical order
             for (joint, child_link_name) in sorted_children {
+                // Links reached a second time (several parents or a cycle) keep their first path
+                if link_bfs_map.contains_key(&child_link_name) {
+                    continue;
+                }
                 let child_link_only_path = format!("{}/{}", parent_link_only_path, child_link_name);
                 let child_link_full_path = format!(
                     "{}/{}/{}",
@@ -161,18 +174,75 @@ pub fn build_link_bfs_map(robot: &Robot) -> (HashMap<String, LinkBfsData>, Vec<S
     (link_bfs_map, bfs_order)
 }
 
-/// Information about a joint's position and transforms
+/// Information about a joint's position, transforms and motion
 #[derive(Debug, Clone)]
 pub struct JointInfo {
     pub entity_path: String,
-    pub origin_translation: [f32; 3],
-    pub base_rotation: [f32; 9],
+    /// Name of the link this joint hangs from
+    pub parent_link: String,
+    /// Name of the link moved by this joint
+    pub child_link: String,
+    /// The joint's `<origin>` relative to its parent link
+    pub origin: na::Isometry3<f64>,
+    /// Revolute, continuous, prismatic, fixed, ...
+    pub joint_type: JointType,
+    /// Axis of motion in the joint frame (as written in the URDF, not normalized)
+    pub axis: [f64; 3],
+    /// Position/velocity/effort limits from the URDF `<limit>` tag
+    pub limit: JointLimit,
+    /// Set when this joint follows another joint through a `<mimic>` tag
+    pub mimic: Option<MimicInfo>,
+}
+
+/// A `<mimic joint=... multiplier=... offset=...>` relationship:
+/// `position = multiplier * leader_position + offset`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MimicInfo {
+    /// Name of the joint being mimicked
+    pub joint: String,
+    pub multiplier: f64,
+    pub offset: f64,
+}
+
+impl JointInfo {
+    /// Whether a single recorded value can move this joint.
+    pub fn is_animated(&self) -> bool {
+        matches!(
+            self.joint_type,
+            JointType::Revolute | JointType::Continuous | JointType::Prismatic
+        )
+    }
+
+    /// The joint's pose relative to its parent link for a given joint position, in radians
+    /// for revolute/continuous joints and meters for prismatic ones.
+    ///
+    /// Joints that cannot be driven by a single value (or have a zero axis) stay at their
+    /// origin.
+    pub fn local_pose(&self, position: f64) -> na::Isometry3<f64> {
+        let Some(axis) = na::Unit::try_new(na::Vector3::from(self.axis), 1e-9) else {
+            return self.origin;
+        };
+        let motion = match self.joint_type {
+            JointType::Revolute | JointType::Continuous => {
+                na::Isometry3::rotation(axis.into_inner() * position)
+            }
+            JointType::Prismatic => {
+                na::Isometry3::translation(axis.x * position, axis.y * position, axis.z * position)
+            }
+            _ => na::Isometry3::identity(),
+        };
+        self.origin * motion
+    }
+
+    /// [`JointInfo::local_pose`] as a row-major transform.
+    pub fn local_transform(&self, position: f64) -> [f32; 16] {
+        isometry_to_4x4(&self.local_pose(position))
+    }
 }
 
-/// Build joint info map using the BFS data
-pub fn build_joint_name_to_joint_info(urdf_path: &str) -> Result<HashMap<String, JointInfo>> {
-    let robot = urdf_rs::read_file(urdf_path)?;
-    let (link_bfs_map, _bfs_order) = build_link_bfs_map(&robot); // Destructure the tuple
+/// Build joint info map for an already parsed robot, walking it from `root_link`
+pub fn build_joint_info_map(robot: &Robot, root_link: &str) -> HashMap<String, JointInfo> {
+    let (link_bfs_map, _bfs_order) = build_link_bfs_map(robot, root_link); // Destructure the tuple
 
     let mut joint_info_map = HashMap::new();
 
@@ -180,27 +250,135 @@ pub fn build_joint_name_to_joint_info(urdf_path: &str) -> Result<HashMap<String,
         if let Some(child_data) = link_bfs_map.get(&joint.child.link) {
             let entity_path = child_data.link_only_path.clone();
 
-            let translation = [
-                joint.origin.xyz[0] as f32,
-                joint.origin.xyz[1] as f32,
-                joint.origin.xyz[2] as f32,
-            ];
-
-            let base_rotation = rotation_from_euler_xyz(
-                joint.origin.rpy[0],
-                joint.origin.rpy[1],
-                joint.origin.rpy[2],
+            let [x, y, z] = [0, 1, 2].map(|i| joint.origin.xyz[i]);
+            let [roll, pitch, yaw] = [0, 1, 2].map(|i| joint.origin.rpy[i]);
+            let origin = na::Isometry3::from_parts(
+                na::Translation3::new(x, y, z),
+                na::UnitQuaternion::from_euler_angles(roll, pitch, yaw),
             );
 
             let info = JointInfo {
                 entity_path,
-                origin_translation: translation,
-                base_rotation,
+                parent_link: joint.parent.link.clone(),
+                child_link: joint.child.link.clone(),
+                origin,
+                joint_type: joint.joint_type.clone(),
+                axis: [joint.axis.xyz[0], joint.axis.xyz[1], joint.axis.xyz[2]],
+                limit: joint.limit.clone(),
+                mimic: joint.mimic.as_ref().map(|m| MimicInfo {
+                    joint: m.joint.clone(),
+                    multiplier: m.multiplier.unwrap_or(1.0),
+                    offset: m.offset.unwrap_or(0.0),
+                }),
             };
 
             joint_info_map.insert(joint.name.clone(), info);
         }
     }
 
-    Ok(joint_info_map)
+    joint_info_map
+}
+
+/// Compute every link's pose relative to the root link for the given joint positions,
+/// walking `bfs_order` from the root.
+///
+/// Joints without a position stay at zero.
+pub fn compute_link_poses(
+    bfs_order: &[String],
+    joint_info_map: &HashMap<String, JointInfo>,
+    joint_positions: &HashMap<String, f64>,
+) -> HashMap<String, na::Isometry3<f64>> {
+    let joint_by_child: HashMap<&str, (&String, &JointInfo)> = joint_info_map
+        .iter()
+        .map(|(name, info)| (info.child_link.as_str(), (name, info)))
+        .collect();
+
+    let mut poses = HashMap::with_capacity(bfs_order.len());
+    for link_name in bfs_order {
+        let pose = match joint_by_child.get(link_name.as_str()) {
+            Some((joint_name, info)) => {
+                let parent = poses
+                    .get(&info.parent_link)
+                    .copied()
+                    .unwrap_or_else(na::Isometry3::identity);
+                let position = joint_positions.get(*joint_name).copied().unwrap_or(0.0);
+                parent * info.local_pose(position)
+            }
+            None => na::Isometry3::identity(),
+        };
+        poses.insert(link_name.clone(), pose);
+    }
+
+    poses
+}
+
+/// Check that every mimic joint follows a known joint and that no mimic chain loops back on itself.
+pub fn validate_mimic_joints(joint_info_map: &HashMap<String, JointInfo>) -> Result<()> {
+    let mut names: Vec<&String> = joint_info_map.keys().collect();
+    names.sort();
+
+    for start in names {
+        let mut chain = vec![start.as_str()];
+        let mut current = start.as_str();
+
+        while let Some(mimic) = joint_info_map.get(current).and_then(|j| j.mimic.as_ref()) {
+            if !joint_info_map.contains_key(&mimic.joint) {
+                bail!("Joint '{}' mimics unknown joint '{}'", current, mimic.joint);
+            }
+            if chain.contains(&mimic.joint.as_str()) {
+                chain.push(&mimic.joint);
+                bail!("Mimic joints form a cycle: {}", chain.join(" -> "));
+            }
+            chain.push(&mimic.joint);
+            current = &mimic.joint;
+        }
+    }
+
+    Ok(())
+}
+
+/// Compute the positions of all mimic joints from the positions of the joints they follow.
+///
+/// Chained mimics are resolved transitively. Joints that already have a position in
+/// `positions` are left alone, and followers whose chain never reaches a known
+/// position are omitted. Only the newly derived positions are returned.
+pub fn resolve_mimic_positions(
+    joint_info_map: &HashMap<String, JointInfo>,
+    positions: &HashMap<String, f64>,
+) -> HashMap<String, f64> {
+    let mut resolved = HashMap::new();
+
+    for (name, info) in joint_info_map {
+        if info.mimic.is_none() || positions.contains_key(name) {
+            continue;
+        }
+
+        // Walk up the chain until we hit a joint with a known position
+        let mut chain = Vec::new();
+        let mut current = name.as_str();
+        let leader_position = loop {
+            if let Some(pos) = positions.get(current).or_else(|| resolved.get(current)) {
+                break Some(*pos);
+            }
+            let Some(mimic) = joint_info_map.get(current).and_then(|j| j.mimic.as_ref()) else {
+                break None;
+            };
+            // Guard against cycles in URDFs that skipped validation
+            if chain.len() > joint_info_map.len() {
+                break None;
+            }
+            chain.push(mimic);
+            current = &mimic.joint;
+        };
+
+        if let Some(leader_position) = leader_position {
+            let position = chain
+                .iter()
+                .rev()
+                .fold(leader_position, |p, m| m.multiplier * p + m.offset);
+            resolved.insert(name.clone(), position);
+        }
+    }
+
+    resolved
 }
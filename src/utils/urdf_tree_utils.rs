@@ -0,0 +1,241 @@
+// urdf_tree_utils.rs
+
+use anyhow::{bail, Result};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use urdf_rs::Robot;
+
+/// A structural problem in a robot's link/joint tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeIssue {
+    /// Several links are not the child of any joint
+    MultipleRoots(Vec<String>),
+    /// Every link is the child of some joint
+    NoRoot,
+    /// A joint's parent or child link is not declared
+    UnknownLink { joint: String, link: String },
+    /// A link is the child of more than one joint
+    MultipleParents { link: String, joints: Vec<String> },
+    /// Joints leading from a link back to itself, as the links along the way
+    Cycle(Vec<String>),
+    /// Links that don't hang from the root link
+    Orphaned { root: String, links: Vec<String> },
+}
+
+impl fmt::Display for TreeIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeIssue::MultipleRoots(links) => {
+                write!(
+                    f,
+                    "several links have no parent joint: {}",
+                    links.join(", ")
+                )
+            }
+            TreeIssue::NoRoot => write!(f, "every link is the child of a joint"),
+            TreeIssue::UnknownLink { joint, link } => {
+                write!(f, "joint '{joint}' references unknown link '{link}'")
+            }
+            TreeIssue::MultipleParents { link, joints } => write!(
+                f,
+                "link '{link}' is the child of several joints: {}",
+                joints.join(", ")
+            ),
+            TreeIssue::Cycle(links) => write!(f, "joints form a cycle: {}", links.join(" -> ")),
+            TreeIssue::Orphaned { root, links } => write!(
+                f,
+                "links not connected to root link '{root}': {}",
+                links.join(", ")
+            ),
+        }
+    }
+}
+
+/// Outcome of checking a robot's link/joint tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeReport {
+    /// The link the tree hangs from
+    pub root: String,
+    /// Empty for a well-formed tree
+    pub issues: Vec<TreeIssue>,
+}
+
+/// Check that the links and joints form a single tree, and pick its root: `root_override`
+/// if given, otherwise the first link (in declaration order) without a parent joint.
+///
+/// Fails only when there is no link at all or the override is unusable; everything else
+/// is reported in [`TreeReport::issues`].
+pub fn check_tree(robot: &Robot, root_override: Option<&str>) -> Result<TreeReport> {
+    let link_names: Vec<&str> = robot.links.iter().map(|l| l.name.as_str()).collect();
+    let known: HashSet<&str> = link_names.iter().copied().collect();
+    let mut issues = Vec::new();
+
+    // Joints between declared links, as parent -> children and child -> parent joints
+    let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut parent_joints: HashMap<&str, Vec<&str>> = HashMap::new();
+    for joint in &robot.joints {
+        let (parent, child) = (joint.parent.link.as_str(), joint.child.link.as_str());
+        let mut both_known = true;
+        for link in [parent, child] {
+            if !known.contains(link) {
+                issues.push(TreeIssue::UnknownLink {
+                    joint: joint.name.clone(),
+                    link: link.to_string(),
+                });
+                both_known = false;
+            }
+        }
+        if both_known {
+            children.entry(parent).or_default().push(child);
+            parent_joints
+                .entry(child)
+                .or_default()
+                .push(joint.name.as_str());
+        }
+    }
+
+    for link in &link_names {
+        if let Some(joints) = parent_joints.get(link).filter(|j| j.len() > 1) {
+            issues.push(TreeIssue::MultipleParents {
+                link: link.to_string(),
+                joints: joints.iter().map(|j| j.to_string()).collect(),
+            });
+        }
+    }
+
+    let parentless: Vec<&str> = link_names
+        .iter()
+        .copied()
+        .filter(|link| !parent_joints.contains_key(link))
+        .collect();
+    let root = match root_override {
+        Some(root) => {
+            if !known.contains(root) {
+                bail!("Root link '{root}' not found in URDF");
+            }
+            if let Some(joints) = parent_joints.get(root) {
+                bail!("Root link '{root}' is the child of joint '{}'", joints[0]);
+            }
+            root
+        }
+        None => {
+            if parentless.len() > 1 {
+                issues.push(TreeIssue::MultipleRoots(
+                    parentless.iter().map(|l| l.to_string()).collect(),
+                ));
+            }
+            match (parentless.first(), link_names.first()) {
+                (Some(root), _) => root,
+                (None, Some(first)) => {
+                    issues.push(TreeIssue::NoRoot);
+                    first
+                }
+                (None, None) => bail!("URDF has no links"),
+            }
+        }
+    };
+
+    let mut search = CycleSearch {
+        children: &children,
+        visiting: HashSet::new(),
+        done: HashSet::new(),
+        path: Vec::new(),
+        cycles: Vec::new(),
+    };
+    for link in &link_names {
+        search.visit(link);
+    }
+    issues.extend(search.cycles.into_iter().map(TreeIssue::Cycle));
+
+    let reachable = reachable_links(&children, root);
+    let orphaned: Vec<String> = link_names
+        .iter()
+        .filter(|link| !reachable.contains(*link))
+        .map(|link| link.to_string())
+        .collect();
+    if !orphaned.is_empty() {
+        issues.push(TreeIssue::Orphaned {
+            root: root.to_string(),
+            links: orphaned,
+        });
+    }
+
+    Ok(TreeReport {
+        root: root.to_string(),
+        issues,
+    })
+}
+
+/// A copy of the robot keeping only the links that hang from `root`, each through the first
+/// joint reaching it breadth-first, so it can be walked as a tree.
+pub fn prune_to_tree(robot: &Robot, root: &str) -> Robot {
+    let mut reached: HashSet<&str> = HashSet::from([root]);
+    let mut kept_joints: HashSet<&str> = HashSet::new();
+    let mut queue = VecDeque::from([root]);
+    while let Some(parent) = queue.pop_front() {
+        for joint in robot.joints.iter().filter(|j| j.parent.link == parent) {
+            let child = joint.child.link.as_str();
+            if robot.links.iter().any(|l| l.name == child) && reached.insert(child) {
+                kept_joints.insert(joint.name.as_str());
+                queue.push_back(child);
+            }
+        }
+    }
+
+    let mut pruned = robot.clone();
+    pruned.links.retain(|l| reached.contains(l.name.as_str()));
+    pruned
+        .joints
+        .retain(|j| kept_joints.contains(j.name.as_str()));
+    pruned
+}
+
+fn reachable_links<'a>(
+    children: &HashMap<&'a str, Vec<&'a str>>,
+    root: &'a str,
+) -> HashSet<&'a str> {
+    let mut reached = HashSet::from([root]);
+    let mut queue = VecDeque::from([root]);
+    while let Some(link) = queue.pop_front() {
+        for child in children.get(link).into_iter().flatten() {
+            if reached.insert(child) {
+                queue.push_back(child);
+            }
+        }
+    }
+    reached
+}
+
+/// Depth-first search recording every back edge as a cycle.
+struct CycleSearch<'a, 'b> {
+    children: &'b HashMap<&'a str, Vec<&'a str>>,
+    /// Links on the current path
+    visiting: HashSet<&'a str>,
+    done: HashSet<&'a str>,
+    path: Vec<&'a str>,
+    cycles: Vec<Vec<String>>,
+}
+
+impl<'a> CycleSearch<'a, '_> {
+    fn visit(&mut self, link: &'a str) {
+        if self.done.contains(link) {
+            return;
+        }
+        self.visiting.insert(link);
+        self.path.push(link);
+        for &child in self.children.get(link).into_iter().flatten() {
+            if self.visiting.contains(child) {
+                let start = self.path.iter().position(|l| *l == child).unwrap_or(0);
+                let mut cycle: Vec<String> =
+                    self.path[start..].iter().map(|l| l.to_string()).collect();
+                cycle.push(child.to_string());
+                self.cycles.push(cycle);
+            } else {
+                self.visit(child);
+            }
+        }
+        self.path.pop();
+        self.visiting.remove(link);
+        self.done.insert(link);
+    }
+}
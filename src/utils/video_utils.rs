@@ -0,0 +1,207 @@
+// video_utils.rs
+
+use krec::KRecFrame;
+use std::io::{self, BufReader, Read};
+
+/// How the frames of a KREC are matched to the frames of its camera video.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoSync {
+    /// The `video_frame_number` the recorder stored with each frame
+    FrameNumber,
+    /// Each frame's `video_timestamp`, counted from the first frame's
+    Timestamp,
+    /// Neither was recorded: KREC frame i shows video frame i
+    FrameIndex,
+}
+
+/// Where in the video a KREC frame is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoPosition {
+    /// Index of the video frame
+    Frame(usize),
+    /// Nanoseconds after the first video frame
+    Time(u64),
+}
+
+impl VideoSync {
+    /// Use what the recorder filled in, preferring the exact frame numbers.
+    pub fn detect(frames: &[KRecFrame]) -> Self {
+        if frames.iter().any(|f| f.video_frame_number != 0) {
+            VideoSync::FrameNumber
+        } else if frames
+            .windows(2)
+            .any(|w| w[0].video_timestamp != w[1].video_timestamp)
+        {
+            VideoSync::Timestamp
+        } else {
+            VideoSync::FrameIndex
+        }
+    }
+
+    /// Position in the video of KREC frame `frame_idx`. The first KREC frame, with
+    /// `first_video_timestamp`, is taken to show the first video frame.
+    pub fn position(
+        &self,
+        frame_idx: usize,
+        frame: &KRecFrame,
+        first_video_timestamp: u64,
+    ) -> VideoPosition {
+        match self {
+            VideoSync::FrameNumber => VideoPosition::Frame(frame.video_frame_number as usize),
+            VideoSync::Timestamp => {
+                VideoPosition::Time(frame.video_timestamp.saturating_sub(first_video_timestamp))
+            }
+            VideoSync::FrameIndex => VideoPosition::Frame(frame_idx),
+        }
+    }
+}
+
+/// Index of the video frame on screen `time_ns` after the first one, i.e. the last frame
+/// starting at or before it, given every frame's presentation time in ascending order.
+pub fn frame_at_time(frame_timestamps_ns: &[i64], time_ns: i64) -> Option<usize> {
+    let first = *frame_timestamps_ns.first()?;
+    frame_timestamps_ns
+        .partition_point(|&t| t - first <= time_ns)
+        .checked_sub(1)
+}
+
+/// Index of the frame on screen `time_ns` after the first one in a video recorded at
+/// a constant `fps`.
+pub fn frame_at_rate(time_ns: u64, fps: f64) -> usize {
+    // The margin keeps a time right at a frame's start from rounding down onto the previous one
+    (time_ns as f64 * fps / 1e9 + 1e-6).floor() as usize
+}
+
+/// Parse a frame rate as printed by ffprobe, e.g. `30000/1001` or `25`.
+pub fn parse_frame_rate(rate: &str) -> Option<f64> {
+    let rate = rate.trim();
+    let fps = match rate.split_once('/') {
+        Some((num, den)) => num.parse::<f64>().ok()? / den.parse::<f64>().ok()?,
+        None => rate.parse().ok()?,
+    };
+    (fps.is_finite() && fps > 0.0).then_some(fps)
+}
+
+/// The images of a stream of concatenated JPEGs, such as ffmpeg's `image2pipe` output.
+pub struct JpegFrames<R: Read> {
+    reader: BufReader<R>,
+    failed: bool,
+}
+
+impl<R: Read> JpegFrames<R> {
+    pub fn new(reader: R) -> Self {
+        JpegFrames {
+            reader: BufReader::new(reader),
+            failed: false,
+        }
+    }
+}
+
+impl<R: Read> Iterator for JpegFrames<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        match read_jpeg(&mut self.reader) {
+            Ok(jpeg) => jpeg.map(Ok),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Read one JPEG, from its SOI marker to its EOI marker, or `None` at the end of the stream.
+///
+/// Walks the marker segments by their lengths, so only the entropy-coded data after SOS
+/// has to be scanned for the next marker.
+fn read_jpeg(reader: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut soi = [0u8; 2];
+    match reader.read_exact(&mut soi) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    if soi != [0xFF, 0xD8] {
+        return Err(invalid_data("expected a JPEG start of image marker"));
+    }
+
+    let mut jpeg = soi.to_vec();
+    let mut marker = read_marker(reader, &mut jpeg)?;
+    loop {
+        match marker {
+            // End of image
+            0xD9 => return Ok(Some(jpeg)),
+            // Markers without a segment
+            0x01 | 0xD0..=0xD7 => marker = read_marker(reader, &mut jpeg)?,
+            _ => {
+                let mut length = [0u8; 2];
+                reader.read_exact(&mut length)?;
+                jpeg.extend_from_slice(&length);
+                let length = u16::from_be_bytes(length) as usize;
+                if length < 2 {
+                    return Err(invalid_data("JPEG segment shorter than its length field"));
+                }
+                let start = jpeg.len();
+                jpeg.resize(start + length - 2, 0);
+                reader.read_exact(&mut jpeg[start..])?;
+
+                // Start of scan: entropy-coded data follows the header
+                marker = if marker == 0xDA {
+                    read_entropy_coded_data(reader, &mut jpeg)?
+                } else {
+                    read_marker(reader, &mut jpeg)?
+                };
+            }
+        }
+    }
+}
+
+/// Read the marker that follows, skipping fill bytes, and return its code.
+fn read_marker(reader: &mut impl Read, jpeg: &mut Vec<u8>) -> io::Result<u8> {
+    if read_byte(reader)? != 0xFF {
+        return Err(invalid_data("expected a JPEG marker"));
+    }
+    jpeg.push(0xFF);
+    loop {
+        let code = read_byte(reader)?;
+        jpeg.push(code);
+        if code != 0xFF {
+            return Ok(code);
+        }
+    }
+}
+
+/// Read entropy-coded data up to the next marker, other than stuffed bytes and restart
+/// markers, and return its code.
+fn read_entropy_coded_data(reader: &mut impl Read, jpeg: &mut Vec<u8>) -> io::Result<u8> {
+    loop {
+        let byte = read_byte(reader)?;
+        jpeg.push(byte);
+        if byte != 0xFF {
+            continue;
+        }
+        let mut code = read_byte(reader)?;
+        while code == 0xFF {
+            jpeg.push(code);
+            code = read_byte(reader)?;
+        }
+        jpeg.push(code);
+        if code != 0x00 && !(0xD0..=0xD7).contains(&code) {
+            return Ok(code);
+        }
+    }
+}
+
+fn read_byte(reader: &mut impl Read) -> io::Result<u8> {
+    let mut byte = [0u8; 1];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
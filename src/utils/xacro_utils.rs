@@ -0,0 +1,1176 @@
+// xacro_utils.rs
+
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::utils::resource_path_utils::ResourceResolver;
+
+/// Macro calls nested deeper than this are reported as infinite recursion.
+const MAX_MACRO_DEPTH: usize = 100;
+
+/// Whether an XML document uses xacro, i.e. declares the xacro namespace.
+pub fn is_xacro(xml: &str) -> bool {
+    roxmltree::Document::parse(xml)
+        .map(|doc| {
+            doc.root_element()
+                .namespaces()
+                .any(|ns| is_xacro_namespace(ns.uri()))
+        })
+        .unwrap_or(false)
+}
+
+/// Expand a xacro file into a plain URDF document.
+///
+/// Relative `xacro:include` file names are resolved against the including file and
+/// `$(find <package>)` through `resolver`'s package search path. `args` override the
+/// defaults of `<xacro:arg>`.
+pub fn expand_xacro_file(
+    path: &Path,
+    resolver: &ResourceResolver,
+    args: &HashMap<String, String>,
+) -> Result<String> {
+    let xml = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read xacro file {path:?}: {e}"))?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let includes = vec![path.canonicalize().unwrap_or_else(|_| path.to_path_buf())];
+    expand_xacro_in_dir(&xml, dir, includes, resolver, args)
+}
+
+/// Expand a xacro document into a plain URDF document, resolving relative includes
+/// against `resolver`'s base directory.
+///
+/// Supports `xacro:property` (values and blocks), `${}` expressions, `xacro:macro` with
+/// value, default (`:=`), inherited (`:=^`) and block (`*`, `**`) parameters,
+/// `xacro:insert_block`, `xacro:include` (with `ns`), `xacro:if`/`xacro:unless`,
+/// `xacro:arg`, and the `$(arg)`, `$(find)`, `$(env)`, `$(optenv)` and `$(eval)`
+/// substitutions.
+pub fn expand_xacro(
+    xml: &str,
+    resolver: &ResourceResolver,
+    args: &HashMap<String, String>,
+) -> Result<String> {
+    expand_xacro_in_dir(xml, resolver.base_dir(), Vec::new(), resolver, args)
+}
+
+fn expand_xacro_in_dir(
+    xml: &str,
+    dir: &Path,
+    includes: Vec<PathBuf>,
+    resolver: &ResourceResolver,
+    args: &HashMap<String, String>,
+) -> Result<String> {
+    let root = parse_xml(xml)?;
+    let mut expander = Expander {
+        resolver,
+        args: args.clone(),
+        scopes: vec![Scope::default()],
+        dirs: vec![dir.to_path_buf()],
+        includes,
+        depth: 0,
+    };
+
+    let mut expanded = Vec::new();
+    expander.expand_nodes(&[XmlNode::Element(root)], &mut expanded)?;
+    let mut elements = expanded.into_iter().filter_map(|node| match node {
+        XmlNode::Element(e) => Some(e),
+        XmlNode::Text(_) => None,
+    });
+    let (Some(root), None) = (elements.next(), elements.next()) else {
+        bail!("Expanded xacro document must have exactly one root element");
+    };
+
+    let mut out = String::from("<?xml version=\"1.0\"?>\n");
+    write_element(&root, 0, &mut out);
+    Ok(out)
+}
+
+// -----------------------------------------------------------------------------
+// Owned XML tree
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+enum XmlNode {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug, Clone)]
+struct Element {
+    /// Local name for xacro elements, qualified name (`prefix:name`) otherwise.
+    name: String,
+    is_xacro: bool,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlNode>,
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn required_attribute(&self, name: &str) -> Result<&str> {
+        self.attribute(name)
+            .ok_or_else(|| anyhow!("<xacro:{}> is missing the {name:?} attribute", self.name))
+    }
+
+    fn child_elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|c| match c {
+            XmlNode::Element(e) => Some(e),
+            XmlNode::Text(_) => None,
+        })
+    }
+}
+
+fn is_xacro_namespace(uri: &str) -> bool {
+    uri.contains("ros.org/wiki/xacro")
+}
+
+/// Parse a document into an owned tree, dropping comments and the xacro namespace
+/// declaration.
+fn parse_xml(xml: &str) -> Result<Element> {
+    let doc = roxmltree::Document::parse(xml).map_err(|e| anyhow!("Failed to parse xacro: {e}"))?;
+    let root = doc.root_element();
+    let mut element = convert_node(root);
+    for ns in root.namespaces() {
+        if is_xacro_namespace(ns.uri()) || ns.name() == Some("xml") {
+            continue;
+        }
+        let name = match ns.name() {
+            Some(prefix) => format!("xmlns:{prefix}"),
+            None => "xmlns".to_string(),
+        };
+        element.attributes.insert(0, (name, ns.uri().to_string()));
+    }
+    Ok(element)
+}
+
+fn qualified_name(node: roxmltree::Node, namespace: Option<&str>, local: &str) -> String {
+    match namespace.and_then(|uri| node.lookup_prefix(uri)) {
+        Some(prefix) => format!("{prefix}:{local}"),
+        None => local.to_string(),
+    }
+}
+
+fn convert_node(node: roxmltree::Node) -> Element {
+    let tag = node.tag_name();
+    let is_xacro = tag.namespace().is_some_and(is_xacro_namespace);
+    let name = if is_xacro {
+        tag.name().to_string()
+    } else {
+        qualified_name(node, tag.namespace(), tag.name())
+    };
+    let attributes = node
+        .attributes()
+        .filter(|a| !a.namespace().is_some_and(is_xacro_namespace))
+        .map(|a| {
+            (
+                qualified_name(node, a.namespace(), a.name()),
+                a.value().to_string(),
+            )
+        })
+        .collect();
+    let children = node
+        .children()
+        .filter_map(|c| {
+            if c.is_element() {
+                Some(XmlNode::Element(convert_node(c)))
+            } else if c.is_text() {
+                c.text()
+                    .filter(|t| !t.trim().is_empty())
+                    .map(|t| XmlNode::Text(t.to_string()))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    Element {
+        name,
+        is_xacro,
+        attributes,
+        children,
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn write_element(element: &Element, indent: usize, out: &mut String) {
+    let pad = "  ".repeat(indent);
+    let _ = write!(out, "{pad}<{}", element.name);
+    for (name, value) in &element.attributes {
+        let _ = write!(out, " {name}=\"{}\"", xml_escape(value));
+    }
+    match element.children.as_slice() {
+        [] => out.push_str("/>\n"),
+        [XmlNode::Text(text)] => {
+            let _ = writeln!(out, ">{}</{}>", xml_escape(text), element.name);
+        }
+        children => {
+            out.push_str(">\n");
+            for child in children {
+                match child {
+                    XmlNode::Element(e) => write_element(e, indent + 1, out),
+                    XmlNode::Text(text) => {
+                        let _ = writeln!(out, "{pad}  {}", xml_escape(text.trim()));
+                    }
+                }
+            }
+            let _ = writeln!(out, "{pad}</{}>", element.name);
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Expansion
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+enum Property {
+    Value(Value),
+    /// XML content inserted with `<xacro:insert_block>`; `expanded` blocks were already
+    /// expanded where they were written (macro block parameters).
+    Block {
+        nodes: Vec<XmlNode>,
+        expanded: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ParamKind {
+    Value,
+    /// `*name`: the next child element of the call.
+    Block,
+    /// `**name`: the children of the next child element of the call.
+    BlockContents,
+}
+
+#[derive(Debug, Clone)]
+struct MacroParam {
+    name: String,
+    kind: ParamKind,
+    /// Text after `:=`, without the leading `^` of an inherited parameter.
+    default: Option<String>,
+    /// `:=^`: take the value of the same-named property from the calling scope.
+    inherit: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Macro {
+    name: String,
+    params: Vec<MacroParam>,
+    body: Vec<XmlNode>,
+    /// Properties of the `ns` include the macro was defined in, visible to its body.
+    namespace: Option<Rc<HashMap<String, Property>>>,
+}
+
+#[derive(Debug, Default)]
+struct Scope {
+    properties: HashMap<String, Property>,
+    macros: HashMap<String, Rc<Macro>>,
+}
+
+struct Expander<'a> {
+    resolver: &'a ResourceResolver,
+    args: HashMap<String, String>,
+    /// Innermost scope last.
+    scopes: Vec<Scope>,
+    /// Directory of the file being expanded, innermost last.
+    dirs: Vec<PathBuf>,
+    /// Canonical paths of the files being expanded, innermost last, to catch include cycles.
+    includes: Vec<PathBuf>,
+    depth: usize,
+}
+
+impl Expander<'_> {
+    fn expand_nodes(&mut self, nodes: &[XmlNode], out: &mut Vec<XmlNode>) -> Result<()> {
+        for node in nodes {
+            match node {
+                XmlNode::Text(text) => {
+                    out.push(XmlNode::Text(self.eval_text(text)?.to_string()));
+                }
+                XmlNode::Element(element) if element.is_xacro => {
+                    self.expand_xacro_element(element, out)?;
+                }
+                XmlNode::Element(element) => {
+                    let attributes = element
+                        .attributes
+                        .iter()
+                        .map(|(name, value)| Ok((name.clone(), self.eval_text(value)?.to_string())))
+                        .collect::<Result<_>>()?;
+                    let mut children = Vec::new();
+                    self.expand_nodes(&element.children, &mut children)?;
+                    out.push(XmlNode::Element(Element {
+                        name: element.name.clone(),
+                        is_xacro: false,
+                        attributes,
+                        children,
+                    }));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn expand_xacro_element(&mut self, element: &Element, out: &mut Vec<XmlNode>) -> Result<()> {
+        match element.name.as_str() {
+            "property" => self.define_property(element),
+            "arg" => {
+                let name = element.required_attribute("name")?;
+                if !self.args.contains_key(name) {
+                    let default = element.attribute("default").ok_or_else(|| {
+                        anyhow!("xacro arg {name:?} has no default and was not given")
+                    })?;
+                    let value = self.eval_text(default)?.to_string();
+                    self.args.insert(name.to_string(), value);
+                }
+                Ok(())
+            }
+            "macro" => {
+                let name = element.required_attribute("name")?.to_string();
+                let params = parse_macro_params(element.attribute("params").unwrap_or(""))?;
+                let scope = self.scopes.last_mut().expect("at least the global scope");
+                scope.macros.insert(
+                    name.clone(),
+                    Rc::new(Macro {
+                        name,
+                        params,
+                        body: element.children.clone(),
+                        namespace: None,
+                    }),
+                );
+                Ok(())
+            }
+            "include" => self.include(element, out),
+            "if" | "unless" => {
+                let value = self.eval_text(element.required_attribute("value")?)?;
+                let condition = value
+                    .truthy()
+                    .map_err(|e| anyhow!("<xacro:{}>: {e}", element.name))?;
+                if condition == (element.name == "if") {
+                    self.expand_nodes(&element.children, out)?;
+                }
+                Ok(())
+            }
+            "insert_block" => {
+                let name = self
+                    .eval_text(element.required_attribute("name")?)?
+                    .to_string();
+                match self.lookup(&name) {
+                    Some(Property::Block { nodes, expanded }) => {
+                        let (nodes, expanded) = (nodes.clone(), *expanded);
+                        if expanded {
+                            out.extend(nodes);
+                            Ok(())
+                        } else {
+                            self.expand_nodes(&nodes, out)
+                        }
+                    }
+                    Some(Property::Value(_)) => bail!("xacro property {name:?} is not a block"),
+                    None => bail!("Undefined xacro block {name:?}"),
+                }
+            }
+            "call" => {
+                let name = self
+                    .eval_text(element.required_attribute("macro")?)?
+                    .to_string();
+                let mut call = element.clone();
+                call.attributes.retain(|(n, _)| n != "macro");
+                self.call_macro(&name, &call, out)
+            }
+            name => self.call_macro(name, element, out),
+        }
+    }
+
+    fn define_property(&mut self, element: &Element) -> Result<()> {
+        let name = element.required_attribute("name")?.to_string();
+        let property = if let Some(value) = element.attribute("value") {
+            Property::Value(self.eval_text(value)?)
+        } else if let Some(default) = element.attribute("default") {
+            if self.lookup(&name).is_some() {
+                return Ok(());
+            }
+            Property::Value(self.eval_text(default)?)
+        } else {
+            Property::Block {
+                nodes: element.children.clone(),
+                expanded: false,
+            }
+        };
+
+        let index = match element.attribute("scope") {
+            Some("global") => 0,
+            Some("parent") => self.scopes.len().saturating_sub(2),
+            _ => self.scopes.len() - 1,
+        };
+        self.scopes[index].properties.insert(name, property);
+        Ok(())
+    }
+
+    fn include(&mut self, element: &Element, out: &mut Vec<XmlNode>) -> Result<()> {
+        let filename = self
+            .eval_text(element.required_attribute("filename")?)?
+            .to_string();
+        let path = self
+            .dirs
+            .last()
+            .expect("at least the top-level dir")
+            .join(&filename);
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if self.includes.contains(&canonical) {
+            bail!("xacro include {path:?} includes itself (include cycle)");
+        }
+        let xml = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Failed to read xacro include {path:?}: {e}"))?;
+        let root = parse_xml(&xml).map_err(|e| anyhow!("In xacro include {path:?}: {e}"))?;
+
+        let namespace = element
+            .attribute("ns")
+            .map(|ns| self.eval_text(ns).map(|v| v.to_string()))
+            .transpose()?;
+        if namespace.is_some() {
+            self.scopes.push(Scope::default());
+        }
+        self.dirs
+            .push(path.parent().unwrap_or(Path::new(".")).to_path_buf());
+        self.includes.push(canonical);
+        let result = self.expand_nodes(&root.children, out);
+        self.includes.pop();
+        self.dirs.pop();
+
+        if let Some(ns) = namespace {
+            let included = self.scopes.pop().expect("pushed above");
+            let properties = Rc::new(included.properties);
+            let scope = self.scopes.last_mut().expect("at least the global scope");
+            for (name, property) in properties.iter() {
+                scope
+                    .properties
+                    .insert(format!("{ns}.{name}"), property.clone());
+            }
+            for (name, m) in included.macros {
+                let mut m = (*m).clone();
+                m.namespace.get_or_insert_with(|| properties.clone());
+                scope.macros.insert(format!("{ns}.{name}"), Rc::new(m));
+            }
+        }
+        result.map_err(|e| anyhow!("In xacro include {path:?}: {e}"))
+    }
+
+    fn call_macro(&mut self, name: &str, call: &Element, out: &mut Vec<XmlNode>) -> Result<()> {
+        let m = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.macros.get(name))
+            .cloned()
+            .ok_or_else(|| anyhow!("Unknown xacro macro or tag <xacro:{name}>"))?;
+        if self.depth >= MAX_MACRO_DEPTH {
+            bail!("xacro macro {name:?} nested too deep (infinite recursion?)");
+        }
+
+        for (attribute, _) in &call.attributes {
+            if !m.params.iter().any(|p| &p.name == attribute) {
+                bail!("Macro {name:?} has no parameter {attribute:?}");
+            }
+        }
+
+        // Block arguments are expanded where they are written, in the calling scope
+        let mut blocks = Vec::new();
+        for child in call.child_elements() {
+            let mut expanded = Vec::new();
+            self.expand_nodes(&[XmlNode::Element(child.clone())], &mut expanded)?;
+            blocks.extend(expanded);
+        }
+        let mut blocks = blocks.into_iter();
+
+        let mut scope = Scope::default();
+        for param in &m.params {
+            let property = match param.kind {
+                ParamKind::Value => {
+                    let value = match (call.attribute(&param.name), &param.default) {
+                        (Some(value), _) => self.eval_text(value)?,
+                        (None, default) if param.inherit => match self.lookup(&param.name) {
+                            Some(Property::Value(v)) => v.clone(),
+                            _ => match default {
+                                Some(d) => self.eval_text(d)?,
+                                None => bail!(
+                                    "Macro {:?}: no property {:?} to inherit",
+                                    m.name,
+                                    param.name
+                                ),
+                            },
+                        },
+                        (None, Some(default)) => self.eval_text(default)?,
+                        (None, None) => {
+                            bail!("Macro {:?}: missing parameter {:?}", m.name, param.name)
+                        }
+                    };
+                    Property::Value(value)
+                }
+                ParamKind::Block | ParamKind::BlockContents => {
+                    let block = blocks.next().ok_or_else(|| {
+                        anyhow!(
+                            "Macro {:?}: missing block parameter {:?}",
+                            m.name,
+                            param.name
+                        )
+                    })?;
+                    let nodes = match (param.kind, block) {
+                        (ParamKind::BlockContents, XmlNode::Element(e)) => e.children,
+                        (_, block) => vec![block],
+                    };
+                    Property::Block {
+                        nodes,
+                        expanded: true,
+                    }
+                }
+            };
+            scope.properties.insert(param.name.clone(), property);
+        }
+
+        let namespace = m.namespace.as_ref().map(|properties| Scope {
+            properties: (**properties).clone(),
+            macros: HashMap::new(),
+        });
+        let pushed = 1 + usize::from(namespace.is_some());
+        self.scopes.extend(namespace);
+        self.scopes.push(scope);
+        self.depth += 1;
+        let result = self.expand_nodes(&m.body, out);
+        self.depth -= 1;
+        self.scopes.truncate(self.scopes.len() - pushed);
+        result.map_err(|e| anyhow!("In xacro macro {:?}: {e}", m.name))
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Property> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.properties.get(name))
+    }
+
+    /// Substitute `$(...)` and evaluate `${...}` in an attribute value or text.
+    ///
+    /// Text that is a single `${}` expression keeps the expression's type.
+    fn eval_text(&self, text: &str) -> Result<Value> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find('$') {
+            literal.push_str(&rest[..start]);
+            let tail = &rest[start..];
+            if let Some(after) = tail.strip_prefix("$${") {
+                literal.push_str("${");
+                rest = after;
+            } else if tail.starts_with("${") || tail.starts_with("$(") {
+                let end = matching_close(tail)
+                    .ok_or_else(|| anyhow!("Unterminated {:?} in {text:?}", &tail[..2]))?;
+                let inner = &tail[2..end];
+                let value = if tail.starts_with("${") {
+                    self.eval_expression(inner)?
+                } else {
+                    Value::Str(self.substitute(inner)?)
+                };
+                if !literal.is_empty() {
+                    parts.push(Value::Str(std::mem::take(&mut literal)));
+                }
+                parts.push(value);
+                rest = &tail[end + 1..];
+            } else {
+                literal.push('$');
+                rest = &tail[1..];
+            }
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            parts.push(Value::Str(literal));
+        }
+
+        Ok(match parts.len() {
+            0 => Value::Str(String::new()),
+            1 => parts.pop().expect("one part"),
+            _ => Value::Str(parts.iter().map(|p| p.to_string()).collect()),
+        })
+    }
+
+    /// `$(command args)` substitution.
+    fn substitute(&self, inner: &str) -> Result<String> {
+        let inner = inner.trim();
+        let (command, argument) = inner.split_once(char::is_whitespace).unwrap_or((inner, ""));
+        let argument = argument.trim();
+        match command {
+            "arg" => self
+                .args
+                .get(argument)
+                .cloned()
+                .ok_or_else(|| anyhow!("Undefined xacro arg {argument:?}")),
+            "find" => {
+                let path = self.resolver.resolve(&format!("package://{argument}"))?;
+                Ok(path.display().to_string())
+            }
+            "env" => std::env::var(argument)
+                .map_err(|_| anyhow!("Environment variable {argument:?} is not set")),
+            "optenv" => {
+                let (var, default) = argument
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((argument, ""));
+                Ok(std::env::var(var).unwrap_or_else(|_| default.trim().to_string()))
+            }
+            "eval" => Ok(self.eval_expression(argument)?.to_string()),
+            "dirname" => Ok(self
+                .dirs
+                .last()
+                .expect("at least the top-level dir")
+                .display()
+                .to_string()),
+            _ => bail!("Unsupported substitution $({inner})"),
+        }
+    }
+
+    fn eval_expression(&self, source: &str) -> Result<Value> {
+        let tokens = tokenize(source).map_err(|e| anyhow!("In ${{{source}}}: {e}"))?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser
+            .parse_expression()
+            .and_then(|expr| match parser.peek() {
+                None => Ok(expr),
+                Some(token) => bail!("unexpected {token:?}"),
+            })
+            .map_err(|e| anyhow!("In ${{{source}}}: {e}"))?;
+        self.eval(&expr)
+            .map_err(|e| anyhow!("In ${{{source}}}: {e}"))
+    }
+
+    fn eval(&self, expr: &Expr) -> Result<Value> {
+        Ok(match expr {
+            Expr::Literal(value) => value.clone(),
+            Expr::Name(name) => self.variable(name)?,
+            Expr::Unary(op, operand) => {
+                let value = self.eval(operand)?;
+                match *op {
+                    "not" => Value::Bool(!value.truthy()?),
+                    "-" => Value::Num(-value.number()?),
+                    _ => Value::Num(value.number()?),
+                }
+            }
+            Expr::Binary("and", lhs, rhs) => {
+                let lhs = self.eval(lhs)?;
+                if lhs.truthy()? {
+                    self.eval(rhs)?
+                } else {
+                    lhs
+                }
+            }
+            Expr::Binary("or", lhs, rhs) => {
+                let lhs = self.eval(lhs)?;
+                if lhs.truthy()? {
+                    lhs
+                } else {
+                    self.eval(rhs)?
+                }
+            }
+            Expr::Binary(op, lhs, rhs) => binary(op, self.eval(lhs)?, self.eval(rhs)?)?,
+            Expr::Conditional(condition, then, otherwise) => {
+                if self.eval(condition)?.truthy()? {
+                    self.eval(then)?
+                } else {
+                    self.eval(otherwise)?
+                }
+            }
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|a| self.eval(a))
+                    .collect::<Result<Vec<_>>>()?;
+                call_function(name, &args)?
+            }
+        })
+    }
+
+    fn variable(&self, name: &str) -> Result<Value> {
+        match self.lookup(name) {
+            Some(Property::Value(Value::Str(s))) => return Ok(Value::from_literal(s)),
+            Some(Property::Value(value)) => return Ok(value.clone()),
+            Some(Property::Block { .. }) => bail!("{name:?} is a block, not a value"),
+            None => {}
+        }
+        let name = name.strip_prefix("math.").unwrap_or(name);
+        Ok(match name {
+            "pi" => Value::Num(std::f64::consts::PI),
+            "e" => Value::Num(std::f64::consts::E),
+            "inf" => Value::Num(f64::INFINITY),
+            "nan" => Value::Num(f64::NAN),
+            "True" | "true" => Value::Bool(true),
+            "False" | "false" => Value::Bool(false),
+            _ => bail!("undefined property {name:?}"),
+        })
+    }
+}
+
+/// Index of the bracket closing the `${` or `$(` at the start of `s`, skipping quoted strings.
+fn matching_close(s: &str) -> Option<usize> {
+    let (open, close) = if s.starts_with("${") {
+        ('{', '}')
+    } else {
+        ('(', ')')
+    };
+    let mut depth = 0;
+    let mut quote = None;
+    for (i, c) in s.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, c) if c == open => depth += 1,
+            (None, c) if c == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parse a macro's `params` attribute, e.g. `"name *origin **content x:=0 y:=^|1"`.
+fn parse_macro_params(params: &str) -> Result<Vec<MacroParam>> {
+    params
+        .split_whitespace()
+        .map(|param| {
+            let (kind, param) = if let Some(p) = param.strip_prefix("**") {
+                (ParamKind::BlockContents, p)
+            } else if let Some(p) = param.strip_prefix('*') {
+                (ParamKind::Block, p)
+            } else {
+                (ParamKind::Value, param)
+            };
+            let (name, default) = match param.split_once(":=") {
+                Some((name, default)) => (name, Some(default)),
+                None => (param, None),
+            };
+            if name.is_empty() {
+                bail!("Invalid macro parameter list {params:?}");
+            }
+            let (default, inherit) = match default.and_then(|d| d.strip_prefix('^')) {
+                Some(rest) => (rest.strip_prefix('|').map(str::to_string), true),
+                None => (default.map(str::to_string), false),
+            };
+            Ok(MacroParam {
+                name: name.to_string(),
+                kind,
+                default,
+                inherit,
+            })
+        })
+        .collect()
+}
+
+// -----------------------------------------------------------------------------
+// Expressions
+// -----------------------------------------------------------------------------
+
+/// A value of a `${}` expression, with Python-like semantics.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Num(f64),
+    Str(String),
+    Bool(bool),
+}
+
+impl Value {
+    /// Interpret a property's text the way xacro does: numbers and booleans become
+    /// typed values, `'quoted'` text loses its quotes.
+    fn from_literal(s: &str) -> Value {
+        let trimmed = s.trim();
+        if trimmed.len() >= 2 && trimmed.starts_with('\'') && trimmed.ends_with('\'') {
+            return Value::Str(trimmed[1..trimmed.len() - 1].to_string());
+        }
+        if trimmed.contains('_') {
+            return Value::Str(s.to_string());
+        }
+        if let Ok(n) = trimmed.parse::<f64>() {
+            return Value::Num(n);
+        }
+        match trimmed.to_ascii_lowercase().as_str() {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => Value::Str(s.to_string()),
+        }
+    }
+
+    fn number(&self) -> Result<f64> {
+        match self {
+            Value::Num(n) => Ok(*n),
+            Value::Bool(b) => Ok(f64::from(u8::from(*b))),
+            Value::Str(s) => match Value::from_literal(s) {
+                Value::Str(_) => bail!("{s:?} is not a number"),
+                value => value.number(),
+            },
+        }
+    }
+
+    fn truthy(&self) -> Result<bool> {
+        match self {
+            Value::Bool(b) => Ok(*b),
+            Value::Num(n) => Ok(*n != 0.0),
+            Value::Str(s) => match Value::from_literal(s) {
+                Value::Str(_) => bail!("{s:?} is not a boolean"),
+                value => value.truthy(),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Num(n) => write!(f, "{n}"),
+            Value::Str(s) => f.write_str(s),
+            Value::Bool(true) => f.write_str("True"),
+            Value::Bool(false) => f.write_str("False"),
+        }
+    }
+}
+
+fn binary(op: &str, lhs: Value, rhs: Value) -> Result<Value> {
+    if op == "+" {
+        if let (Value::Str(_), _) | (_, Value::Str(_)) = (&lhs, &rhs) {
+            // Numeric text still adds up as numbers, like an xacro property would
+            if let (Ok(a), Ok(b)) = (lhs.number(), rhs.number()) {
+                return Ok(Value::Num(a + b));
+            }
+            return Ok(Value::Str(format!("{lhs}{rhs}")));
+        }
+    }
+    if op == "==" || op == "!=" {
+        let equal = match (lhs.number(), rhs.number()) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => lhs.to_string() == rhs.to_string(),
+        };
+        return Ok(Value::Bool(equal == (op == "==")));
+    }
+
+    let (a, b) = (lhs.number()?, rhs.number()?);
+    Ok(match op {
+        "+" => Value::Num(a + b),
+        "-" => Value::Num(a - b),
+        "*" => Value::Num(a * b),
+        "/" if b == 0.0 => bail!("division by zero"),
+        "/" => Value::Num(a / b),
+        "//" if b == 0.0 => bail!("division by zero"),
+        "//" => Value::Num((a / b).floor()),
+        "%" => Value::Num(a - b * (a / b).floor()),
+        "**" => Value::Num(a.powf(b)),
+        "<" => Value::Bool(a < b),
+        "<=" => Value::Bool(a <= b),
+        ">" => Value::Bool(a > b),
+        ">=" => Value::Bool(a >= b),
+        _ => bail!("unsupported operator {op:?}"),
+    })
+}
+
+fn call_function(name: &str, args: &[Value]) -> Result<Value> {
+    let name = name.strip_prefix("math.").unwrap_or(name);
+    let numbers = || args.iter().map(Value::number).collect::<Result<Vec<_>>>();
+    let unary = |f: fn(f64) -> f64| -> Result<Value> {
+        match numbers()?.as_slice() {
+            [x] => Ok(Value::Num(f(*x))),
+            _ => bail!("{name}() takes one argument"),
+        }
+    };
+    match name {
+        "sin" => unary(f64::sin),
+        "cos" => unary(f64::cos),
+        "tan" => unary(f64::tan),
+        "asin" => unary(f64::asin),
+        "acos" => unary(f64::acos),
+        "atan" => unary(f64::atan),
+        "sqrt" => unary(f64::sqrt),
+        "exp" => unary(f64::exp),
+        "log" => unary(f64::ln),
+        "log10" => unary(f64::log10),
+        "abs" | "fabs" => unary(f64::abs),
+        "floor" => unary(f64::floor),
+        "ceil" => unary(f64::ceil),
+        "int" => unary(f64::trunc),
+        "float" => unary(|x| x),
+        "radians" => unary(f64::to_radians),
+        "degrees" => unary(f64::to_degrees),
+        "round" => unary(f64::round),
+        "atan2" | "pow" => match numbers()?.as_slice() {
+            [y, x] if name == "atan2" => Ok(Value::Num(y.atan2(*x))),
+            [x, y] => Ok(Value::Num(x.powf(*y))),
+            _ => bail!("{name}() takes two arguments"),
+        },
+        "min" | "max" => {
+            let numbers = numbers()?;
+            let pick = if name == "min" { f64::min } else { f64::max };
+            numbers
+                .into_iter()
+                .reduce(pick)
+                .map(Value::Num)
+                .ok_or_else(|| anyhow!("{name}() needs at least one argument"))
+        }
+        "str" => match args {
+            [value] => Ok(Value::Str(value.to_string())),
+            _ => bail!("str() takes one argument"),
+        },
+        "bool" => match args {
+            [value] => Ok(Value::Bool(value.truthy()?)),
+            _ => bail!("bool() takes one argument"),
+        },
+        _ => bail!("unknown function {name}()"),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Str(String),
+    Name(String),
+    Op(&'static str),
+}
+
+/// Operators, longest first so that e.g. `**` wins over `*`.
+const OPERATORS: [&str; 16] = [
+    "**", "//", "==", "!=", "<=", ">=", "+", "-", "*", "/", "%", "<", ">", "(", ")", ",",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = source.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit))
+        {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_ascii_alphanumeric()
+                    || chars[i] == '.'
+                    || ((chars[i] == '-' || chars[i] == '+') && matches!(chars[i - 1], 'e' | 'E')))
+            {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = text
+                .parse()
+                .map_err(|_| anyhow!("invalid number {text:?}"))?;
+            tokens.push(Token::Num(number));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+            {
+                i += 1;
+            }
+            tokens.push(Token::Name(chars[start..i].iter().collect()));
+        } else if c == '\'' || c == '"' {
+            let end = chars[i + 1..]
+                .iter()
+                .position(|&q| q == c)
+                .ok_or_else(|| anyhow!("unterminated string"))?;
+            tokens.push(Token::Str(chars[i + 1..i + 1 + end].iter().collect()));
+            i += end + 2;
+        } else {
+            let rest: String = chars[i..].iter().take(2).collect();
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(*op))
+                .ok_or_else(|| anyhow!("unexpected character {c:?}"))?;
+            tokens.push(Token::Op(op));
+            i += op.len();
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug)]
+enum Expr {
+    Literal(Value),
+    Name(String),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    /// `then if condition else otherwise`, stored as (condition, then, otherwise).
+    Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+/// Recursive-descent parser following Python's operator precedence.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat_op(&mut self, op: &str) -> bool {
+        if self.peek() == Some(&Token::Op(op_str(op))) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Name(name)) if name == keyword) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn parse_expression(&mut self) -> Result<Expr> {
+        let then = self.parse_or()?;
+        if !self.eat_keyword("if") {
+            return Ok(then);
+        }
+        let condition = self.parse_or()?;
+        if !self.eat_keyword("else") {
+            bail!("expected 'else'");
+        }
+        let otherwise = self.parse_expression()?;
+        Ok(Expr::Conditional(
+            Box::new(condition),
+            Box::new(then),
+            Box::new(otherwise),
+        ))
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_and()?;
+        while self.eat_keyword("or") {
+            lhs = Expr::Binary("or", Box::new(lhs), Box::new(self.parse_and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_not()?;
+        while self.eat_keyword("and") {
+            lhs = Expr::Binary("and", Box::new(lhs), Box::new(self.parse_not()?));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr> {
+        if self.eat_keyword("not") {
+            return Ok(Expr::Unary("not", Box::new(self.parse_not()?)));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr> {
+        let lhs = self.parse_binary_level(0)?;
+        for op in ["==", "!=", "<=", ">=", "<", ">"] {
+            if self.eat_op(op) {
+                let rhs = self.parse_binary_level(0)?;
+                return Ok(Expr::Binary(op_str(op), Box::new(lhs), Box::new(rhs)));
+            }
+        }
+        Ok(lhs)
+    }
+
+    /// Left-associative arithmetic: level 0 is `+ -`, level 1 is `* / // %`.
+    fn parse_binary_level(&mut self, level: usize) -> Result<Expr> {
+        const LEVELS: [&[&str]; 2] = [&["+", "-"], &["*", "/", "//", "%"]];
+        let operand = |parser: &mut Self| {
+            if level + 1 < LEVELS.len() {
+                parser.parse_binary_level(level + 1)
+            } else {
+                parser.parse_unary()
+            }
+        };
+        let mut lhs = operand(self)?;
+        'outer: loop {
+            for op in LEVELS[level] {
+                if self.eat_op(op) {
+                    lhs = Expr::Binary(op_str(op), Box::new(lhs), Box::new(operand(self)?));
+                    continue 'outer;
+                }
+            }
+            return Ok(lhs);
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        for op in ["-", "+"] {
+            if self.eat_op(op) {
+                return Ok(Expr::Unary(op_str(op), Box::new(self.parse_unary()?)));
+            }
+        }
+        let base = self.parse_primary()?;
+        if self.eat_op("**") {
+            // Right-associative, and binds tighter than a unary minus on its left
+            return Ok(Expr::Binary(
+                "**",
+                Box::new(base),
+                Box::new(self.parse_unary()?),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some(Token::Num(n)) => Ok(Expr::Literal(Value::Num(n))),
+            Some(Token::Str(s)) => Ok(Expr::Literal(Value::Str(s))),
+            Some(Token::Op("(")) => {
+                let expr = self.parse_expression()?;
+                if !self.eat_op(")") {
+                    bail!("expected ')'");
+                }
+                Ok(expr)
+            }
+            Some(Token::Name(name)) => {
+                if !self.eat_op("(") {
+                    return Ok(Expr::Name(name));
+                }
+                let mut args = Vec::new();
+                if !self.eat_op(")") {
+                    loop {
+                        args.push(self.parse_expression()?);
+                        if self.eat_op(")") {
+                            break;
+                        }
+                        if !self.eat_op(",") {
+                            bail!("expected ',' or ')' in call to {name}()");
+                        }
+                    }
+                }
+                Ok(Expr::Call(name, args))
+            }
+            Some(token) => bail!("unexpected {token:?}"),
+            None => bail!("unexpected end of expression"),
+        }
+    }
+}
+
+/// The `'static` spelling of an operator.
+fn op_str(op: &str) -> &'static str {
+    OPERATORS.iter().find(|o| **o == op).copied().unwrap_or("?")
+}
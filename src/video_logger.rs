@@ -0,0 +1,214 @@
+// video_logger.rs
+
+use anyhow::{anyhow, Result};
+use krec::KRec;
+use log::{info, warn};
+use rerun::RecordingStream;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::utils::krec_utils::{has_inference_steps, set_frame_timelines};
+use crate::utils::video_utils::{
+    frame_at_rate, frame_at_time, parse_frame_rate, JpegFrames, VideoPosition, VideoSync,
+};
+
+/// Entity path the camera footage is logged under.
+const CAMERA_ENTITY_PATH: &str = "camera";
+
+/// Log the camera footage of a KREC video on the same timelines as the joint transforms.
+///
+/// MP4 files are logged as an `AssetVideo` that the viewer decodes itself; other
+/// containers (e.g. the `.krec.mkv` files written by krec) are decoded to JPEG frames
+/// with ffmpeg and logged as images.
+///
+/// Each KREC frame shows the video frame with its recorded `video_frame_number`, or the one
+/// on screen at its `video_timestamp` when the recorder left the frame numbers out.
+pub fn parse_and_log_krec_video(
+    video_path: &str,
+    krec: &KRec,
+    rec: &RecordingStream,
+) -> Result<()> {
+    let is_mp4 = Path::new(video_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|s| s.eq_ignore_ascii_case("mp4"))
+        .unwrap_or(false);
+
+    if is_mp4 {
+        log_video_asset(video_path, krec, rec)
+    } else {
+        log_decoded_frames(video_path, krec, rec)
+    }
+}
+
+/// Log an MP4 as an `AssetVideo` and reference one of its frames for every KREC frame.
+fn log_video_asset(video_path: &str, krec: &KRec, rec: &RecordingStream) -> Result<()> {
+    let video_asset = rerun::AssetVideo::from_file_path(video_path)?;
+    rec.log_static(CAMERA_ENTITY_PATH, &video_asset)?;
+
+    let frame_timestamps_ns = video_asset
+        .read_frame_timestamps_ns()
+        .map_err(|e| anyhow!("Failed to read frame timestamps of {video_path:?}: {e}"))?;
+    info!(
+        "Logging {} video frames from {}",
+        frame_timestamps_ns.len(),
+        video_path
+    );
+
+    let sync = VideoSync::detect(&krec.frames);
+    let first_video_timestamp = krec.frames.first().map_or(0, |f| f.video_timestamp);
+    let log_inference_steps = has_inference_steps(krec);
+
+    for (frame_idx, frame) in krec.frames.iter().enumerate() {
+        let video_idx = match sync.position(frame_idx, frame, first_video_timestamp) {
+            VideoPosition::Frame(idx) => Some(idx),
+            VideoPosition::Time(time_ns) => frame_at_time(&frame_timestamps_ns, time_ns as i64),
+        };
+        let Some(timestamp_ns) = video_idx.and_then(|idx| frame_timestamps_ns.get(idx)) else {
+            continue;
+        };
+
+        set_frame_timelines(
+            rec,
+            krec.header.start_timestamp,
+            frame_idx,
+            frame,
+            log_inference_steps,
+        );
+        rec.log(
+            CAMERA_ENTITY_PATH,
+            &rerun::archetypes::VideoFrameReference::new(
+                rerun::components::VideoTimestamp::from_nanoseconds(*timestamp_ns),
+            ),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Decode the video frames shown with KREC frames to JPEGs with ffmpeg, streamed through
+/// a pipe, and log each one for its KREC frames.
+fn log_decoded_frames(video_path: &str, krec: &KRec, rec: &RecordingStream) -> Result<()> {
+    let sync = VideoSync::detect(&krec.frames);
+    let fps = probe_frame_rate(video_path)?;
+    let first_video_timestamp = krec.frames.first().map_or(0, |f| f.video_timestamp);
+
+    // KREC frames shown with each video frame
+    let mut krec_frames_by_video_frame: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (frame_idx, frame) in krec.frames.iter().enumerate() {
+        let video_idx = match sync.position(frame_idx, frame, first_video_timestamp) {
+            VideoPosition::Frame(idx) => idx,
+            VideoPosition::Time(time_ns) => frame_at_rate(time_ns, fps),
+        };
+        krec_frames_by_video_frame
+            .entry(video_idx)
+            .or_default()
+            .push(frame_idx);
+    }
+    let Some(&last_video_idx) = krec_frames_by_video_frame.keys().next_back() else {
+        return Ok(());
+    };
+
+    info!("Decoding video frames of {} with ffmpeg", video_path);
+    // Decoding stops after the last frame needed
+    let mut ffmpeg = Command::new("ffmpeg")
+        .args([
+            "-loglevel",
+            "error",
+            "-i",
+            video_path,
+            "-map",
+            "0:v:0",
+            "-vsync",
+            "0",
+            "-frames:v",
+            &(last_video_idx + 1).to_string(),
+            "-f",
+            "image2pipe",
+            "-c:v",
+            "mjpeg",
+            "-q:v",
+            "3",
+            "-",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Failed to run ffmpeg (is it installed?): {e}"))?;
+    let stdout = ffmpeg
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("Failed to read the output of ffmpeg"))?;
+
+    let log_inference_steps = has_inference_steps(krec);
+    let mut frames_logged = 0;
+
+    for (video_idx, jpeg) in JpegFrames::new(stdout).enumerate() {
+        let jpeg =
+            jpeg.map_err(|e| anyhow!("Failed to read frames decoded from {video_path:?}: {e}"))?;
+        let Some(frame_idxs) = krec_frames_by_video_frame.get(&video_idx) else {
+            continue;
+        };
+        for &frame_idx in frame_idxs {
+            set_frame_timelines(
+                rec,
+                krec.header.start_timestamp,
+                frame_idx,
+                &krec.frames[frame_idx],
+                log_inference_steps,
+            );
+            rec.log(
+                CAMERA_ENTITY_PATH,
+                &rerun::archetypes::EncodedImage::from_file_contents(jpeg.clone()),
+            )?;
+            frames_logged += 1;
+        }
+    }
+
+    let output = ffmpeg.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "ffmpeg failed to decode {video_path:?}: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    if frames_logged == 0 {
+        warn!("No video frames matched the KREC frames of {}", video_path);
+    } else {
+        info!("Logged {} camera frames", frames_logged);
+    }
+
+    Ok(())
+}
+
+/// Frame rate of the first video stream of a file, read with ffprobe.
+fn probe_frame_rate(video_path: &str) -> Result<f64> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "stream=avg_frame_rate,r_frame_rate",
+            "-of",
+            "default=noprint_wrappers=1:nokey=1",
+            video_path,
+        ])
+        .output()
+        .map_err(|e| anyhow!("Failed to run ffprobe (is it installed?): {e}"))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "ffprobe failed to read {video_path:?}: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let rates = String::from_utf8_lossy(&output.stdout);
+    rates
+        .lines()
+        .find_map(parse_frame_rate)
+        .ok_or_else(|| anyhow!("No frame rate found for the video of {video_path:?}"))
+}
@@ -0,0 +1,127 @@
+// tests/test_base_pose_utils.rs
+
+use std::f64::consts::FRAC_PI_2;
+
+use krecviz::utils::base_pose_utils::{BasePoseTrack, LegOdometry, PoseKey};
+use nalgebra as na;
+
+const EPSILON: f64 = 1e-9;
+
+fn assert_vector(actual: na::Vector3<f64>, expected: [f64; 3]) {
+    assert!(
+        (actual - na::Vector3::from(expected)).norm() < EPSILON,
+        "expected {expected:?}, got {actual:?}"
+    );
+}
+
+/// Test frame-indexed poses with quaternions are interpolated and held at the ends.
+#[test]
+fn test_frame_track_with_quaternions() {
+    let csv = "frame,x,y,z,qx,qy,qz,qw\n\
+               0,0,0,1,0,0,0,1\n\
+               10,1,0,1,0,0,0.7071067811865476,0.7071067811865476\n";
+    let track = BasePoseTrack::from_csv_str(csv).unwrap();
+    assert_eq!(track.key(), PoseKey::Frame);
+
+    let halfway = track.pose_at(5.0);
+    assert_vector(halfway.position, [0.5, 0.0, 1.0]);
+    let yaw = halfway.orientation.unwrap().euler_angles().2;
+    assert!((yaw - FRAC_PI_2 / 2.0).abs() < 1e-6, "yaw {yaw}");
+
+    assert_vector(track.pose_at(-3.0).position, [0.0, 0.0, 1.0]);
+    assert_vector(track.pose_at(42.0).position, [1.0, 0.0, 1.0]);
+}
+
+/// Test time-indexed poses with roll/pitch/yaw, comments, extra columns and unsorted rows.
+#[test]
+fn test_time_track_with_rpy() {
+    let csv = "# exported from mocap\n\
+               \n\
+               Time, X, Y, Z, Roll, Pitch, Yaw, Marker_Error\n\
+               0.2, 2, 0, 0, 0, 0, 1.0, 0.001\n\
+               0.0, 0, 0, 0, 0, 0, 0.0, 0.002\n";
+    let track = BasePoseTrack::from_csv_str(csv).unwrap();
+    assert_eq!(track.key(), PoseKey::Time);
+
+    let pose = track.pose_at(0.05);
+    assert_vector(pose.position, [0.5, 0.0, 0.0]);
+    let yaw = pose.orientation.unwrap().euler_angles().2;
+    assert!((yaw - 0.25).abs() < 1e-6, "yaw {yaw}");
+}
+
+/// Test position-only files have no orientation.
+#[test]
+fn test_position_only_track() {
+    let track = BasePoseTrack::from_csv_str("frame,x,y,z\n0,1,2,3\n").unwrap();
+    let pose = track.pose_at(0.0);
+    assert_vector(pose.position, [1.0, 2.0, 3.0]);
+    assert!(pose.orientation.is_none());
+}
+
+/// Test malformed files are rejected with the offending column or line.
+#[test]
+fn test_invalid_tracks() {
+    let err = BasePoseTrack::from_csv_str("frame,x,y\n0,1,2\n").unwrap_err();
+    assert!(err.to_string().contains("`z`"), "{err}");
+
+    let err = BasePoseTrack::from_csv_str("x,y,z\n0,1,2\n").unwrap_err();
+    assert!(err.to_string().contains("`frame` or `time`"), "{err}");
+
+    let err = BasePoseTrack::from_csv_str("frame,x,y,z\n0,0,0,0\n1,0,oops,0\n").unwrap_err();
+    assert!(err.to_string().contains("line 3"), "{err}");
+
+    assert!(BasePoseTrack::from_csv_str("frame,x,y,z\n").is_err());
+}
+
+/// Test the base walks forward as the planted foot moves back, and the stance switches
+/// to the other foot once it gets lower.
+#[test]
+fn test_leg_odometry_walk() {
+    let identity = na::UnitQuaternion::identity();
+    let feet = |left: [f64; 3], right: [f64; 3]| {
+        vec![
+            ("foot_l".to_string(), na::Vector3::from(left)),
+            ("foot_r".to_string(), na::Vector3::from(right)),
+        ]
+    };
+    let mut odometry = LegOdometry::new();
+
+    // Standing: the feet are put on the ground
+    let base = odometry.update(&identity, &feet([0.0, 0.1, -1.0], [0.0, -0.1, -1.0]));
+    assert_vector(base, [0.0, 0.0, 1.0]);
+    assert_eq!(odometry.stance_link(), Some("foot_l"));
+
+    // The left foot pushes back while the right one swings forward
+    let base = odometry.update(&identity, &feet([-0.2, 0.1, -1.0], [0.2, -0.1, -0.9]));
+    assert_vector(base, [0.2, 0.0, 1.0]);
+    assert_eq!(odometry.stance_link(), Some("foot_l"));
+
+    // The right foot lands while the left one lifts off
+    let base = odometry.update(&identity, &feet([-0.2, 0.1, -0.95], [0.2, -0.1, -1.0]));
+    assert_vector(base, [0.2, 0.0, 1.0]);
+    assert_eq!(odometry.stance_link(), Some("foot_r"));
+
+    // The body moves over the right foot
+    let base = odometry.update(&identity, &feet([0.0, 0.1, -0.9], [0.0, -0.1, -1.0]));
+    assert_vector(base, [0.4, 0.0, 1.0]);
+}
+
+/// Test stance positions are rotated by the base orientation.
+#[test]
+fn test_leg_odometry_orientation() {
+    let yawed = na::UnitQuaternion::from_euler_angles(0.0, 0.0, FRAC_PI_2);
+    let mut odometry = LegOdometry::new();
+    odometry.update(
+        &yawed,
+        &[("foot".to_string(), na::Vector3::new(0.0, 0.0, -1.0))],
+    );
+
+    // With the foot planted, a step along the base's x moves the base along world y
+    let base = odometry.update(
+        &yawed,
+        &[("foot".to_string(), na::Vector3::new(-0.3, 0.0, -1.0))],
+    );
+    assert_vector(base, [0.0, 0.3, 1.0]);
+
+    assert_vector(odometry.update(&yawed, &[]), [0.0, 0.3, 1.0]);
+}
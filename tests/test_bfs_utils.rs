@@ -2,7 +2,11 @@
 
 #[cfg(test)]
 mod test_urdf_bfs_utils {
-    use krecviz::utils::urdf_bfs_utils::{build_link_bfs_map, find_root_link_name};
+    use krecviz::utils::urdf_bfs_utils::{
+        build_joint_info_map, build_link_bfs_map, compute_link_poses, find_root_link_name,
+        join_entity_path, resolve_mimic_positions, validate_mimic_joints,
+    };
+    use std::collections::HashMap;
     use std::path::PathBuf;
     use urdf_rs::{read_file, read_from_string};
 
@@ -29,7 +33,7 @@ mod test_urdf_bfs_utils {
         assert_eq!(root_name, "base_link");
 
         // 2) Build BFS data
-        let (bfs_map, bfs_order) = build_link_bfs_map(&robot);
+        let (bfs_map, bfs_order) = build_link_bfs_map(&robot, &root_name);
 
         // Should have 2 entries: 'base_link' and 'link_1'
         assert_eq!(bfs_map.len(), 2, "We should have BFS data for two links");
@@ -73,7 +77,7 @@ mod test_urdf_bfs_utils {
         assert_eq!(root, "base", "Root link should be 'base'.");
 
         // 3) Build BFS map & BFS order
-        let (bfs_map, bfs_order) = build_link_bfs_map(&robot);
+        let (bfs_map, bfs_order) = build_link_bfs_map(&robot, &root);
 
         // We expect these four links in BFS order:
         //    base -> Part_1 -> Part_1_2 -> Part_1_3
@@ -139,4 +143,187 @@ mod test_urdf_bfs_utils {
 
         // If we reach here, BFS & transforms match expected for this manual URDF
     }
+
+    #[test]
+    fn test_joint_info_axis_and_type() {
+        let urdf_str = r#"
+        <robot name="TestRobot">
+          <link name="base_link"/>
+          <link name="arm"/>
+          <link name="slider"/>
+          <link name="tool"/>
+          <joint name="shoulder" type="revolute">
+            <origin xyz="0 0 1" rpy="0 0 0"/>
+            <parent link="base_link"/>
+            <child link="arm"/>
+            <axis xyz="1 0 0"/>
+            <limit lower="-1.0" upper="1.0" effort="10" velocity="2"/>
+          </joint>
+          <joint name="rail" type="prismatic">
+            <origin xyz="0 0 0" rpy="0 0 1.5707963"/>
+            <parent link="arm"/>
+            <child link="slider"/>
+            <axis xyz="1 0 0"/>
+            <limit lower="0" upper="0.5" effort="10" velocity="1"/>
+          </joint>
+          <joint name="mount" type="fixed">
+            <origin xyz="0.1 0 0" rpy="0 0 0"/>
+            <parent link="slider"/>
+            <child link="tool"/>
+          </joint>
+        </robot>
+        "#;
+        let robot = read_from_string(urdf_str).expect("Failed to parse URDF string");
+        let joint_info = build_joint_info_map(&robot, "base_link");
+
+        // Revolute about +X: 90° maps the +Y column of the rotation onto +Z
+        let shoulder = &joint_info["shoulder"];
+        assert!(shoulder.is_animated());
+        assert_eq!(shoulder.axis, [1.0, 0.0, 0.0]);
+        assert!((shoulder.limit.upper - 1.0).abs() < 1e-9);
+        let tf = shoulder.local_transform(std::f64::consts::FRAC_PI_2);
+        // Row-major: column 1 is (tf[1], tf[5], tf[9])
+        assert!(tf[1].abs() < 1e-6 && tf[5].abs() < 1e-6 && (tf[9] - 1.0).abs() < 1e-6);
+        assert!(
+            (tf[11] - 1.0).abs() < 1e-6,
+            "Origin Z offset should be kept"
+        );
+
+        // Prismatic along +X, but the joint frame is yawed 90°, so it slides along parent +Y
+        let rail = &joint_info["rail"];
+        assert!(rail.is_animated());
+        let tf = rail.local_transform(0.25);
+        assert!(
+            tf[3].abs() < 1e-6,
+            "X translation should stay 0, got {}",
+            tf[3]
+        );
+        assert!((tf[7] - 0.25).abs() < 1e-6, "Y translation should be 0.25");
+
+        // Fixed joints never move
+        let mount = &joint_info["mount"];
+        assert!(!mount.is_animated());
+        let tf = mount.local_transform(123.0);
+        assert!((tf[3] - 0.1).abs() < 1e-6);
+        assert!((tf[0] - 1.0).abs() < 1e-6);
+    }
+
+    /// Build a chain base -> a -> b -> c of revolute joints with the given `<mimic>` tags.
+    fn mimic_chain_urdf(mimic_a: &str, mimic_b: &str, mimic_c: &str) -> String {
+        format!(
+            r#"
+        <robot name="Gripper">
+          <link name="base"/>
+          <link name="a"/>
+          <link name="b"/>
+          <link name="c"/>
+          <joint name="joint_a" type="revolute">
+            <parent link="base"/>
+            <child link="a"/>
+            <axis xyz="0 0 1"/>
+            <limit lower="-1" upper="1" effort="1" velocity="1"/>
+            {mimic_a}
+          </joint>
+          <joint name="joint_b" type="revolute">
+            <parent link="a"/>
+            <child link="b"/>
+            <axis xyz="0 0 1"/>
+            <limit lower="-1" upper="1" effort="1" velocity="1"/>
+            {mimic_b}
+          </joint>
+          <joint name="joint_c" type="revolute">
+            <parent link="b"/>
+            <child link="c"/>
+            <axis xyz="0 0 1"/>
+            <limit lower="-1" upper="1" effort="1" velocity="1"/>
+            {mimic_c}
+          </joint>
+        </robot>
+        "#
+        )
+    }
+
+    #[test]
+    fn test_resolve_chained_mimic_joints() {
+        let urdf_str = mimic_chain_urdf(
+            "",
+            r#"<mimic joint="joint_a" multiplier="2.0" offset="0.1"/>"#,
+            r#"<mimic joint="joint_b" multiplier="-1.0"/>"#,
+        );
+        let robot = read_from_string(&urdf_str).expect("Failed to parse URDF string");
+        let joint_info = build_joint_info_map(&robot, "base");
+        validate_mimic_joints(&joint_info).expect("Valid mimic chain rejected");
+
+        let positions = HashMap::from([("joint_a".to_string(), 0.3)]);
+        let resolved = resolve_mimic_positions(&joint_info, &positions);
+
+        assert_eq!(
+            resolved.len(),
+            2,
+            "Only the two followers should be resolved"
+        );
+        // joint_b = 2.0 * 0.3 + 0.1, joint_c = -1.0 * joint_b
+        assert!((resolved["joint_b"] - 0.7).abs() < 1e-9);
+        assert!((resolved["joint_c"] + 0.7).abs() < 1e-9);
+
+        // Without a leader position nothing can be derived
+        assert!(resolve_mimic_positions(&joint_info, &HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_mimic_cycle_is_rejected() {
+        let urdf_str = mimic_chain_urdf(
+            r#"<mimic joint="joint_c"/>"#,
+            r#"<mimic joint="joint_a"/>"#,
+            r#"<mimic joint="joint_b"/>"#,
+        );
+        let robot = read_from_string(&urdf_str).expect("Failed to parse URDF string");
+        let joint_info = build_joint_info_map(&robot, "base");
+
+        let err = validate_mimic_joints(&joint_info)
+            .expect_err("Mimic cycle should be rejected")
+            .to_string();
+        assert!(err.contains("cycle"), "{err}");
+    }
+
+    #[test]
+    fn test_mimic_unknown_leader_is_rejected() {
+        let urdf_str = mimic_chain_urdf("", r#"<mimic joint="does_not_exist"/>"#, "");
+        let robot = read_from_string(&urdf_str).expect("Failed to parse URDF string");
+        let joint_info = build_joint_info_map(&robot, "base");
+
+        let err = validate_mimic_joints(&joint_info)
+            .expect_err("Unknown mimic leader should be rejected")
+            .to_string();
+        assert!(err.contains("does_not_exist"), "{err}");
+    }
+
+    #[test]
+    fn test_link_poses_follow_joint_positions() {
+        // Each link is 1 unit along its parent's +X, every joint turns about +Z
+        let urdf_str = mimic_chain_urdf("", "", "").replace(
+            "<parent link=\"a\"/>",
+            "<origin xyz=\"1 0 0\"/><parent link=\"a\"/>",
+        );
+        let robot = read_from_string(&urdf_str).expect("Failed to parse URDF string");
+        let joint_info = build_joint_info_map(&robot, "base");
+        let (_, bfs_order) = build_link_bfs_map(&robot, "base");
+
+        // joint_b is missing and stays at zero
+        let positions = HashMap::from([("joint_a".to_string(), std::f64::consts::FRAC_PI_2)]);
+        let poses = compute_link_poses(&bfs_order, &joint_info, &positions);
+
+        assert_eq!(poses.len(), 4);
+        assert_eq!(poses["base"].translation.vector.norm(), 0.0);
+        // Link b hangs 1 unit along a's +X, which joint_a turned onto the world's +Y
+        let b = poses["b"].translation.vector;
+        assert!(b.x.abs() < 1e-9 && (b.y - 1.0).abs() < 1e-9, "{b:?}");
+    }
+
+    #[test]
+    fn test_join_entity_path() {
+        assert_eq!(join_entity_path("", "base/arm"), "base/arm");
+        assert_eq!(join_entity_path("ghost", "base/arm"), "ghost/base/arm");
+        assert_eq!(join_entity_path("ghost/", "base"), "ghost/base");
+    }
 }
@@ -3,9 +3,13 @@
 use krecviz::utils::geometry_utils::{
     compute_vertex_normals, apply_4x4_to_mesh3d, float_rgba_to_u8, 
     create_box_mesh, create_cylinder_mesh, create_sphere_mesh,
+    apply_scale_to_mesh3d, load_mesh_file, MeshFormat,
 };
 use krecviz::utils::spatial_transform_utils;
 
+use std::fs;
+use std::path::Path;
+
 use rerun::{
     archetypes::Mesh3D,
     components::{Position3D, TriangleIndices, Vector3D},
@@ -249,6 +253,75 @@ fn test_create_sphere_properties() {
     }
 }
 
+/// Test a millimetre mesh scaled by 0.001 ends up in metres.
+#[test]
+fn test_apply_scale_to_mesh3d_uniform() {
+    let mut mesh = create_box_mesh([1000.0, 2000.0, 3000.0]);
+    apply_scale_to_mesh3d(&mut mesh, [0.001, 0.001, 0.001]);
+
+    for p_rerun in &mesh.vertex_positions {
+        let p_arr = p_rerun.0.0;
+        assert!((p_arr[0].abs() - 0.5).abs() < 1e-4, "X not scaled: {}", p_arr[0]);
+        assert!((p_arr[1].abs() - 1.0).abs() < 1e-4, "Y not scaled: {}", p_arr[1]);
+        assert!((p_arr[2].abs() - 1.5).abs() < 1e-4, "Z not scaled: {}", p_arr[2]);
+    }
+}
+
+/// Test normals use the inverse-transpose of a non-uniform scale and stay unit length.
+#[test]
+fn test_apply_scale_to_mesh3d_non_uniform_normals() {
+    let s = std::f32::consts::FRAC_1_SQRT_2;
+    let mut mesh = Mesh3D::new(vec![Position3D::from([1.0, 1.0, 0.0])])
+        .with_vertex_normals(vec![Vector3D::from([s, s, 0.0])]);
+
+    apply_scale_to_mesh3d(&mut mesh, [2.0, 1.0, 1.0]);
+
+    assert_f32_slice_approx_eq(&mesh.vertex_positions[0].0.0, &[2.0, 1.0, 0.0], "Scaled position mismatch");
+    // The surface x + y = 2 becomes x / 2 + y = 2, with normal (0.5, 1, 0) / |(0.5, 1, 0)|
+    let len = (0.25f32 + 1.0).sqrt();
+    assert_f32_slice_approx_eq(
+        &mesh.vertex_normals.as_ref().unwrap()[0].0.0,
+        &[0.5 / len, 1.0 / len, 0.0],
+        "Scaled normal mismatch",
+    );
+}
+
+/// Test a mirroring scale flips the triangle winding so the faces keep pointing outwards.
+#[test]
+fn test_apply_scale_to_mesh3d_negative_flips_winding() {
+    let positions = vec![
+        Position3D::from([0.0, 0.0, 0.0]),
+        Position3D::from([1.0, 0.0, 0.0]),
+        Position3D::from([0.0, 1.0, 0.0]),
+    ];
+    let mut mesh = Mesh3D::new(positions).with_triangle_indices(vec![TriangleIndices::from([0, 1, 2])]);
+    compute_vertex_normals(&mut mesh);
+
+    apply_scale_to_mesh3d(&mut mesh, [-1.0, 1.0, 1.0]);
+
+    assert_eq!(mesh.triangle_indices.as_ref().unwrap()[0].0.0, [0, 2, 1]);
+    assert_f32_slice_approx_eq(&mesh.vertex_positions[1].0.0, &[-1.0, 0.0, 0.0], "Mirrored position mismatch");
+
+    // The stored normals must agree with the normals implied by the new winding
+    let scaled_normals = mesh.vertex_normals.clone().unwrap();
+    compute_vertex_normals(&mut mesh);
+    let winding_normals = mesh.vertex_normals.as_ref().unwrap();
+    for (scaled, winding) in scaled_normals.iter().zip(winding_normals.iter()) {
+        assert_f32_slice_approx_eq(&scaled.0.0, &winding.0.0, "Normal disagrees with winding");
+        assert_f32_slice_approx_eq(&scaled.0.0, &[0.0, 0.0, 1.0], "Mirrored normal mismatch");
+    }
+}
+
+/// Test a unit scale leaves the mesh untouched.
+#[test]
+fn test_apply_scale_to_mesh3d_identity() {
+    let mut mesh = create_sphere_mesh(1.0);
+    let original = mesh.clone();
+    apply_scale_to_mesh3d(&mut mesh, [1.0, 1.0, 1.0]);
+    assert_eq!(mesh.vertex_positions, original.vertex_positions);
+    assert_eq!(mesh.triangle_indices, original.triangle_indices);
+}
+
 /// Test correct conversion to 8-bit RGBA values, including clamping.
 #[test]
 fn test_float_rgba_to_u8_conversion() {
@@ -257,4 +330,217 @@ fn test_float_rgba_to_u8_conversion() {
     assert_eq!(float_rgba_to_u8([0.5, 0.5, 0.5, 0.5]), [127, 127, 127, 127]);
     assert_eq!(float_rgba_to_u8([0.2, 0.4, 0.6, 0.8]), [51, 102, 153, 204]);
     assert_eq!(float_rgba_to_u8([-0.1, 1.1, 0.5, 0.0]), [0, 255, 127, 0]);
-}
\ No newline at end of file
+}
+
+const QUAD_OBJ: &str = "mtllib quad.mtl
+o quad
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+usemtl red
+f 1/1/1 2/2/1 3/3/1 4/4/1
+";
+
+const QUAD_MTL: &str = "newmtl red
+Kd 1.0 0.0 0.0
+d 0.5
+";
+
+/// A green quad in millimetres, translated 500 mm up by its scene node.
+const QUAD_DAE: &str = r##"<?xml version="1.0" encoding="utf-8"?>
+<COLLADA xmlns="http://www.collada.org/2005/11/COLLADASchema" version="1.4.1">
+  <asset><unit name="millimeter" meter="0.001"/><up_axis>Z_UP</up_axis></asset>
+  <library_effects>
+    <effect id="green-effect"><profile_COMMON><technique sid="common"><lambert>
+      <diffuse><color sid="diffuse">0 1 0 1</color></diffuse>
+    </lambert></technique></profile_COMMON></effect>
+  </library_effects>
+  <library_materials>
+    <material id="green-material" name="green"><instance_effect url="#green-effect"/></material>
+  </library_materials>
+  <library_geometries>
+    <geometry id="quad-mesh"><mesh>
+      <source id="quad-positions">
+        <float_array id="quad-positions-array" count="12">0 0 0 1000 0 0 1000 1000 0 0 1000 0</float_array>
+        <technique_common><accessor source="#quad-positions-array" count="4" stride="3"/></technique_common>
+      </source>
+      <vertices id="quad-vertices"><input semantic="POSITION" source="#quad-positions"/></vertices>
+      <polylist material="green-symbol" count="1">
+        <input semantic="VERTEX" source="#quad-vertices" offset="0"/>
+        <vcount>4</vcount>
+        <p>0 1 2 3</p>
+      </polylist>
+    </mesh></geometry>
+  </library_geometries>
+  <library_visual_scenes>
+    <visual_scene id="Scene">
+      <node id="quad"><translate>0 0 500</translate>
+        <instance_geometry url="#quad-mesh"><bind_material><technique_common>
+          <instance_material symbol="green-symbol" target="#green-material"/>
+        </technique_common></bind_material></instance_geometry>
+      </node>
+    </visual_scene>
+  </library_visual_scenes>
+  <scene><instance_visual_scene url="#Scene"/></scene>
+</COLLADA>"##;
+
+/// A single triangle with a blue base color, translated 2 m up by its node.
+const TRIANGLE_GLTF: &str = "{\"asset\": {\"version\": \"2.0\"}, \"scene\": 0, \"scenes\": [{\"nodes\": [0]}], \"nodes\": [{\"mesh\": 0, \"translation\": [0, 0, 2]}], \"meshes\": [{\"primitives\": [{\"attributes\": {\"POSITION\": 0}, \"indices\": 1, \"material\": 0}]}], \"materials\": [{\"pbrMetallicRoughness\": {\"baseColorFactor\": [0, 0, 1, 1]}}], \"buffers\": [{\"byteLength\": 44, \"uri\": \"data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAABAAIAAAA=\"}], \"bufferViews\": [{\"buffer\": 0, \"byteOffset\": 0, \"byteLength\": 36}, {\"buffer\": 0, \"byteOffset\": 36, \"byteLength\": 6}], \"accessors\": [{\"bufferView\": 0, \"componentType\": 5126, \"count\": 3, \"type\": \"VEC3\", \"min\": [0, 0, 0], \"max\": [1, 1, 0]}, {\"bufferView\": 1, \"componentType\": 5123, \"count\": 3, \"type\": \"SCALAR\"}]}";
+
+const TRIANGLE_ASCII_STL: &str = "solid tri
+facet normal 0 0 1
+  outer loop
+    vertex 0 0 0
+    vertex 1 0 0
+    vertex 0 1 0
+  endloop
+endfacet
+endsolid tri
+";
+
+fn positions(mesh: &Mesh3D) -> Vec<[f32; 3]> {
+    mesh.vertex_positions.iter().map(|p| p.0.0).collect()
+}
+
+/// Test formats are recognized by extension and by content.
+#[test]
+fn test_mesh_format_detection() {
+    assert_eq!(MeshFormat::from_path(Path::new("a/b.STL")), Some(MeshFormat::Stl));
+    assert_eq!(MeshFormat::from_path(Path::new("b.dae")), Some(MeshFormat::Dae));
+    assert_eq!(MeshFormat::from_path(Path::new("b.glb")), Some(MeshFormat::Glb));
+    assert_eq!(MeshFormat::from_path(Path::new("b.mesh")), None);
+
+    assert_eq!(MeshFormat::from_magic(QUAD_OBJ.as_bytes()), Some(MeshFormat::Obj));
+    assert_eq!(MeshFormat::from_magic(QUAD_DAE.as_bytes()), Some(MeshFormat::Dae));
+    assert_eq!(MeshFormat::from_magic(TRIANGLE_GLTF.as_bytes()), Some(MeshFormat::Gltf));
+    assert_eq!(MeshFormat::from_magic(TRIANGLE_ASCII_STL.as_bytes()), Some(MeshFormat::Stl));
+    assert_eq!(MeshFormat::from_magic(b"glTF\x02\x00\x00\x00"), Some(MeshFormat::Glb));
+
+    let mut binary_stl = vec![0u8; 84 + 50];
+    binary_stl[80] = 1;
+    assert_eq!(MeshFormat::from_magic(&binary_stl), Some(MeshFormat::Stl));
+    assert_eq!(MeshFormat::from_magic(b"\x00\x01\x02"), None);
+}
+
+/// Test an OBJ keeps its material color (with `d` as alpha), normals and flipped UVs.
+#[test]
+fn test_load_obj_with_material() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("quad.obj"), QUAD_OBJ).unwrap();
+    fs::write(dir.path().join("quad.mtl"), QUAD_MTL).unwrap();
+
+    let mesh = load_mesh_file(&dir.path().join("quad.obj")).unwrap();
+    assert_eq!(mesh.vertex_positions.len(), 4);
+    assert_eq!(mesh.triangle_indices.as_ref().unwrap().len(), 2);
+
+    let colors = mesh.vertex_colors.as_ref().expect("material color should be baked in");
+    assert!(colors.iter().all(|c| c.0.to_array() == [255, 0, 0, 128]));
+
+    let normals = mesh.vertex_normals.as_ref().unwrap();
+    assert_f32_slice_approx_eq(&normals[0].0.0, &[0.0, 0.0, 1.0], "OBJ normal mismatch");
+
+    let texcoords = mesh.vertex_texcoords.as_ref().unwrap();
+    assert_f32_slice_approx_eq(&texcoords[0].0.0, &[0.0, 1.0], "OBJ UV should be flipped");
+    assert_f32_slice_approx_eq(&texcoords[2].0.0, &[1.0, 0.0], "OBJ UV should be flipped");
+}
+
+/// Test a COLLADA file applies its unit, node transform and bound material,
+/// and that polygons are triangulated.
+#[test]
+fn test_load_dae_units_transform_and_material() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("quad.dae");
+    fs::write(&path, QUAD_DAE).unwrap();
+
+    let mesh = load_mesh_file(&path).unwrap();
+    assert_eq!(mesh.triangle_indices.as_ref().unwrap().len(), 2);
+    let expected = [
+        [0.0, 0.0, 0.5],
+        [1.0, 0.0, 0.5],
+        [1.0, 1.0, 0.5],
+        [0.0, 1.0, 0.5],
+    ];
+    for (got, want) in positions(&mesh).iter().zip(expected.iter()) {
+        assert_f32_slice_approx_eq(got, want, "DAE position mismatch");
+    }
+
+    let colors = mesh.vertex_colors.as_ref().unwrap();
+    assert!(colors.iter().all(|c| c.0.to_array() == [0, 255, 0, 255]));
+    assert!(mesh.vertex_normals.is_some(), "Missing normals should be computed");
+}
+
+/// Test a Y-up COLLADA file is rotated to Z up along with its unit scaling.
+#[test]
+fn test_load_dae_y_up() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("quad.dae");
+    fs::write(&path, QUAD_DAE.replace("Z_UP", "Y_UP")).unwrap();
+
+    let mesh = load_mesh_file(&path).unwrap();
+    // Y up -> Z up maps (x, y, z) to (x, -z, y)
+    let expected = [
+        [0.0, -0.5, 0.0],
+        [1.0, -0.5, 0.0],
+        [1.0, -0.5, 1.0],
+        [0.0, -0.5, 1.0],
+    ];
+    for (got, want) in positions(&mesh).iter().zip(expected.iter()) {
+        assert_f32_slice_approx_eq(got, want, "Y-up DAE position mismatch");
+    }
+}
+
+/// Test a zero rotation axis is ignored and a node instancing itself isn't expanded forever.
+#[test]
+fn test_load_dae_degenerate_nodes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("quad.dae");
+    let dae = QUAD_DAE.replace(
+        "<translate>0 0 500</translate>",
+        "<translate>0 0 500</translate><rotate>0 0 0 90</rotate><instance_node url=\"#quad\"/>",
+    );
+    fs::write(&path, dae).unwrap();
+
+    let mesh = load_mesh_file(&path).unwrap();
+    assert_eq!(mesh.vertex_positions.len(), 4);
+    let expected = [[0.0, 0.0, 0.5], [1.0, 0.0, 0.5]];
+    for (got, want) in positions(&mesh).iter().zip(expected.iter()) {
+        assert_f32_slice_approx_eq(got, want, "DAE position mismatch");
+    }
+}
+
+/// Test a glTF file applies its node transform and base color factor.
+#[test]
+fn test_load_gltf_transform_and_base_color() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tri.gltf");
+    fs::write(&path, TRIANGLE_GLTF).unwrap();
+
+    let mesh = load_mesh_file(&path).unwrap();
+    let expected = [[0.0, 0.0, 2.0], [1.0, 0.0, 2.0], [0.0, 1.0, 2.0]];
+    for (got, want) in positions(&mesh).iter().zip(expected.iter()) {
+        assert_f32_slice_approx_eq(got, want, "glTF position mismatch");
+    }
+    let colors = mesh.vertex_colors.as_ref().unwrap();
+    assert!(colors.iter().all(|c| c.0.to_array() == [0, 0, 255, 255]));
+}
+
+/// Test a file without a known extension is loaded by sniffing its contents.
+#[test]
+fn test_load_mesh_without_extension() {
+    let dir = tempfile::tempdir().unwrap();
+    let stl_path = dir.path().join("triangle_mesh");
+    fs::write(&stl_path, TRIANGLE_ASCII_STL).unwrap();
+    let mesh = load_mesh_file(&stl_path).unwrap();
+    assert_eq!(mesh.triangle_indices.as_ref().unwrap().len(), 1);
+
+    let dae_path = dir.path().join("quad_mesh");
+    fs::write(&dae_path, QUAD_DAE).unwrap();
+    let mesh = load_mesh_file(&dae_path).unwrap();
+    assert_eq!(mesh.vertex_positions.len(), 4);
+}
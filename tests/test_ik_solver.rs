@@ -0,0 +1,108 @@
+// tests/test_ik_solver.rs
+
+use std::collections::HashMap;
+use std::f64::consts::FRAC_PI_2;
+
+use krecviz::{solve_ik, IkOptions, IkTarget, RobotModel, UrdfLogOptions};
+use nalgebra as na;
+use urdf_rs::read_file;
+
+/// The planar arm of `arm.urdf`: a shoulder 1 m above the base and two 1 m links along x,
+/// both turning about z, then a tool on a 0.5 m prismatic joint.
+fn arm() -> RobotModel {
+    arm_with_elbow_limits(-3.0, 3.0)
+}
+
+fn arm_with_elbow_limits(lower: f64, upper: f64) -> RobotModel {
+    let mut robot =
+        read_file("tests/assets/urdf_examples/arm/arm.urdf").expect("Failed to parse arm.urdf");
+    let elbow = robot
+        .joints
+        .iter_mut()
+        .find(|j| j.name == "elbow")
+        .expect("arm.urdf has an elbow");
+    elbow.limit.lower = lower;
+    elbow.limit.upper = upper;
+    RobotModel::from_robot(&robot, &UrdfLogOptions::default()).expect("Failed to build robot model")
+}
+
+fn target(position: [f64; 3]) -> IkTarget {
+    IkTarget {
+        link: "tool".to_string(),
+        position,
+        orientation: None,
+    }
+}
+
+/// Test a reachable position is reached, and the solved positions put the link there.
+#[test]
+fn test_reaches_position() {
+    let model = arm();
+    let solution = solve_ik(&model, &target([0.5, 1.5, 1.0]), &IkOptions::default()).unwrap();
+
+    assert!(solution.converged);
+    let final_iteration = solution.final_iteration();
+    assert!(final_iteration.position_error < 1e-4);
+    assert!(solution.trace.len() > 1);
+    assert!(solution.trace[0].position_error > final_iteration.position_error);
+
+    let tool = model.link_pose("tool", solution.joint_positions()).unwrap();
+    let error = tool.translation.vector - na::Vector3::new(0.5, 1.5, 1.0);
+    assert!(error.norm() < 1e-4, "{error}");
+}
+
+/// Test an orientation target is reached along with the position.
+#[test]
+fn test_reaches_pose() {
+    let model = arm();
+    let mut pose_target = target([0.0, 2.0, 1.0]);
+    pose_target.orientation = Some(na::UnitQuaternion::from_euler_angles(0.0, 0.0, FRAC_PI_2));
+    let solution = solve_ik(&model, &pose_target, &IkOptions::default()).unwrap();
+
+    assert!(solution.converged);
+    let positions = solution.joint_positions();
+    assert!(
+        (positions["shoulder"] - FRAC_PI_2).abs() < 1e-3,
+        "{positions:?}"
+    );
+    assert!(positions["elbow"].abs() < 1e-3, "{positions:?}");
+}
+
+/// Test out-of-reach targets stretch the arm toward them without exceeding the limits.
+#[test]
+fn test_unreachable_target_respects_limits() {
+    let model = arm();
+    let solution = solve_ik(&model, &target([5.0, 0.0, 1.0]), &IkOptions::default()).unwrap();
+
+    assert!(!solution.converged);
+    assert!((solution.final_iteration().position_error - 2.5).abs() < 1e-3);
+    assert_eq!(solution.joint_positions()["slide"], 0.5);
+
+    // An elbow bending at most 0.3 rad cannot fold the arm back
+    let model = arm_with_elbow_limits(0.0, 0.3);
+    let options = IkOptions {
+        initial_positions: HashMap::from([("elbow".to_string(), 1.0)]),
+        ..Default::default()
+    };
+    let solution = solve_ik(&model, &target([0.0, 1.0, 1.0]), &options).unwrap();
+    assert!(!solution.converged);
+    for iteration in &solution.trace {
+        let elbow = iteration.joint_positions["elbow"];
+        assert!((0.0..=0.3).contains(&elbow), "elbow at {elbow}");
+    }
+}
+
+/// Test unknown links and links without movable joints above them are rejected.
+#[test]
+fn test_invalid_targets() {
+    let model = arm();
+
+    let mut unknown = target([0.0; 3]);
+    unknown.link = "gripper".to_string();
+    let err = solve_ik(&model, &unknown, &IkOptions::default()).unwrap_err();
+    assert!(err.to_string().contains("gripper"), "{err}");
+
+    let mut root = target([0.0; 3]);
+    root.link = "base".to_string();
+    assert!(solve_ik(&model, &root, &IkOptions::default()).is_err());
+}
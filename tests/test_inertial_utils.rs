@@ -0,0 +1,128 @@
+// tests/test_inertial_utils.rs
+
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use krecviz::utils::inertial_utils::{
+    combined_center_of_mass, mass_point_radius, LinkInertial, MASS_POINT_DENSITY,
+};
+use nalgebra as na;
+
+const EPSILON: f64 = 1e-9;
+
+/// Inertia tensor `[ixx, ixy, ixz, iyy, iyz, izz]` of a uniform box with full side lengths.
+fn box_inertia(mass: f64, [x, y, z]: [f64; 3]) -> [f64; 6] {
+    let k = mass / 12.0;
+    [
+        k * (y * y + z * z),
+        0.0,
+        0.0,
+        k * (x * x + z * z),
+        0.0,
+        k * (x * x + y * y),
+    ]
+}
+
+/// Each principal extent as a vector in the link frame, with absolute components.
+fn extents_in_link_frame(inertial: &LinkInertial, extents: [f64; 3]) -> Vec<[f64; 3]> {
+    (0..3)
+        .map(|i| {
+            let v = inertial.principal_axes * (na::Vector3::ith(i, 1.0) * extents[i]);
+            [v.x.abs(), v.y.abs(), v.z.abs()]
+        })
+        .collect()
+}
+
+fn assert_contains_approx(vectors: &[[f64; 3]], expected: [f64; 3]) {
+    assert!(
+        vectors
+            .iter()
+            .any(|v| v.iter().zip(expected).all(|(a, b)| (a - b).abs() < 1e-6)),
+        "{expected:?} not in {vectors:?}"
+    );
+}
+
+/// Test the equivalent box of a box's own inertia is that box.
+#[test]
+fn test_equivalent_box() {
+    let inertial = LinkInertial::new(
+        2.0,
+        [0.1, 0.2, 0.3],
+        [0.0; 3],
+        box_inertia(2.0, [0.2, 0.4, 0.6]),
+    );
+    assert_eq!(inertial.center_of_mass, [0.1, 0.2, 0.3]);
+
+    let extents = extents_in_link_frame(&inertial, inertial.equivalent_box_half_sizes());
+    assert_contains_approx(&extents, [0.1, 0.0, 0.0]);
+    assert_contains_approx(&extents, [0.0, 0.2, 0.0]);
+    assert_contains_approx(&extents, [0.0, 0.0, 0.3]);
+}
+
+/// Test the principal axes follow the inertial frame's rotation.
+#[test]
+fn test_principal_axes_follow_inertial_frame() {
+    let inertial = LinkInertial::new(
+        1.0,
+        [0.0; 3],
+        [0.0, 0.0, FRAC_PI_2],
+        box_inertia(1.0, [1.0, 2.0, 3.0]),
+    );
+
+    // The box's x side now lies along the link's y axis
+    let extents = extents_in_link_frame(&inertial, inertial.equivalent_box_half_sizes());
+    assert_contains_approx(&extents, [0.0, 0.5, 0.0]);
+    assert_contains_approx(&extents, [1.0, 0.0, 0.0]);
+    assert_contains_approx(&extents, [0.0, 0.0, 1.5]);
+}
+
+/// Test a solid sphere's equivalent ellipsoid is the sphere, even with products of inertia
+/// from an off-axis tensor.
+#[test]
+fn test_equivalent_ellipsoid() {
+    let (mass, radius) = (3.0, 0.25);
+    let i = 0.4 * mass * radius * radius;
+    let sphere = LinkInertial::new(mass, [0.0; 3], [0.0; 3], [i, 0.0, 0.0, i, 0.0, i]);
+    for r in sphere.equivalent_ellipsoid_radii() {
+        assert!((r - radius).abs() < EPSILON, "radius {r}");
+    }
+
+    let tilted = LinkInertial::new(1.0, [0.0; 3], [0.0; 3], [2.0, 0.5, 0.0, 2.0, 0.0, 3.0]);
+    let mut moments = tilted.principal_moments;
+    moments.sort_by(f64::total_cmp);
+    for (m, expected) in moments.iter().zip([1.5, 2.5, 3.0]) {
+        assert!((m - expected).abs() < EPSILON, "moments {moments:?}");
+    }
+}
+
+/// Test massless links and inertias breaking the triangle inequality give zero extents.
+#[test]
+fn test_degenerate_inertia() {
+    let massless = LinkInertial::new(0.0, [0.0; 3], [0.0; 3], [0.0; 6]);
+    assert_eq!(massless.equivalent_box_half_sizes(), [0.0; 3]);
+
+    let invalid = LinkInertial::new(1.0, [0.0; 3], [0.0; 3], [10.0, 0.0, 0.0, 1.0, 0.0, 1.0]);
+    let half_sizes = invalid.equivalent_box_half_sizes();
+    assert!(half_sizes.iter().all(|h| h.is_finite()), "{half_sizes:?}");
+    assert!(half_sizes.contains(&0.0), "{half_sizes:?}");
+}
+
+/// Test the mass marker is a sphere of `MASS_POINT_DENSITY` with the link's mass.
+#[test]
+fn test_mass_point_radius() {
+    let r = mass_point_radius(5.0);
+    let volume = 4.0 / 3.0 * PI * r.powi(3);
+    assert!((volume * MASS_POINT_DENSITY - 5.0).abs() < EPSILON);
+    assert_eq!(mass_point_radius(0.0), 0.0);
+}
+
+/// Test the combined center of mass is the mass-weighted mean.
+#[test]
+fn test_combined_center_of_mass() {
+    let (mass, com) =
+        combined_center_of_mass([(1.0, [0.0, 0.0, 0.0]), (3.0, [4.0, 0.0, 2.0])]).unwrap();
+    assert_eq!(mass, 4.0);
+    assert_eq!(com, [3.0, 0.0, 1.5]);
+
+    assert!(combined_center_of_mass([(0.0, [1.0, 2.0, 3.0])]).is_none());
+    assert!(combined_center_of_mass(Vec::new()).is_none());
+}
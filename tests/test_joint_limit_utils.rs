@@ -0,0 +1,123 @@
+// tests/test_joint_limit_utils.rs
+
+use krecviz::utils::joint_limit_utils::{
+    check_joint_limits, JointSample, LimitKind, LimitStatus, LimitViolationSummary,
+};
+use urdf_rs::{JointLimit, JointType};
+
+const NEAR_MARGIN: f64 = 0.05;
+
+fn limit() -> JointLimit {
+    JointLimit {
+        lower: -1.0,
+        upper: 1.0,
+        effort: 10.0,
+        velocity: 2.0,
+    }
+}
+
+fn position(position: f64) -> JointSample {
+    JointSample {
+        position: Some(position),
+        ..Default::default()
+    }
+}
+
+/// Test positions inside, near and beyond the revolute range.
+#[test]
+fn test_position_limits() {
+    let within = check_joint_limits(&JointType::Revolute, &limit(), &position(0.5), NEAR_MARGIN);
+    assert_eq!(within.status, LimitStatus::Within);
+    assert!(within.violations.is_empty());
+
+    // Within 5% of the 2 rad range from the upper bound
+    let near = check_joint_limits(&JointType::Revolute, &limit(), &position(0.95), NEAR_MARGIN);
+    assert_eq!(near.status, LimitStatus::NearLimit);
+
+    let below = check_joint_limits(
+        &JointType::Revolute,
+        &limit(),
+        &position(-1.25),
+        NEAR_MARGIN,
+    );
+    assert_eq!(below.status, LimitStatus::Exceeded);
+    let violation = &below.violations[0];
+    assert_eq!(violation.kind, LimitKind::Position);
+    assert_eq!(violation.limit, -1.0);
+    assert_eq!(violation.excess, 0.25);
+    assert_eq!(violation.unit, "rad");
+    assert!(violation.message("knee").contains("knee position"));
+}
+
+/// Test continuous joints and empty ranges have no position limit.
+#[test]
+fn test_unbounded_positions_are_not_checked() {
+    let far = position(100.0);
+    let continuous = check_joint_limits(&JointType::Continuous, &limit(), &far, NEAR_MARGIN);
+    assert_eq!(continuous.status, LimitStatus::Within);
+
+    let unset = JointLimit {
+        lower: 0.0,
+        upper: 0.0,
+        effort: 0.0,
+        velocity: 0.0,
+    };
+    let revolute = check_joint_limits(&JointType::Revolute, &unset, &far, NEAR_MARGIN);
+    assert_eq!(revolute.status, LimitStatus::Within);
+}
+
+/// Test velocity and effort are checked by magnitude, and only when their limit is set.
+#[test]
+fn test_velocity_and_effort_limits() {
+    let sample = JointSample {
+        position: Some(0.0),
+        velocity: Some(-3.0),
+        effort: Some(12.0),
+    };
+    let check = check_joint_limits(&JointType::Prismatic, &limit(), &sample, NEAR_MARGIN);
+    assert_eq!(check.status, LimitStatus::Exceeded);
+    assert_eq!(check.violations.len(), 2);
+
+    let velocity = &check.violations[0];
+    assert_eq!(velocity.kind, LimitKind::Velocity);
+    assert_eq!(velocity.limit, -2.0);
+    assert_eq!(velocity.excess, 1.0);
+    assert_eq!(velocity.unit, "m/s");
+    let effort = &check.violations[1];
+    assert_eq!(effort.kind, LimitKind::Effort);
+    assert_eq!(effort.excess, 2.0);
+
+    let no_limits = JointLimit {
+        effort: 0.0,
+        velocity: 0.0,
+        ..limit()
+    };
+    let check = check_joint_limits(&JointType::Prismatic, &no_limits, &sample, NEAR_MARGIN);
+    assert_eq!(check.status, LimitStatus::Within);
+}
+
+/// Test the summary counts frames and keeps the largest excess per joint and limit.
+#[test]
+fn test_violation_summary() {
+    let mut summary = LimitViolationSummary::default();
+    assert!(summary.markdown(10).contains("No joint exceeded"));
+
+    for (frame_idx, value) in [(3, 1.5), (4, 1.2), (8, -1.1)] {
+        let check = check_joint_limits(&JointType::Revolute, &limit(), &position(value), 0.0);
+        for violation in &check.violations {
+            summary.record("knee", violation, frame_idx);
+        }
+    }
+
+    let stats = summary.get("knee", LimitKind::Position).unwrap();
+    assert_eq!(stats.frames, 3);
+    assert_eq!(stats.first_frame, 3);
+    assert_eq!(stats.max_excess, 0.5);
+    assert!(summary.get("knee", LimitKind::Velocity).is_none());
+
+    let md = summary.markdown(10);
+    assert!(
+        md.contains("| knee | position | 3 | 30.0 % | 0.5000 rad | 3 |"),
+        "{md}"
+    );
+}
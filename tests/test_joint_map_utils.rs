@@ -0,0 +1,83 @@
+// tests/test_joint_map_utils.rs
+
+use krecviz::utils::joint_map_utils::{
+    default_actuator_to_urdf_joint_map, load_actuator_joint_map, parse_actuator_joint_map,
+    validate_actuator_joint_map, JointMapFormat,
+};
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+
+const BASE_PATH: &str = env!("CARGO_MANIFEST_DIR");
+
+#[test]
+fn test_parse_joint_map_all_formats() {
+    let toml_str = r#"
+        [actuators]
+        11 = "joint_a"
+        12 = "joint_b"
+    "#;
+    let json_str = r#"{ "actuators": { "11": "joint_a", "12": "joint_b" } }"#;
+    let yaml_str = "actuators:\n  11: joint_a\n  12: joint_b\n";
+
+    for (contents, format) in [
+        (toml_str, JointMapFormat::Toml),
+        (json_str, JointMapFormat::Json),
+        (yaml_str, JointMapFormat::Yaml),
+    ] {
+        let map = parse_actuator_joint_map(contents, format)
+            .unwrap_or_else(|e| panic!("Failed to parse {format:?}: {e}"));
+        assert_eq!(map.len(), 2, "{format:?}: expected 2 entries");
+        assert_eq!(map[&11], "joint_a");
+        assert_eq!(map[&12], "joint_b");
+    }
+}
+
+#[test]
+fn test_parse_joint_map_rejects_bad_ids() {
+    let toml_str = r#"
+        [actuators]
+        left_knee = "joint_a"
+    "#;
+    assert!(parse_actuator_joint_map(toml_str, JointMapFormat::Toml).is_err());
+}
+
+#[test]
+fn test_format_from_path() {
+    assert_eq!(
+        JointMapFormat::from_path(Path::new("map.TOML")),
+        Some(JointMapFormat::Toml)
+    );
+    assert_eq!(
+        JointMapFormat::from_path(Path::new("map.yml")),
+        Some(JointMapFormat::Yaml)
+    );
+    assert_eq!(JointMapFormat::from_path(Path::new("map.txt")), None);
+}
+
+#[test]
+fn test_gpr_joint_map_file_matches_default() {
+    let path = PathBuf::from(format!("{BASE_PATH}/tests/assets/joint_maps/gpr.toml"));
+    let map = load_actuator_joint_map(&path).expect("Failed to load gpr.toml");
+    assert_eq!(map, default_actuator_to_urdf_joint_map());
+}
+
+#[test]
+fn test_validate_joint_map_reports_all_problems() {
+    let map = default_actuator_to_urdf_joint_map();
+    let actuator_ids: BTreeSet<u32> = [11, 99].into_iter().collect();
+    let joint_names: HashSet<String> = map
+        .values()
+        .filter(|name| name.as_str() != "L_knee")
+        .cloned()
+        .collect();
+
+    let err = validate_actuator_joint_map(&map, &actuator_ids, &joint_names)
+        .expect_err("Validation should fail")
+        .to_string();
+    assert!(err.contains("unmapped actuators: [99]"), "{err}");
+    assert!(err.contains("\"L_knee\""), "{err}");
+
+    let all_joints: HashSet<String> = map.values().cloned().collect();
+    let known_ids: BTreeSet<u32> = [11, 45].into_iter().collect();
+    assert!(validate_actuator_joint_map(&map, &known_ids, &all_joints).is_ok());
+}
@@ -0,0 +1,67 @@
+// tests/test_joint_overlay_utils.rs
+
+use std::f64::consts::FRAC_PI_2;
+
+use krecviz::utils::joint_overlay_utils::{
+    perpendicular_unit, prismatic_limit_segment, revolute_limit_outline, unit_axis,
+};
+
+const EPSILON: f32 = 1e-6;
+
+fn assert_point_approx(actual: [f32; 3], expected: [f32; 3]) {
+    for (a, e) in actual.iter().zip(expected) {
+        assert!(
+            (a - e).abs() < EPSILON,
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+}
+
+/// Test joint axes are normalized and zero axes rejected.
+#[test]
+fn test_unit_axis() {
+    assert_eq!(unit_axis([0.0, 0.0, 2.0]), Some([0.0, 0.0, 1.0]));
+    assert_eq!(unit_axis([0.0; 3]), None);
+}
+
+/// Test the arc reference direction is perpendicular to the axis and unit length.
+#[test]
+fn test_perpendicular_unit() {
+    assert_eq!(perpendicular_unit([0.0, 0.0, 1.0]), [1.0, 0.0, 0.0]);
+
+    let axis = unit_axis([1.0, 2.0, 3.0]).unwrap();
+    let p = perpendicular_unit(axis);
+    let dot: f64 = axis.iter().zip(p).map(|(a, b)| a * b).sum();
+    let norm: f64 = p.iter().map(|v| v * v).sum::<f64>().sqrt();
+    assert!(dot.abs() < 1e-9, "not perpendicular: {p:?}");
+    assert!((norm - 1.0).abs() < 1e-9, "not unit length: {p:?}");
+}
+
+/// Test the limit outline runs from the origin along the arc from lower to upper and back.
+#[test]
+fn test_revolute_limit_outline() {
+    let outline = revolute_limit_outline([0.0, 0.0, 1.0], -FRAC_PI_2, FRAC_PI_2, 0.5, 4);
+
+    assert_eq!(outline.len(), 4 + 3);
+    assert_eq!(outline[0], [0.0; 3]);
+    assert_eq!(outline[outline.len() - 1], [0.0; 3]);
+    // About +z, starting along +x: -90° is -y, 0° is +x, +90° is +y
+    assert_point_approx(outline[1], [0.0, -0.5, 0.0]);
+    assert_point_approx(outline[3], [0.5, 0.0, 0.0]);
+    assert_point_approx(outline[5], [0.0, 0.5, 0.0]);
+    for p in &outline[1..outline.len() - 1] {
+        assert!((p[0].hypot(p[1]) - 0.5).abs() < EPSILON && p[2].abs() < EPSILON);
+    }
+
+    assert!(revolute_limit_outline([0.0, 0.0, 1.0], 1.0, 1.0, 0.5, 4).is_empty());
+    assert!(revolute_limit_outline([0.0; 3], -1.0, 1.0, 0.5, 4).is_empty());
+}
+
+/// Test prismatic limits span the normalized axis.
+#[test]
+fn test_prismatic_limit_segment() {
+    let [a, b] = prismatic_limit_segment([0.0, 2.0, 0.0], -0.1, 0.3).unwrap();
+    assert_point_approx(a, [0.0, -0.1, 0.0]);
+    assert_point_approx(b, [0.0, 0.3, 0.0]);
+    assert!(prismatic_limit_segment([0.0; 3], 0.0, 1.0).is_none());
+}
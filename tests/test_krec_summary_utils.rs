@@ -0,0 +1,146 @@
+// tests/test_krec_summary_utils.rs
+
+use krec::{
+    ActuatorCommand, ActuatorConfig, ActuatorState, ImuValues, KRec, KRecFrame, KRecHeader,
+};
+use krecviz::utils::krec_summary_utils::{FrameGap, KrecSummary};
+
+const START_NS: u64 = 1_700_000_000_000_000_000;
+const FRAME_NS: u64 = 10_000_000;
+
+/// A 100 Hz recording of actuators 11 and 12 (and commands to 13) with frames 3 and 4
+/// dropped, 1 ms of jitter on frame 6 and the IMU in even frames.
+fn recording() -> KRec {
+    let mut krec = KRec::new(KRecHeader {
+        uuid: "fixture".to_string(),
+        task: "walking".to_string(),
+        start_timestamp: START_NS,
+        actuator_configs: vec![ActuatorConfig {
+            actuator_id: 12,
+            name: Some("left_knee".to_string()),
+            ..Default::default()
+        }],
+        ..Default::default()
+    });
+    for i in [0u64, 1, 2, 5, 6, 7] {
+        let jitter = if i == 6 { 1_000_000 } else { 0 };
+        krec.add_frame(KRecFrame {
+            video_timestamp: i * FRAME_NS + jitter,
+            video_frame_number: i,
+            actuator_states: vec![
+                ActuatorState {
+                    actuator_id: 11,
+                    online: i > 0,
+                    position: Some(i as f64),
+                    voltage: Some(24.0),
+                    ..Default::default()
+                },
+                ActuatorState {
+                    actuator_id: 12,
+                    online: true,
+                    ..Default::default()
+                },
+            ],
+            actuator_commands: vec![ActuatorCommand {
+                actuator_id: 13,
+                position: 0.5,
+                ..Default::default()
+            }],
+            imu_values: (i % 2 == 0).then(|| ImuValues {
+                accel: Some(Default::default()),
+                quaternion: Some(Default::default()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+    }
+    krec
+}
+
+/// Test frame counts, timing, jitter and dropped-frame gaps.
+#[test]
+fn test_summary_timing() {
+    let summary = KrecSummary::from_krec(&recording());
+    assert_eq!(summary.frames, 6);
+    assert_eq!(summary.header.task, "walking");
+
+    let timing = summary.timing.expect("No timing");
+    assert!((timing.duration_s - 0.07).abs() < 1e-9);
+    assert!((timing.frame_rate_hz - 5.0 / 0.07).abs() < 1e-6);
+    assert!((timing.median_interval_ms - 10.0).abs() < 1e-9);
+    assert!((timing.min_interval_ms - 9.0).abs() < 1e-9);
+    assert!((timing.max_interval_ms - 30.0).abs() < 1e-9);
+    assert!(timing.jitter_ms > 0.0);
+
+    assert_eq!(
+        summary.gaps,
+        [FrameGap {
+            after_frame: 2,
+            interval_ms: 30.0,
+            missing_frames: 2,
+        }]
+    );
+}
+
+/// Test actuator IDs come from the header, states and commands, with per-field stats.
+#[test]
+fn test_summary_actuators() {
+    let summary = KrecSummary::from_krec(&recording());
+    assert_eq!(summary.actuator_ids, [11, 12, 13]);
+
+    let actuator_11 = &summary.actuators[0];
+    assert_eq!(actuator_11.state_frames, 6);
+    assert_eq!(actuator_11.online_frames, 5);
+    let position = actuator_11.fields["position"];
+    assert_eq!((position.min, position.max, position.count), (0.0, 7.0, 6));
+    assert!((position.mean - 21.0 / 6.0).abs() < 1e-9);
+    assert_eq!(actuator_11.fields["voltage"].mean, 24.0);
+    assert!(!actuator_11.fields.contains_key("torque"));
+
+    let actuator_12 = &summary.actuators[1];
+    assert_eq!(actuator_12.name.as_deref(), Some("left_knee"));
+    assert!(actuator_12.fields.is_empty());
+
+    let actuator_13 = &summary.actuators[2];
+    assert_eq!(
+        (actuator_13.state_frames, actuator_13.command_frames),
+        (0, 6)
+    );
+    assert_eq!(actuator_13.fields["command_position"].max, 0.5);
+}
+
+/// Test IMU presence and the text and JSON reports.
+#[test]
+fn test_summary_reports() {
+    let summary = KrecSummary::from_krec(&recording());
+    assert_eq!(summary.imu.frames, 3);
+    assert_eq!(summary.imu.quaternion, 3);
+    assert_eq!(summary.imu.gyro, 0);
+
+    let text = summary.text();
+    assert!(text.contains("Frames:          6"), "{text}");
+    assert!(text.contains("Actuators:       11, 12, 13"), "{text}");
+    assert!(
+        text.contains("after frame 2: 30.000 ms, ~2 frames missing"),
+        "{text}"
+    );
+    assert!(text.contains("Actuator 12 (left_knee)"), "{text}");
+
+    let json = serde_json::to_value(&summary).unwrap();
+    assert_eq!(json["frames"], 6);
+    assert_eq!(json["actuators"][0]["fields"]["position"]["max"], 7.0);
+    assert_eq!(json["gaps"][0]["missing_frames"], 2);
+}
+
+/// Test recordings without video timestamps have no timing or gaps.
+#[test]
+fn test_summary_without_timestamps() {
+    let mut krec = KRec::new(KRecHeader::default());
+    for _ in 0..3 {
+        krec.add_frame(KRecFrame::default());
+    }
+    let summary = KrecSummary::from_krec(&krec);
+    assert_eq!(summary.timing, None);
+    assert!(summary.gaps.is_empty());
+    assert!(summary.text().contains("no video timestamps"));
+}
@@ -0,0 +1,198 @@
+// tests/test_krec_utils.rs
+
+use krec::{ActuatorConfig, ActuatorState, KRec, KRecFrame, KRecHeader};
+use krecviz::utils::krec_utils::{
+    actuator_config_markdown, frame_wall_clock_nanos, header_markdown, is_video_container,
+    load_krec, ActuatorThresholds,
+};
+use std::path::Path;
+use std::process::Command;
+
+const START_NS: u64 = 1_700_000_000_000_000_000;
+
+#[test]
+fn test_wall_clock_without_start_timestamp() {
+    assert_eq!(frame_wall_clock_nanos(0, 1_000), None);
+}
+
+#[test]
+fn test_wall_clock_relative_video_timestamp() {
+    // 20 ms into the recording
+    assert_eq!(
+        frame_wall_clock_nanos(START_NS, 20_000_000),
+        Some((START_NS + 20_000_000) as i64)
+    );
+}
+
+#[test]
+fn test_wall_clock_absolute_video_timestamp() {
+    let absolute = START_NS + 5_000_000;
+    assert_eq!(
+        frame_wall_clock_nanos(START_NS, absolute),
+        Some(absolute as i64)
+    );
+}
+
+#[test]
+fn test_actuator_thresholds() {
+    let thresholds = ActuatorThresholds {
+        max_temperature: Some(70.0),
+        min_voltage: Some(20.0),
+        max_voltage: None,
+        max_current: Some(10.0),
+    };
+
+    let healthy = ActuatorState {
+        actuator_id: 11,
+        online: true,
+        temperature: Some(45.0),
+        voltage: Some(24.0),
+        current: Some(-3.0),
+        ..Default::default()
+    };
+    assert!(thresholds.violations(&healthy).is_empty());
+
+    let failing = ActuatorState {
+        actuator_id: 11,
+        online: true,
+        temperature: Some(85.0),
+        voltage: Some(18.5),
+        current: Some(-12.0),
+        ..Default::default()
+    };
+    let checks: Vec<&str> = thresholds
+        .violations(&failing)
+        .iter()
+        .map(|(check, _)| *check)
+        .collect();
+    assert_eq!(
+        checks,
+        ["over_temperature", "under_voltage", "over_current"]
+    );
+
+    // Missing readings are never reported
+    let silent = ActuatorState {
+        actuator_id: 11,
+        ..Default::default()
+    };
+    assert!(thresholds.violations(&silent).is_empty());
+}
+
+#[test]
+fn test_header_markdown() {
+    let header = KRecHeader {
+        uuid: "1234-abcd".to_string(),
+        task: "walking".to_string(),
+        robot_platform: "gpr".to_string(),
+        robot_serial: "SN-001".to_string(),
+        start_timestamp: START_NS,
+        end_timestamp: START_NS + 2_500_000_000,
+        actuator_configs: vec![
+            ActuatorConfig {
+                actuator_id: 12,
+                kp: Some(40.0),
+                ..Default::default()
+            },
+            ActuatorConfig {
+                actuator_id: 11,
+                name: Some("left_shoulder".to_string()),
+                max_torque: Some(17.0),
+                ..Default::default()
+            },
+        ],
+    };
+
+    let md = header_markdown(&header, 250);
+    assert!(md.contains("| UUID | `1234-abcd` |"), "{md}");
+    assert!(md.contains("| Task | walking |"), "{md}");
+    assert!(md.contains("| Duration | 2.500 s |"), "{md}");
+    assert!(md.contains("| Frames | 250 |"), "{md}");
+    // Actuators are listed sorted by ID
+    let pos_11 = md
+        .find("| 11 | left_shoulder |")
+        .expect("Actuator 11 missing");
+    let pos_12 = md.find("| 12 | - | 40 |").expect("Actuator 12 missing");
+    assert!(pos_11 < pos_12);
+
+    let config_md = actuator_config_markdown(&header.actuator_configs[1]);
+    assert!(config_md.starts_with("# Actuator 11 (left_shoulder)"));
+    assert!(config_md.contains("- max torque: 17"));
+    assert!(config_md.contains("- kp: -"));
+}
+
+/// Write a small KREC fixture with `num_frames` frames of a single actuator.
+fn write_fixture_krec(path: &Path, num_frames: u64) {
+    let header = KRecHeader {
+        uuid: "fixture".to_string(),
+        task: "test".to_string(),
+        start_timestamp: START_NS,
+        end_timestamp: START_NS + num_frames * 10_000_000,
+        ..Default::default()
+    };
+    let mut krec = KRec::new(header);
+    for i in 0..num_frames {
+        krec.add_frame(KRecFrame {
+            video_timestamp: i * 10_000_000,
+            video_frame_number: i,
+            inference_step: i,
+            actuator_states: vec![ActuatorState {
+                actuator_id: 11,
+                online: true,
+                position: Some(i as f64),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+    }
+    krec.save(path.to_str().unwrap())
+        .expect("Failed to write KREC fixture");
+}
+
+#[test]
+fn test_load_krec_from_raw_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let krec_path = dir.path().join("fixture.krec");
+    write_fixture_krec(&krec_path, 5);
+
+    assert!(!is_video_container(&krec_path));
+    let loaded = load_krec(krec_path.to_str().unwrap()).expect("Failed to load raw KREC");
+    assert_eq!(loaded.frames.len(), 5);
+    assert_eq!(loaded.header.uuid, "fixture");
+}
+
+// Building the video fixture needs the ffmpeg CLI, same as extracting it does;
+// run with `cargo test -- --ignored` where ffmpeg is installed
+#[test]
+#[ignore = "needs ffmpeg"]
+fn test_load_krec_from_mkv() {
+    Command::new("ffmpeg")
+        .arg("-version")
+        .output()
+        .expect("ffmpeg not found on the PATH");
+
+    let dir = tempfile::tempdir().unwrap();
+    let krec_path = dir.path().join("fixture.krec");
+    let video_path = dir.path().join("video.mkv");
+    let combined_path = dir.path().join("fixture.krec.mkv");
+    write_fixture_krec(&krec_path, 5);
+
+    let status = Command::new("ffmpeg")
+        .args(["-loglevel", "error", "-f", "lavfi", "-i"])
+        .arg("testsrc=duration=0.5:size=64x64:rate=10")
+        .arg(&video_path)
+        .status()
+        .expect("Failed to run ffmpeg");
+    assert!(status.success(), "ffmpeg failed to create the test video");
+
+    krec::combine_with_video(
+        video_path.to_str().unwrap(),
+        krec_path.to_str().unwrap(),
+        combined_path.to_str().unwrap(),
+    )
+    .expect("Failed to embed KREC into video");
+
+    assert!(is_video_container(&combined_path));
+    let loaded = load_krec(combined_path.to_str().unwrap()).expect("Failed to load MKV KREC");
+    assert_eq!(loaded.frames.len(), 5);
+    assert_eq!(loaded.frames[4].actuator_states[0].position, Some(4.0));
+}
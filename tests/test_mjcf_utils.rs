@@ -0,0 +1,253 @@
+// tests/test_mjcf_utils.rs
+
+use std::collections::HashMap;
+use std::f64::consts::FRAC_PI_2;
+
+use krecviz::utils::mjcf_utils::{is_mjcf, mjcf_to_urdf, MjcfConversion};
+use urdf_rs::{Geometry, JointType, Robot};
+
+const BASE_PATH: &str = env!("CARGO_MANIFEST_DIR");
+const EPSILON: f64 = 1e-9;
+
+/// A small arm exercising defaults, childclass, degrees, multi-joint bodies with joint
+/// offsets, `fromto` capsules, ellipsoids, meshes and materials.
+const ARM_MJCF: &str = r#"<mujoco model="arm">
+  <compiler angle="degree" meshdir="meshes"/>
+  <default>
+    <geom rgba="0.5 0.5 0.5 1"/>
+    <default class="link">
+      <joint axis="0 1 0" range="-90 90"/>
+    </default>
+  </default>
+  <asset>
+    <material name="red" rgba="1 0 0 1"/>
+    <mesh name="hand" file="hand.stl" scale="0.001 0.001 0.001"/>
+  </asset>
+  <worldbody>
+    <geom name="floor" type="plane" size="1 1 0.1"/>
+    <body name="base">
+      <geom type="box" size="0.1 0.2 0.3" rgba="0.1 0.2 0.3 1"/>
+      <body name="upper" pos="0 0 0.5" childclass="link">
+        <joint name="shoulder_pitch" pos="0 0 0.1"/>
+        <joint name="shoulder_roll" axis="1 0 0" pos="0 0 0.1"/>
+        <geom type="capsule" fromto="0 0 0 0 0 0.4" size="0.05" material="red"/>
+        <body name="lower" pos="0 0 0.4" euler="0 0 90">
+          <joint name="elbow" limited="false"/>
+          <geom type="ellipsoid" size="0.1 0.2 0.3"/>
+          <body name="hand">
+            <joint name="wrist_slide" type="slide" axis="0 0 1" range="0 0.05"/>
+            <geom type="mesh" mesh="hand"/>
+          </body>
+        </body>
+      </body>
+    </body>
+  </worldbody>
+</mujoco>"#;
+
+fn convert(xml: &str) -> (Robot, MjcfConversion) {
+    let conversion = mjcf_to_urdf(xml).unwrap();
+    let robot = urdf_rs::read_from_string(&conversion.urdf).unwrap();
+    (robot, conversion)
+}
+
+fn assert_approx(a: f64, b: f64, message: &str) {
+    assert!((a - b).abs() < EPSILON, "{message}: expected {b}, got {a}");
+}
+
+/// Test MJCF models are told apart from URDF.
+#[test]
+fn test_is_mjcf() {
+    assert!(is_mjcf(ARM_MJCF));
+    assert!(!is_mjcf(r#"<robot name="r"><link name="a"/></robot>"#));
+    assert!(!is_mjcf("not xml"));
+}
+
+/// Test bodies become links, with an intermediate link for a body with two joints.
+#[test]
+fn test_mjcf_link_tree() {
+    let (robot, _) = convert(ARM_MJCF);
+
+    let mut links: Vec<&str> = robot.links.iter().map(|l| l.name.as_str()).collect();
+    links.sort();
+    assert_eq!(
+        links,
+        [
+            "base",
+            "hand",
+            "lower",
+            "upper",
+            "upper_shoulder_pitch_link"
+        ]
+    );
+
+    let parents: HashMap<&str, (&str, &str)> = robot
+        .joints
+        .iter()
+        .map(|j| {
+            (
+                j.name.as_str(),
+                (j.parent.link.as_str(), j.child.link.as_str()),
+            )
+        })
+        .collect();
+    assert_eq!(
+        parents["shoulder_pitch"],
+        ("base", "upper_shoulder_pitch_link")
+    );
+    assert_eq!(
+        parents["shoulder_roll"],
+        ("upper_shoulder_pitch_link", "upper")
+    );
+    assert_eq!(parents["elbow"], ("upper", "lower"));
+    assert_eq!(parents["wrist_slide"], ("lower", "hand"));
+}
+
+/// Test joint types, axes, limits in degrees and joint origins at the joint's `pos`.
+#[test]
+fn test_mjcf_joints() {
+    let (robot, _) = convert(ARM_MJCF);
+    let joint = |name: &str| robot.joints.iter().find(|j| j.name == name).unwrap();
+
+    let shoulder = joint("shoulder_pitch");
+    assert!(matches!(shoulder.joint_type, JointType::Revolute));
+    assert_eq!(shoulder.axis.xyz[..], [0.0, 1.0, 0.0]);
+    assert_approx(shoulder.limit.lower, -FRAC_PI_2, "lower limit");
+    assert_approx(shoulder.limit.upper, FRAC_PI_2, "upper limit");
+    // Body at z = 0.5 plus the joint's pos z = 0.1
+    assert_approx(shoulder.origin.xyz[2], 0.6, "shoulder origin z");
+
+    assert_eq!(joint("shoulder_roll").axis.xyz[..], [1.0, 0.0, 0.0]);
+
+    // limited="false" wins over the class range
+    let elbow = joint("elbow");
+    assert!(matches!(elbow.joint_type, JointType::Continuous));
+    // The upper link frame sits at the joint, 0.1 above the body frame
+    assert_approx(elbow.origin.xyz[2], 0.3, "elbow origin z");
+    assert_approx(elbow.origin.rpy[2], FRAC_PI_2, "elbow origin yaw");
+
+    let wrist = joint("wrist_slide");
+    assert!(matches!(wrist.joint_type, JointType::Prismatic));
+    assert_approx(
+        wrist.limit.upper,
+        0.05,
+        "prismatic limits are not converted",
+    );
+}
+
+/// Test geoms become visuals with their geometry, pose and material.
+#[test]
+fn test_mjcf_geometry_and_materials() {
+    let (robot, conversion) = convert(ARM_MJCF);
+    let link = |name: &str| robot.links.iter().find(|l| l.name == name).unwrap();
+
+    let base = link("base");
+    assert_eq!(base.visual.len(), 1, "the floor plane is skipped");
+    match &base.visual[0].geometry {
+        Geometry::Box { size } => assert_eq!(size[..], [0.2, 0.4, 0.6]),
+        other => panic!("expected a box, got {other:?}"),
+    }
+    let color = base.visual[0]
+        .material
+        .as_ref()
+        .unwrap()
+        .color
+        .as_ref()
+        .unwrap();
+    assert_eq!(color.rgba[..], [0.1, 0.2, 0.3, 1.0]);
+
+    let upper = &link("upper").visual[0];
+    match &upper.geometry {
+        Geometry::Capsule { radius, length } => {
+            assert_approx(*radius, 0.05, "capsule radius");
+            assert_approx(*length, 0.4, "capsule length");
+        }
+        other => panic!("expected a capsule, got {other:?}"),
+    }
+    // Capsule center at z = 0.2 in the body, which sits 0.1 below the link frame
+    assert_approx(upper.origin.xyz[2], 0.1, "capsule origin z");
+    let color = upper.material.as_ref().unwrap().color.as_ref().unwrap();
+    assert_eq!(color.rgba[..], [1.0, 0.0, 0.0, 1.0]);
+
+    assert!(matches!(
+        link("lower").visual[0].geometry,
+        Geometry::Sphere { radius } if radius == 1.0
+    ));
+    let key = ("lower".to_string(), 0);
+    assert_eq!(conversion.ellipsoids[&key], [0.1, 0.2, 0.3]);
+    // Without contype/conaffinity to tell them apart, geoms are also collisions
+    assert_eq!(link("lower").collision.len(), 1);
+    assert_eq!(conversion.collision_ellipsoids[&key], [0.1, 0.2, 0.3]);
+
+    match &link("hand").visual[0].geometry {
+        Geometry::Mesh { filename, scale } => {
+            assert_eq!(filename, "meshes/hand.stl");
+            assert_eq!(scale.as_ref().unwrap()[..], [0.001, 0.001, 0.001]);
+        }
+        other => panic!("expected a mesh, got {other:?}"),
+    }
+}
+
+/// Test bodies and joints sharing a name keep it, as links and joints don't clash in URDF.
+#[test]
+fn test_mjcf_body_and_joint_names() {
+    let (robot, _) = convert(
+        r#"<mujoco model="leg">
+  <worldbody>
+    <body name="thigh">
+      <body name="knee">
+        <joint name="knee" range="-1 1"/>
+        <body name="foot">
+          <joint name="ankle" type="ball"/>
+        </body>
+      </body>
+    </body>
+  </worldbody>
+</mujoco>"#,
+    );
+
+    let links: Vec<&str> = robot.links.iter().map(|l| l.name.as_str()).collect();
+    assert_eq!(links, ["thigh", "knee", "foot"]);
+    let knee = robot.joints.iter().find(|j| j.name == "knee").unwrap();
+    assert_eq!(
+        (knee.parent.link.as_str(), knee.child.link.as_str()),
+        ("thigh", "knee")
+    );
+    // Unsupported joint types become fixed joints under their own name
+    let ankle = robot.joints.iter().find(|j| j.name == "ankle").unwrap();
+    assert!(matches!(ankle.joint_type, JointType::Fixed));
+}
+
+/// Test the XBot MJCF converts to the same moving joints as the XBot URDF.
+#[test]
+fn test_mjcf_matches_urdf_joints() {
+    let mjcf_path = format!("{BASE_PATH}/tests/assets/urdf_examples/XBot/mjcf/XBot-L.xml");
+    let urdf_path = format!("{BASE_PATH}/tests/assets/urdf_examples/XBot/urdf/XBot-L.urdf");
+    let (from_mjcf, _) = convert(&std::fs::read_to_string(mjcf_path).unwrap());
+    let from_urdf = urdf_rs::read_file(urdf_path).unwrap();
+
+    let moving_joints = from_mjcf
+        .joints
+        .iter()
+        .filter(|j| !matches!(j.joint_type, JointType::Fixed));
+    for joint in moving_joints {
+        let reference = from_urdf
+            .joints
+            .iter()
+            .find(|j| j.name == joint.name)
+            .unwrap_or_else(|| panic!("joint {} not in the URDF", joint.name));
+        assert_eq!(joint.parent.link, reference.parent.link, "{}", joint.name);
+        assert_eq!(joint.child.link, reference.child.link, "{}", joint.name);
+        for i in 0..3 {
+            assert!(
+                (joint.origin.xyz[i] - reference.origin.xyz[i]).abs() < 1e-4,
+                "{} origin mismatch",
+                joint.name
+            );
+        }
+        assert!(
+            (joint.limit.lower - reference.limit.lower).abs() < 1e-3,
+            "{} limit mismatch",
+            joint.name
+        );
+    }
+}
@@ -0,0 +1,144 @@
+// tests/test_resource_path_utils.rs
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use krecviz::utils::resource_path_utils::ResourceResolver;
+
+/// Create an empty file (and its parent directories).
+fn touch(path: &Path) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, b"").unwrap();
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap()
+}
+
+/// Test relative paths (including `..`) resolve against the description's directory.
+#[test]
+fn test_resolve_relative_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let mesh = dir.path().join("robot/meshes/base.stl");
+    touch(&mesh);
+    touch(&dir.path().join("robot/urdf/robot.urdf"));
+
+    let resolver = ResourceResolver::with_package_paths(&dir.path().join("robot/urdf"), vec![]);
+    assert_eq!(
+        resolver.resolve("../meshes/base.stl").unwrap(),
+        canonical(&mesh)
+    );
+}
+
+/// Test `file://` URIs and plain absolute paths.
+#[test]
+fn test_resolve_file_uri_and_absolute_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let mesh = dir.path().join("meshes/base.stl");
+    touch(&mesh);
+
+    let resolver = ResourceResolver::with_package_paths(Path::new("/nonexistent"), vec![]);
+    let uri = format!("file://{}", mesh.display());
+    assert_eq!(resolver.resolve(&uri).unwrap(), canonical(&mesh));
+    assert_eq!(
+        resolver.resolve(mesh.to_str().unwrap()).unwrap(),
+        canonical(&mesh)
+    );
+}
+
+/// Test `package://` URIs are found under a search root, or in a root that is the package itself.
+#[test]
+fn test_resolve_package_uri_from_search_paths() {
+    let dir = tempfile::tempdir().unwrap();
+    let workspace = dir.path().join("ws/src");
+    let mesh = workspace.join("my_robot_description/meshes/foo.stl");
+    touch(&mesh);
+    let uri = "package://my_robot_description/meshes/foo.stl";
+
+    let resolver =
+        ResourceResolver::with_package_paths(Path::new("/nonexistent"), vec![workspace.clone()]);
+    assert_eq!(resolver.resolve(uri).unwrap(), canonical(&mesh));
+
+    let resolver = ResourceResolver::with_package_paths(
+        Path::new("/nonexistent"),
+        vec![workspace.join("my_robot_description")],
+    );
+    assert_eq!(resolver.resolve(uri).unwrap(), canonical(&mesh));
+}
+
+/// Test packages nested deeper in a search root are found by the `<name>` in their
+/// `package.xml`, whatever their directory is called.
+#[test]
+fn test_resolve_package_uri_from_package_xml() {
+    let dir = tempfile::tempdir().unwrap();
+    let workspace = dir.path().join("ws/src");
+    let package = workspace.join("robots/description");
+    let mesh = package.join("meshes/foo.stl");
+    touch(&mesh);
+    fs::write(
+        package.join("package.xml"),
+        "<package format=\"3\">\n  <name> my_robot_description </name>\n</package>",
+    )
+    .unwrap();
+    // Ignored directories and packages inside packages aren't searched
+    touch(&workspace.join("ignored/CATKIN_IGNORE"));
+    fs::write(
+        workspace.join("ignored/package.xml"),
+        "<package><name>ignored_robot</name></package>",
+    )
+    .unwrap();
+    fs::write(
+        package.join("meshes/package.xml"),
+        "<package><name>inner_robot</name></package>",
+    )
+    .unwrap();
+
+    let resolver = ResourceResolver::with_package_paths(Path::new("/nonexistent"), vec![workspace]);
+    assert_eq!(
+        resolver
+            .resolve("package://my_robot_description/meshes/foo.stl")
+            .unwrap(),
+        canonical(&mesh)
+    );
+    assert!(resolver
+        .resolve("package://ignored_robot/package.xml")
+        .is_err());
+    assert!(resolver
+        .resolve("package://inner_robot/package.xml")
+        .is_err());
+}
+
+/// Test a `package://` URI falls back to the package the URDF itself lives in.
+#[test]
+fn test_resolve_package_uri_from_urdf_location() {
+    let dir = tempfile::tempdir().unwrap();
+    let package = dir.path().join("my_robot_description");
+    let mesh = package.join("meshes/foo.stl");
+    touch(&mesh);
+
+    let resolver = ResourceResolver::with_package_paths(&package.join("urdf"), vec![]);
+    assert_eq!(
+        resolver
+            .resolve("package://my_robot_description/meshes/foo.stl")
+            .unwrap(),
+        canonical(&mesh)
+    );
+}
+
+/// Test a missing file reports every candidate that was tried.
+#[test]
+fn test_resolve_missing_lists_candidates() {
+    let dir = tempfile::tempdir().unwrap();
+    let roots = vec![dir.path().join("a"), dir.path().join("b")];
+    let resolver = ResourceResolver::with_package_paths(dir.path(), roots.clone());
+
+    let err = resolver
+        .resolve("package://pkg/meshes/missing.stl")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("package://pkg/meshes/missing.stl"), "{err}");
+    for root in roots {
+        let candidate = root.join("pkg/meshes/missing.stl");
+        assert!(err.contains(&candidate.display().to_string()), "{err}");
+    }
+}
@@ -0,0 +1,148 @@
+// tests/test_robot_model.rs
+
+use std::collections::HashMap;
+use std::f64::consts::FRAC_PI_2;
+
+use krecviz::utils::urdf_bfs_utils::build_link_bfs_map;
+use krecviz::{RobotModel, UrdfLogOptions};
+use nalgebra as na;
+use urdf_rs::read_from_string;
+
+const EPSILON: f64 = 1e-9;
+
+/// The planar arm of `arm.urdf`: a shoulder and an elbow turning about z, a tool on a
+/// prismatic joint and a finger mimicking it.
+const ARM_URDF_PATH: &str = "tests/assets/urdf_examples/arm/arm.urdf";
+
+fn arm() -> RobotModel {
+    RobotModel::load(ARM_URDF_PATH, &UrdfLogOptions::default()).expect("Failed to load arm.urdf")
+}
+
+fn positions(values: &[(&str, f64)]) -> HashMap<String, f64> {
+    values
+        .iter()
+        .map(|(name, value)| (name.to_string(), *value))
+        .collect()
+}
+
+fn assert_translation(pose: &na::Isometry3<f64>, expected: [f64; 3]) {
+    let t = pose.translation.vector;
+    assert!(
+        (t - na::Vector3::from(expected)).norm() < EPSILON,
+        "expected {expected:?}, got {t:?}"
+    );
+}
+
+/// Test the model lists the tree in BFS order.
+#[test]
+fn test_model_structure() {
+    let model = arm();
+    assert_eq!(model.root_link(), "base");
+    assert_eq!(
+        model.link_names(),
+        ["base", "upper", "lower", "tool", "finger"]
+    );
+    assert_eq!(
+        model.joint_names(),
+        ["elbow", "finger_slide", "shoulder", "slide"]
+    );
+    assert_eq!(model.joint("elbow").unwrap().child_link, "lower");
+    assert!(model.joint("wrist").is_none());
+}
+
+/// Test the tree is checked: links off the root are left out, the root can be overridden,
+/// and strict mode rejects a robot with several roots.
+#[test]
+fn test_model_root_link() {
+    let urdf = std::fs::read_to_string(ARM_URDF_PATH)
+        .expect("Failed to read arm.urdf")
+        .replace(
+            "</robot>",
+            r#"<link name="stand"/><link name="camera"/>
+  <joint name="mount" type="fixed"><parent link="stand"/><child link="camera"/></joint>
+</robot>"#,
+        );
+    let robot = read_from_string(&urdf).expect("Failed to parse URDF string");
+
+    let model = RobotModel::from_robot(&robot, &UrdfLogOptions::default()).unwrap();
+    assert_eq!(model.root_link(), "base");
+    assert!(model.link_pose("camera", &HashMap::new()).is_none());
+
+    let options = UrdfLogOptions {
+        root_link: Some("stand".to_string()),
+        ..Default::default()
+    };
+    let model = RobotModel::from_robot(&robot, &options).unwrap();
+    assert_eq!(model.link_names(), ["stand", "camera"]);
+    assert_eq!(model.joint_names(), ["mount"]);
+
+    let options = UrdfLogOptions {
+        strict: true,
+        ..Default::default()
+    };
+    assert!(RobotModel::from_robot(&robot, &options).is_err());
+}
+
+/// Test link poses follow revolute and prismatic joints, with missing joints at zero.
+#[test]
+fn test_forward_kinematics() {
+    let model = arm();
+
+    let poses = model.forward_kinematics(&HashMap::new());
+    assert_eq!(poses.len(), 5);
+    assert_translation(&poses["base"], [0.0, 0.0, 0.0]);
+    assert_translation(&poses["tool"], [2.0, 0.0, 1.0]);
+
+    // The shoulder turns the arm onto +y, the elbow folds the lower link back onto -x
+    let poses =
+        model.forward_kinematics(&positions(&[("shoulder", FRAC_PI_2), ("elbow", FRAC_PI_2)]));
+    assert_translation(&poses["lower"], [0.0, 1.0, 1.0]);
+    assert_translation(&poses["tool"], [-1.0, 1.0, 1.0]);
+    let tool_x = poses["tool"].rotation * na::Vector3::x();
+    assert!((tool_x - na::Vector3::new(-1.0, 0.0, 0.0)).norm() < EPSILON);
+
+    // The prismatic axis is normalized, so the slide moves 0.25 m
+    let tool = model
+        .link_pose("tool", &positions(&[("slide", 0.25)]))
+        .unwrap();
+    assert_translation(&tool, [2.25, 0.0, 1.0]);
+    assert!(model.link_pose("wrist", &HashMap::new()).is_none());
+}
+
+/// Test mimic joints follow their leader unless given a position.
+#[test]
+fn test_forward_kinematics_mimic_joints() {
+    let model = arm();
+
+    let poses = model.forward_kinematics(&positions(&[("slide", 0.25)]));
+    assert_translation(&poses["finger"], [2.25, 0.6, 1.0]);
+
+    let poses = model.forward_kinematics(&positions(&[("slide", 0.25), ("finger_slide", 0.0)]));
+    assert_translation(&poses["finger"], [2.25, 0.0, 1.0]);
+}
+
+/// Test the zero pose matches the BFS global transforms of a real URDF.
+#[test]
+fn test_zero_pose_matches_bfs_transforms() {
+    let urdf_path = "tests/assets/urdf_examples/manual_urdf/manual_example.urdf";
+    let model = RobotModel::load(urdf_path, &UrdfLogOptions::default())
+        .expect("Failed to load manual_example.urdf");
+    let (link_bfs_map, _) = build_link_bfs_map(
+        &urdf_rs::read_file(urdf_path).expect("Failed to parse URDF"),
+        model.root_link(),
+    );
+
+    let poses = model.forward_kinematics(&HashMap::new());
+    for (link_name, bfs_data) in &link_bfs_map {
+        let expected = bfs_data.global_transform;
+        let actual = poses[link_name].to_homogeneous();
+        for row in 0..4 {
+            for col in 0..4 {
+                assert!(
+                    (actual[(row, col)] - expected[row * 4 + col] as f64).abs() < 1e-5,
+                    "{link_name}: expected {expected:?}, got {actual}"
+                );
+            }
+        }
+    }
+}
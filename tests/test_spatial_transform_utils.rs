@@ -226,4 +226,4 @@ mod test_spatial_transform_utils {
         ];
         assert_arr_eq(&final_tf_row_major, &expected_tf, "make_4x4_from_rotation_and_translation");
     }
-}
\ No newline at end of file
+}
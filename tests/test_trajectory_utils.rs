@@ -0,0 +1,41 @@
+// tests/test_trajectory_utils.rs
+
+use krecviz::utils::trajectory_utils::{trail_color, Trail};
+
+/// Test a bounded trail keeps only its most recent points, oldest first.
+#[test]
+fn test_bounded_trail() {
+    let mut trail = Trail::new(3);
+    assert!(trail.is_bounded());
+    assert!(trail.is_empty());
+    assert_eq!(trail.last(), None);
+
+    for i in 0..5 {
+        trail.push([i as f32, 0.0, 0.0]);
+    }
+    assert_eq!(trail.len(), 3);
+    assert_eq!(
+        trail.points(),
+        vec![[2.0, 0.0, 0.0], [3.0, 0.0, 0.0], [4.0, 0.0, 0.0]]
+    );
+    assert_eq!(trail.last(), Some([4.0, 0.0, 0.0]));
+}
+
+/// Test a zero trail length keeps every point.
+#[test]
+fn test_unbounded_trail() {
+    let mut trail = Trail::new(0);
+    assert!(!trail.is_bounded());
+    for i in 0..1000 {
+        trail.push([0.0, 0.0, i as f32]);
+    }
+    assert_eq!(trail.len(), 1000);
+    assert_eq!(trail.points()[0], [0.0, 0.0, 0.0]);
+}
+
+/// Test neighbouring trails get different colors and the palette cycles.
+#[test]
+fn test_trail_color() {
+    assert_ne!(trail_color(0), trail_color(1));
+    assert_eq!(trail_color(0), trail_color(6));
+}
@@ -0,0 +1,136 @@
+// tests/test_urdf_tree_utils.rs
+
+use krecviz::utils::urdf_bfs_utils::build_link_bfs_map;
+use krecviz::utils::urdf_tree_utils::{check_tree, prune_to_tree, TreeIssue};
+use urdf_rs::{read_from_string, Robot};
+
+/// A robot with the given links and (name, parent, child) fixed joints.
+fn robot(links: &[&str], joints: &[(&str, &str, &str)]) -> Robot {
+    let links: String = links
+        .iter()
+        .map(|name| format!(r#"<link name="{name}"/>"#))
+        .collect();
+    let joints: String = joints
+        .iter()
+        .map(|(name, parent, child)| {
+            format!(
+                r#"<joint name="{name}" type="fixed"><parent link="{parent}"/><child link="{child}"/></joint>"#
+            )
+        })
+        .collect();
+    read_from_string(&format!(r#"<robot name="test">{links}{joints}</robot>"#))
+        .expect("Failed to parse URDF string")
+}
+
+fn link_names(robot: &Robot) -> Vec<&str> {
+    robot.links.iter().map(|l| l.name.as_str()).collect()
+}
+
+/// Test a well-formed tree has no issues and is left as is.
+#[test]
+fn test_valid_tree() {
+    let robot = robot(
+        &["base", "arm", "hand"],
+        &[("shoulder", "base", "arm"), ("wrist", "arm", "hand")],
+    );
+    let report = check_tree(&robot, None).unwrap();
+    assert_eq!(report.root, "base");
+    assert!(report.issues.is_empty(), "{:?}", report.issues);
+
+    let pruned = prune_to_tree(&robot, &report.root);
+    assert_eq!(link_names(&pruned), ["base", "arm", "hand"]);
+    assert_eq!(pruned.joints.len(), 2);
+}
+
+/// Test several roots are reported, the first one is picked unless overridden, and the
+/// other subtrees are orphaned and pruned.
+#[test]
+fn test_multiple_roots() {
+    let robot = robot(
+        &["base", "arm", "stand", "camera"],
+        &[("shoulder", "base", "arm"), ("mount", "stand", "camera")],
+    );
+    let report = check_tree(&robot, None).unwrap();
+    assert_eq!(report.root, "base");
+    assert_eq!(
+        report.issues,
+        [
+            TreeIssue::MultipleRoots(vec!["base".to_string(), "stand".to_string()]),
+            TreeIssue::Orphaned {
+                root: "base".to_string(),
+                links: vec!["stand".to_string(), "camera".to_string()],
+            },
+        ]
+    );
+
+    let report = check_tree(&robot, Some("stand")).unwrap();
+    assert_eq!(report.root, "stand");
+    assert!(matches!(
+        report.issues.as_slice(),
+        [TreeIssue::Orphaned { links, .. }] if links == &["base", "arm"]
+    ));
+    let pruned = prune_to_tree(&robot, "stand");
+    assert_eq!(link_names(&pruned), ["stand", "camera"]);
+    assert_eq!(build_link_bfs_map(&pruned, "stand").1, ["stand", "camera"]);
+}
+
+/// Test cycles, links with two parents and unknown links are reported, and pruning leaves
+/// a tree the BFS can walk.
+#[test]
+fn test_broken_tree() {
+    let robot = robot(
+        &["base", "a", "b"],
+        &[
+            ("base_a", "base", "a"),
+            ("a_b", "a", "b"),
+            ("b_a", "b", "a"),
+            ("base_ghost", "base", "ghost"),
+        ],
+    );
+    let report = check_tree(&robot, None).unwrap();
+    assert_eq!(report.root, "base");
+    assert_eq!(
+        report.issues,
+        [
+            TreeIssue::UnknownLink {
+                joint: "base_ghost".to_string(),
+                link: "ghost".to_string(),
+            },
+            TreeIssue::MultipleParents {
+                link: "a".to_string(),
+                joints: vec!["base_a".to_string(), "b_a".to_string()],
+            },
+            TreeIssue::Cycle(vec!["a".to_string(), "b".to_string(), "a".to_string()]),
+        ]
+    );
+    assert_eq!(
+        report.issues[2].to_string(),
+        "joints form a cycle: a -> b -> a"
+    );
+
+    let pruned = prune_to_tree(&robot, &report.root);
+    let joint_names: Vec<&str> = pruned.joints.iter().map(|j| j.name.as_str()).collect();
+    assert_eq!(joint_names, ["base_a", "a_b"]);
+    assert_eq!(
+        build_link_bfs_map(&pruned, &report.root).1,
+        ["base", "a", "b"]
+    );
+
+    // Without any parentless link the first one is used
+    let ring = self::robot(&["a", "b"], &[("a_b", "a", "b"), ("b_a", "b", "a")]);
+    let report = check_tree(&ring, None).unwrap();
+    assert_eq!(report.root, "a");
+    assert!(report.issues.contains(&TreeIssue::NoRoot));
+}
+
+/// Test root overrides must name a link without a parent joint.
+#[test]
+fn test_invalid_root_override() {
+    let robot = robot(&["base", "arm"], &[("shoulder", "base", "arm")]);
+
+    let err = check_tree(&robot, Some("gripper")).unwrap_err();
+    assert!(err.to_string().contains("gripper"), "{err}");
+
+    let err = check_tree(&robot, Some("arm")).unwrap_err();
+    assert!(err.to_string().contains("shoulder"), "{err}");
+}
@@ -0,0 +1,97 @@
+// tests/test_video_utils.rs
+
+use krec::KRecFrame;
+use krecviz::utils::video_utils::{
+    frame_at_rate, frame_at_time, parse_frame_rate, JpegFrames, VideoPosition, VideoSync,
+};
+
+fn frames(timestamps_and_numbers: &[(u64, u64)]) -> Vec<KRecFrame> {
+    timestamps_and_numbers
+        .iter()
+        .map(|&(video_timestamp, video_frame_number)| KRecFrame {
+            video_timestamp,
+            video_frame_number,
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Test frame numbers win over timestamps, and the frame index is the last resort.
+#[test]
+fn test_video_sync_detect() {
+    let numbered = frames(&[(1_000, 0), (21_000, 1)]);
+    assert_eq!(VideoSync::detect(&numbered), VideoSync::FrameNumber);
+
+    let timed = frames(&[(1_000, 0), (21_000, 0)]);
+    assert_eq!(VideoSync::detect(&timed), VideoSync::Timestamp);
+    assert_eq!(
+        VideoSync::Timestamp.position(1, &timed[1], 1_000),
+        VideoPosition::Time(20_000)
+    );
+
+    let bare = frames(&[(0, 0), (0, 0)]);
+    assert_eq!(VideoSync::detect(&bare), VideoSync::FrameIndex);
+    assert_eq!(
+        VideoSync::FrameIndex.position(1, &bare[1], 0),
+        VideoPosition::Frame(1)
+    );
+}
+
+/// Test a 50 Hz control loop lines up with a 30 fps camera instead of drifting ahead.
+#[test]
+fn test_frame_at_rate() {
+    let video_frames: Vec<usize> = (0..6u64)
+        .map(|i| frame_at_rate(i * 20_000_000, 30.0))
+        .collect();
+    assert_eq!(video_frames, [0, 0, 1, 1, 2, 3]);
+    // One second in at 29.97 fps
+    assert_eq!(frame_at_rate(1_000_000_000, 30000.0 / 1001.0), 29);
+}
+
+/// Test picking the frame on screen from presentation times that don't start at zero.
+#[test]
+fn test_frame_at_time() {
+    let timestamps = [500, 1_500, 2_500];
+    assert_eq!(frame_at_time(&timestamps, 0), Some(0));
+    assert_eq!(frame_at_time(&timestamps, 999), Some(0));
+    assert_eq!(frame_at_time(&timestamps, 1_000), Some(1));
+    assert_eq!(frame_at_time(&timestamps, 10_000), Some(2));
+    assert_eq!(frame_at_time(&timestamps, -1), None);
+    assert_eq!(frame_at_time(&[], 0), None);
+}
+
+#[test]
+fn test_parse_frame_rate() {
+    assert_eq!(parse_frame_rate("25\n"), Some(25.0));
+    assert!((parse_frame_rate("30000/1001").unwrap() - 29.97).abs() < 1e-3);
+    assert_eq!(parse_frame_rate("0/0"), None);
+    assert_eq!(parse_frame_rate("N/A"), None);
+}
+
+/// Test concatenated JPEGs are split at their end markers, not at stuffed 0xFF bytes,
+/// restart markers or marker-like bytes inside segments.
+#[test]
+fn test_jpeg_frames() {
+    let jpeg = |scan: &[u8]| -> Vec<u8> {
+        let mut jpeg = vec![0xFF, 0xD8];
+        // A comment segment holding an end of image marker
+        jpeg.extend_from_slice(&[0xFF, 0xFE, 0x00, 0x04, 0xFF, 0xD9]);
+        jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02]);
+        jpeg.extend_from_slice(scan);
+        jpeg.extend_from_slice(&[0xFF, 0xD9]);
+        jpeg
+    };
+    let first = jpeg(&[0x12, 0xFF, 0x00, 0x34, 0xFF, 0xD0, 0x56]);
+    let second = jpeg(&[0x78]);
+    let stream = [first.clone(), second.clone()].concat();
+
+    let images: Vec<Vec<u8>> = JpegFrames::new(stream.as_slice())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(images, [first, second]);
+
+    let mut truncated = JpegFrames::new(&stream[..10]);
+    assert!(truncated.next().unwrap().is_err());
+    assert!(truncated.next().is_none());
+    assert!(JpegFrames::new(&b"not a jpeg"[..]).next().unwrap().is_err());
+}
@@ -2,7 +2,7 @@ use anyhow::Result;
 use env_logger;
 
 // Import the necessary functions from your crate
-use krecviz::parse_and_log_urdf_hierarchy;
+use krecviz::{parse_and_log_urdf_hierarchy, UrdfLogOptions};
 
 const BASE_PATH: &str = env!("CARGO_MANIFEST_DIR");
 const URDF_BASE_PATH: &str = "tests/assets/urdf_examples";
@@ -41,14 +41,14 @@ fn test_run_visualization_gpr_adhoc() -> Result<()> {
     let _ = env_logger::try_init();
     let rec = rerun::RecordingStreamBuilder::new("test_visualization_adhoc").spawn()?;
 
-    parse_and_log_urdf_hierarchy(&get_urdf_path("gpr"), &rec)?;
+    parse_and_log_urdf_hierarchy(&get_urdf_path("gpr"), &UrdfLogOptions::default(), &rec)?;
     Ok(())
 }
 
 // #[test]
 // fn test_run_visualization_simple_adhoc() -> Result<()> {
 //     let rec = rerun::RecordingStreamBuilder::new("test_visualization_adhoc").spawn()?;
-//     parse_and_log_urdf_hierarchy(&get_urdf_path("simple"), &rec)?;
+//     parse_and_log_urdf_hierarchy(&get_urdf_path("simple"), &UrdfLogOptions::default(), &rec)?;
 //     Ok(())
 // }
 
@@ -56,14 +56,14 @@ fn test_run_visualization_gpr_adhoc() -> Result<()> {
 // fn test_run_manual_urdf_adhoc() -> Result<()> {
 //     let _ = env_logger::try_init();
 //     let rec = rerun::RecordingStreamBuilder::new("test_visualization_adhoc").spawn()?;
-//     parse_and_log_urdf_hierarchy(&get_urdf_path("manual_urdf"), &rec)?;
+//     parse_and_log_urdf_hierarchy(&get_urdf_path("manual_urdf"), &UrdfLogOptions::default(), &rec)?;
 //     Ok(())
 // }
 
 // #[test]
 // fn test_run_visualization_simple_onshape_2_joints_adhoc() -> Result<()> {
 //     let rec = rerun::RecordingStreamBuilder::new("test_visualization_adhoc").spawn()?;
-//     parse_and_log_urdf_hierarchy(&get_urdf_path("simple_onshape_2_joints"), &rec)?;
+//     parse_and_log_urdf_hierarchy(&get_urdf_path("simple_onshape_2_joints"), &UrdfLogOptions::default(), &rec)?;
 //     Ok(())
 // }
 
@@ -71,20 +71,20 @@ fn test_run_visualization_gpr_adhoc() -> Result<()> {
 // fn test_run_visualization_simple_onshape_2_joints_asymmetrical_adhoc() -> Result<()> {
 //     let _ = env_logger::try_init();
 //     let rec = rerun::RecordingStreamBuilder::new("test_visualization_adhoc").spawn()?;
-//     parse_and_log_urdf_hierarchy(&get_urdf_path("simple_onshape_2_joints_asymmetrical"), &rec)?;
+//     parse_and_log_urdf_hierarchy(&get_urdf_path("simple_onshape_2_joints_asymmetrical"), &UrdfLogOptions::default(), &rec)?;
 //     Ok(())
 // }
 
 // #[test]
 // fn test_run_visualization_simple_onshape_4_joints_adhoc() -> Result<()> {
 //     let rec = rerun::RecordingStreamBuilder::new("test_visualization_adhoc").spawn()?;
-//     parse_and_log_urdf_hierarchy(&get_urdf_path("simple_onshape_4_joints"), &rec)?;
+//     parse_and_log_urdf_hierarchy(&get_urdf_path("simple_onshape_4_joints"), &UrdfLogOptions::default(), &rec)?;
 //     Ok(())
 // }
 
 // #[test]
 // fn test_run_visualization_xbot_adhoc() -> Result<()> {
 //     let rec = rerun::RecordingStreamBuilder::new("test_visualization_adhoc").spawn()?;
-//     parse_and_log_urdf_hierarchy(&get_urdf_path("xbot"), &rec)?;
+//     parse_and_log_urdf_hierarchy(&get_urdf_path("xbot"), &UrdfLogOptions::default(), &rec)?;
 //     Ok(())
 // }
@@ -0,0 +1,295 @@
+// tests/test_xacro_utils.rs
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use krecviz::utils::resource_path_utils::ResourceResolver;
+use krecviz::utils::xacro_utils::{expand_xacro, expand_xacro_file, is_xacro};
+
+fn expand(xml: &str) -> String {
+    expand_with_args(xml, &[])
+}
+
+fn expand_with_args(xml: &str, args: &[(&str, &str)]) -> String {
+    let resolver = ResourceResolver::with_package_paths(Path::new("."), vec![]);
+    let args: HashMap<String, String> = args
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    expand_xacro(xml, &resolver, &args).unwrap()
+}
+
+/// Value of `attribute` on the first element named `tag`.
+fn attribute(xml: &str, tag: &str, attribute: &str) -> String {
+    let doc = roxmltree::Document::parse(xml).unwrap();
+    doc.descendants()
+        .find(|n| n.has_tag_name(tag))
+        .unwrap_or_else(|| panic!("no <{tag}> in {xml}"))
+        .attribute(attribute)
+        .unwrap_or_else(|| panic!("no {attribute} on <{tag}> in {xml}"))
+        .to_string()
+}
+
+/// Names of all `<link>` elements, in document order.
+fn link_names(xml: &str) -> Vec<String> {
+    let doc = roxmltree::Document::parse(xml).unwrap();
+    doc.descendants()
+        .filter(|n| n.has_tag_name("link"))
+        .map(|n| n.attribute("name").unwrap().to_string())
+        .collect()
+}
+
+/// Test xacro documents are recognized by their namespace.
+#[test]
+fn test_is_xacro() {
+    assert!(is_xacro(
+        r#"<robot name="r" xmlns:xacro="http://www.ros.org/wiki/xacro"/>"#
+    ));
+    assert!(!is_xacro(r#"<robot name="r"><link name="a"/></robot>"#));
+}
+
+/// Test properties, `${}` arithmetic, math functions and string building.
+#[test]
+fn test_properties_and_expressions() {
+    let urdf = expand(
+        r#"<robot name="r" xmlns:xacro="http://www.ros.org/wiki/xacro">
+          <xacro:property name="width" value="0.2"/>
+          <xacro:property name="height" value="${width * 3}"/>
+          <xacro:property name="prefix" value="left"/>
+          <link name="${prefix}_base">
+            <visual>
+              <origin xyz="0 0 ${height / 2}" rpy="0 ${radians(90)} ${-pi/2}"/>
+              <geometry><box size="${width} ${width} ${height}"/></geometry>
+            </visual>
+          </link>
+          <joint name="j" type="${'revolute' if width > 0.1 else 'fixed'}">
+            <limit lower="${-2**2}" upper="${7 // 2 + 7 % 2}" effort="1" velocity="1"/>
+          </joint>
+        </robot>"#,
+    );
+
+    assert_eq!(link_names(&urdf), ["left_base"]);
+    assert_eq!(
+        attribute(&urdf, "box", "size"),
+        "0.2 0.2 0.6000000000000001"
+    );
+    assert_eq!(attribute(&urdf, "origin", "xyz"), "0 0 0.30000000000000004");
+    assert_eq!(
+        attribute(&urdf, "origin", "rpy"),
+        format!(
+            "0 {} {}",
+            std::f64::consts::FRAC_PI_2,
+            -std::f64::consts::FRAC_PI_2
+        )
+    );
+    assert_eq!(attribute(&urdf, "joint", "type"), "revolute");
+    assert_eq!(attribute(&urdf, "limit", "lower"), "-4");
+    assert_eq!(attribute(&urdf, "limit", "upper"), "4");
+    assert!(!urdf.contains("xacro"), "{urdf}");
+}
+
+/// Test macros with plain, default, inherited and block parameters.
+#[test]
+fn test_macros() {
+    let urdf = expand(
+        r#"<robot name="r" xmlns:xacro="http://www.ros.org/wiki/xacro">
+          <xacro:property name="mass" value="2"/>
+          <xacro:macro name="leg" params="side reflect:=1 mass:=^ *origin **extra">
+            <link name="${side}_leg">
+              <inertial>
+                <mass value="${mass}"/>
+                <xacro:insert_block name="origin"/>
+              </inertial>
+              <xacro:insert_block name="extra"/>
+            </link>
+            <joint name="${side}_hip" type="revolute">
+              <origin xyz="0 ${reflect * 0.1} 0"/>
+            </joint>
+          </xacro:macro>
+          <xacro:leg side="left">
+            <origin xyz="0 0 ${mass}"/>
+            <extra><visual><geometry><sphere radius="0.1"/></geometry></visual></extra>
+          </xacro:leg>
+          <xacro:leg side="right" reflect="-1" mass="3">
+            <origin xyz="0 0 0"/>
+            <extra/>
+          </xacro:leg>
+        </robot>"#,
+    );
+
+    assert_eq!(link_names(&urdf), ["left_leg", "right_leg"]);
+    let doc = roxmltree::Document::parse(&urdf).unwrap();
+    let link = |name: &str| {
+        doc.descendants()
+            .find(|n| n.has_tag_name("link") && n.attribute("name") == Some(name))
+            .unwrap()
+    };
+    let child_attribute = |node: roxmltree::Node, tag: &str, attr: &str| {
+        node.descendants()
+            .find(|n| n.has_tag_name(tag))
+            .and_then(|n| n.attribute(attr))
+            .map(str::to_string)
+    };
+
+    let left = link("left_leg");
+    assert_eq!(child_attribute(left, "mass", "value").unwrap(), "2");
+    // The block was expanded where it was written
+    assert_eq!(child_attribute(left, "origin", "xyz").unwrap(), "0 0 2");
+    assert_eq!(child_attribute(left, "sphere", "radius").unwrap(), "0.1");
+
+    let right = link("right_leg");
+    assert_eq!(child_attribute(right, "mass", "value").unwrap(), "3");
+    assert!(child_attribute(right, "sphere", "radius").is_none());
+
+    let hips: Vec<String> = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("joint"))
+        .map(|j| child_attribute(j, "origin", "xyz").unwrap())
+        .collect();
+    assert_eq!(hips, ["0 0.1 0", "0 -0.1 0"]);
+}
+
+/// Test `xacro:if`/`xacro:unless` with booleans, numbers and expressions.
+#[test]
+fn test_conditionals() {
+    let urdf = expand(
+        r#"<robot name="r" xmlns:xacro="http://www.ros.org/wiki/xacro">
+          <xacro:property name="use_arm" value="true"/>
+          <xacro:property name="n" value="2"/>
+          <xacro:if value="${use_arm}"><link name="arm"/></xacro:if>
+          <xacro:unless value="${use_arm}"><link name="no_arm"/></xacro:unless>
+          <xacro:if value="0"><link name="zero"/></xacro:if>
+          <xacro:if value="${n == 2 and not use_arm}"><link name="never"/></xacro:if>
+          <xacro:unless value="${n > 2}"><link name="small"/></xacro:unless>
+        </robot>"#,
+    );
+    assert_eq!(link_names(&urdf), ["arm", "small"]);
+}
+
+/// Test `$(arg)` takes CLI-supplied args over `xacro:arg` defaults.
+#[test]
+fn test_args() {
+    let xml = r#"<robot name="r" xmlns:xacro="http://www.ros.org/wiki/xacro">
+          <xacro:arg name="prefix" default="robot"/>
+          <xacro:arg name="with_gripper" default="false"/>
+          <link name="$(arg prefix)_base"/>
+          <xacro:if value="$(arg with_gripper)"><link name="$(arg prefix)_gripper"/></xacro:if>
+        </robot>"#;
+
+    assert_eq!(link_names(&expand(xml)), ["robot_base"]);
+    assert_eq!(
+        link_names(&expand_with_args(
+            xml,
+            &[("prefix", "kbot"), ("with_gripper", "true")]
+        )),
+        ["kbot_base", "kbot_gripper"]
+    );
+
+    let resolver = ResourceResolver::with_package_paths(Path::new("."), vec![]);
+    let err = expand_xacro(
+        r#"<robot name="r" xmlns:xacro="http://www.ros.org/wiki/xacro"><link name="$(arg missing)"/></robot>"#,
+        &resolver,
+        &HashMap::new(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("missing"), "{err}");
+}
+
+/// Test includes relative to the including file, through `$(find)`, and with `ns`.
+#[test]
+fn test_includes() {
+    let dir = tempfile::tempdir().unwrap();
+    let package = dir.path().join("my_robot_description");
+    fs::create_dir_all(package.join("urdf/parts")).unwrap();
+    fs::write(
+        package.join("urdf/parts/wheel.xacro"),
+        r#"<robot xmlns:xacro="http://www.ros.org/wiki/xacro">
+          <xacro:property name="radius" value="0.3"/>
+          <xacro:macro name="wheel" params="name">
+            <link name="${name}_wheel"><visual><geometry>
+              <cylinder radius="${radius}" length="0.1"/>
+            </geometry></visual></link>
+          </xacro:macro>
+        </robot>"#,
+    )
+    .unwrap();
+    fs::write(
+        package.join("urdf/common.xacro"),
+        r#"<robot xmlns:xacro="http://www.ros.org/wiki/xacro">
+          <xacro:property name="radius" value="0.5"/>
+        </robot>"#,
+    )
+    .unwrap();
+    let main = package.join("urdf/robot.urdf.xacro");
+    fs::write(
+        &main,
+        r#"<robot name="r" xmlns:xacro="http://www.ros.org/wiki/xacro">
+          <xacro:include filename="$(find my_robot_description)/urdf/common.xacro"/>
+          <xacro:include filename="parts/wheel.xacro" ns="w"/>
+          <link name="base"><visual><geometry><sphere radius="${radius}"/></geometry></visual></link>
+          <xacro:w.wheel name="front"/>
+        </robot>"#,
+    )
+    .unwrap();
+
+    let resolver = ResourceResolver::with_package_paths(&package.join("urdf"), vec![]);
+    let urdf = expand_xacro_file(&main, &resolver, &HashMap::new()).unwrap();
+
+    assert_eq!(link_names(&urdf), ["base", "front_wheel"]);
+    // The namespaced include doesn't shadow the top-level property
+    assert_eq!(attribute(&urdf, "sphere", "radius"), "0.5");
+    assert_eq!(attribute(&urdf, "cylinder", "radius"), "0.3");
+}
+
+/// Test a file including itself, directly or through another file, is an error rather
+/// than endless recursion.
+#[test]
+fn test_include_cycles() {
+    let dir = tempfile::tempdir().unwrap();
+    let include = |file: &str| {
+        format!(
+            r#"<robot name="r" xmlns:xacro="http://www.ros.org/wiki/xacro"><xacro:include filename="{file}"/></robot>"#
+        )
+    };
+    fs::write(dir.path().join("self.xacro"), include("self.xacro")).unwrap();
+    fs::write(dir.path().join("a.xacro"), include("b.xacro")).unwrap();
+    fs::write(dir.path().join("b.xacro"), include("sub/../a.xacro")).unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+
+    let resolver = ResourceResolver::with_package_paths(dir.path(), vec![]);
+    for file in ["self.xacro", "a.xacro"] {
+        let err = expand_xacro_file(&dir.path().join(file), &resolver, &HashMap::new())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("include cycle"), "{err}");
+    }
+    // From a string the cycle is caught one level later
+    let err = expand_xacro(&include("a.xacro"), &resolver, &HashMap::new())
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("include cycle"), "{err}");
+}
+
+/// Test errors for undefined properties and macros name the culprit.
+#[test]
+fn test_errors() {
+    let resolver = ResourceResolver::with_package_paths(Path::new("."), vec![]);
+    let args = HashMap::new();
+
+    let err = expand_xacro(
+        r#"<robot name="r" xmlns:xacro="http://www.ros.org/wiki/xacro"><link name="${nope}"/></robot>"#,
+        &resolver,
+        &args,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("nope"), "{err}");
+
+    let err = expand_xacro(
+        r#"<robot name="r" xmlns:xacro="http://www.ros.org/wiki/xacro"><xacro:nothing/></robot>"#,
+        &resolver,
+        &args,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("nothing"), "{err}");
+}